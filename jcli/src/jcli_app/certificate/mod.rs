@@ -3,6 +3,8 @@ mod new_owner_stake_delegation;
 mod new_stake_delegation;
 mod new_stake_pool_registration;
 mod new_stake_pool_retirement;
+mod new_update_proposal;
+mod new_update_vote;
 mod new_vote_cast;
 mod new_vote_plan;
 mod new_vote_tally;
@@ -19,7 +21,8 @@ use crate::jcli_app::utils::{
     io, key_parser,
     vote::{SharesError, VotePlanError},
 };
-use chain_impl_mockchain::block::BlockDate;
+use chain_core::property::Serialize as _;
+use chain_impl_mockchain::{block::BlockDate, fragment::Fragment};
 use jormungandr_lib::interfaces::{self, CertificateFromBech32Error, CertificateFromStrError};
 use std::{
     fmt::Display,
@@ -111,6 +114,10 @@ pub enum Error {
     SharesError(#[from] SharesError),
     #[error("expected decrypted private tally, found {found}")]
     PrivateTallyExpected { found: &'static str },
+    #[error("invalid update proposal configuration")]
+    UpdateProposalConfig(#[source] serde_yaml::Error),
+    #[error("cannot serialize the fragment")]
+    FragmentSerialization(#[source] std::io::Error),
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -165,6 +172,10 @@ pub enum NewArgs {
     EncryptedVoteTally(new_encrypted_vote_tally::EncryptedVoteTally),
     /// create a vote cast certificate
     VoteCast(new_vote_cast::VoteCastCmd),
+    /// create an update proposal of the blockchain parameters
+    UpdateProposal(new_update_proposal::UpdateProposalCmd),
+    /// create the vote of a BFT leader for an update proposal
+    UpdateVote(new_update_vote::UpdateVoteCmd),
 }
 
 #[derive(StructOpt)]
@@ -196,6 +207,8 @@ impl NewArgs {
             NewArgs::VoteTally(args) => args.exec()?,
             NewArgs::VoteCast(args) => args.exec()?,
             NewArgs::EncryptedVoteTally(args) => args.exec()?,
+            NewArgs::UpdateProposal(args) => args.exec()?,
+            NewArgs::UpdateVote(args) => args.exec()?,
         }
         Ok(())
     }
@@ -285,6 +298,15 @@ fn write_signed_cert(
     write_output(output, signedcert)
 }
 
+/// write the hex encoded fragment, update proposals and votes are
+/// fragments of their own and not certificates of a transaction
+fn write_fragment(output: Option<&Path>, fragment: Fragment) -> Result<(), Error> {
+    let bytes = fragment
+        .serialize_as_vec()
+        .map_err(Error::FragmentSerialization)?;
+    write_output(output, hex::encode(&bytes))
+}
+
 fn write_output<P>(output: Option<P>, data: impl Display) -> Result<(), Error>
 where
     P: AsRef<Path>,
@@ -0,0 +1,41 @@
+use crate::jcli_app::{
+    certificate::{write_fragment, Error},
+    utils::{io, key_parser::parse_pub_key},
+};
+use chain_crypto::{Ed25519, PublicKey};
+use jormungandr_lib::interfaces::{ProposalChanges, UpdateProposal};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// create an update proposal of the blockchain parameters
+///
+/// the changes of the parameters need to be provided. The output is the
+/// hex encoded fragment of the proposal, ready to be sent to a node.
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct UpdateProposalCmd {
+    /// the public key of the BFT leader submitting the proposal
+    #[structopt(name = "PROPOSER_ID", parse(try_from_str = parse_pub_key))]
+    proposer_id: PublicKey<Ed25519>,
+
+    /// the file containing the changes of the parameters (YAML). If no file
+    /// provided, it will be read from the standard input
+    input: Option<PathBuf>,
+
+    /// write the output to the given file or print it to the standard output if not defined
+    #[structopt(long = "output")]
+    output: Option<PathBuf>,
+}
+
+impl UpdateProposalCmd {
+    pub fn exec(self) -> Result<(), Error> {
+        let configuration = io::open_file_read(&self.input)?;
+        let changes: ProposalChanges =
+            serde_yaml::from_reader(configuration).map_err(Error::UpdateProposalConfig)?;
+        let proposal = UpdateProposal {
+            proposer_id: self.proposer_id.into(),
+            changes,
+        };
+        write_fragment(self.output.as_deref(), proposal.into())
+    }
+}
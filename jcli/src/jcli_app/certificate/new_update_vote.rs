@@ -0,0 +1,38 @@
+use crate::jcli_app::{
+    certificate::{write_fragment, Error},
+    utils::key_parser::parse_pub_key,
+};
+use chain_crypto::{Ed25519, PublicKey};
+use jormungandr_lib::{crypto::hash::Hash, interfaces::UpdateVote};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// create the vote of a BFT leader for an update proposal
+///
+/// The output is the hex encoded fragment of the vote, ready to be sent
+/// to a node.
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct UpdateVoteCmd {
+    /// the id of the update proposal, the id of its fragment (hex)
+    #[structopt(name = "PROPOSAL_ID")]
+    proposal_id: Hash,
+
+    /// the public key of the BFT leader voting for the proposal
+    #[structopt(name = "VOTER_ID", parse(try_from_str = parse_pub_key))]
+    voter_id: PublicKey<Ed25519>,
+
+    /// write the output to the given file or print it to the standard output if not defined
+    #[structopt(long = "output")]
+    output: Option<PathBuf>,
+}
+
+impl UpdateVoteCmd {
+    pub fn exec(self) -> Result<(), Error> {
+        let vote = UpdateVote {
+            proposal_id: self.proposal_id,
+            voter_id: self.voter_id.into(),
+        };
+        write_fragment(self.output.as_deref(), vote.into())
+    }
+}
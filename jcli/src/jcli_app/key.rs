@@ -13,6 +13,7 @@ use rand_chacha::ChaChaRng;
 use std::{
     io::{Read, Write},
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use structopt::{clap::arg_enum, StructOpt};
 use thiserror::Error;
@@ -108,9 +109,10 @@ pub struct ToBytes {
 pub struct Generate {
     /// Type of a private key
     ///
-    /// supported values are: ed25519, ed25519bip32, ed25519extended, curve25519_2hashdh or sumed25519_12
+    /// supported values are: ed25519, ed25519bip32, ed25519extended, curve25519_2hashdh, sumed25519_12
+    /// or node-id, the ed25519 key authenticating a node to its peers (`p2p.node_key_file`)
     #[structopt(long = "type")]
-    key_type: GenPrivKeyType,
+    key_type: GenerateKeyType,
 
     #[structopt(flatten)]
     output_file: OutputFile,
@@ -195,6 +197,25 @@ arg_enum! {
     }
 }
 
+/// the type of the key to generate
+#[derive(Debug)]
+pub enum GenerateKeyType {
+    PrivKey(GenPrivKeyType),
+    /// the ed25519 key authenticating a node to its peers
+    NodeId,
+}
+
+impl FromStr for GenerateKeyType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("node-id") {
+            Ok(GenerateKeyType::NodeId)
+        } else {
+            s.parse().map(GenerateKeyType::PrivKey)
+        }
+    }
+}
+
 impl Key {
     pub fn exec(self) -> Result<(), Error> {
         match self {
@@ -211,7 +232,11 @@ impl Key {
 
 impl Generate {
     fn exec(self) -> Result<(), Error> {
-        let priv_key_bech32 = match self.key_type {
+        let key_type = match self.key_type {
+            GenerateKeyType::PrivKey(key_type) => key_type,
+            GenerateKeyType::NodeId => GenPrivKeyType::Ed25519,
+        };
+        let priv_key_bech32 = match key_type {
             GenPrivKeyType::Ed25519 => gen_priv_key::<Ed25519>(self.seed)?,
             GenPrivKeyType::Ed25519Bip32 => gen_priv_key::<Ed25519Bip32>(self.seed)?,
             GenPrivKeyType::Ed25519Extended => gen_priv_key::<Ed25519Extended>(self.seed)?,
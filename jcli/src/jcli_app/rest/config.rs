@@ -21,18 +21,24 @@ pub struct RestArgs {
     /// certificate CA is not present within the webpki certificate bundle.
     #[structopt(long, name = "PATH", env = "JORMUNGANDR_TLS_CERT_PATH")]
     tls_cert_path: Option<PathBuf>,
+    /// API token of the node administration endpoints, required by the
+    /// commands using these endpoints.
+    #[structopt(long, name = "TOKEN", env = "JORMUNGANDR_RESTAPI_TOKEN")]
+    api_token: Option<String>,
 }
 
 pub struct RestClient {
     client: Client,
     debug: bool,
     base_url: Url,
+    api_token: Option<String>,
 }
 
 pub struct RestRequestBuilder {
     client: Client,
     request_builder: RequestBuilder,
     debug: bool,
+    api_token: Option<String>,
 }
 
 pub struct RestResponse(reqwest::blocking::Response);
@@ -76,6 +82,7 @@ impl RestArgs {
             tls_cert_path,
             host,
             debug,
+            api_token,
         } = self;
 
         if host.cannot_be_a_base() {
@@ -103,6 +110,7 @@ impl RestArgs {
             client,
             debug,
             base_url: host,
+            api_token,
         };
 
         Ok(rest_client)
@@ -130,6 +138,7 @@ impl RestClient {
             client,
             base_url,
             debug,
+            api_token,
         } = self;
         let url = make_url(base_url, address_segments);
         let request_builder = f(&client, url);
@@ -137,6 +146,7 @@ impl RestClient {
             client,
             request_builder,
             debug,
+            api_token,
         }
     }
 }
@@ -151,6 +161,19 @@ fn make_url(mut host: Url, segments: &[&str]) -> Url {
 }
 
 impl RestRequestBuilder {
+    /// authenticate the request to a node administration endpoint with the
+    /// API token, the token is not sent with the other requests
+    pub fn admin(self) -> Self {
+        let request_builder = match &self.api_token {
+            Some(api_token) => self.request_builder.bearer_auth(api_token),
+            None => self.request_builder,
+        };
+        Self {
+            request_builder,
+            ..self
+        }
+    }
+
     pub fn json<T>(self, json: &T) -> Self
     where
         T: serde::Serialize,
@@ -185,6 +208,7 @@ impl RestRequestBuilder {
             client,
             request_builder,
             debug,
+            ..
         } = self;
 
         let request = request_builder.build().map_err(Error::Request)?;
@@ -1,5 +1,6 @@
 mod config;
 mod v0;
+mod v1;
 
 use crate::jcli_app::utils::{io::ReadYamlError, output_format};
 use config::RestArgs;
@@ -13,6 +14,8 @@ use thiserror::Error;
 pub enum Rest {
     /// API version 0
     V0(v0::V0),
+    /// API version 1
+    V1(v1::V1),
 }
 
 #[derive(Debug, Error)]
@@ -27,6 +30,8 @@ pub enum Error {
     InputFileYamlMalformed(#[from] serde_yaml::Error),
     #[error("input hex encoding is not valid")]
     InputHexMalformed(#[from] FromHexError),
+    #[error("could not load the staging transaction")]
+    StagingFileInvalid(#[from] crate::jcli_app::transaction::Error),
     #[error("error when trying to perform an HTTP request")]
     RequestError(#[from] config::Error),
 }
@@ -44,6 +49,7 @@ impl Rest {
     pub fn exec(self) -> Result<(), Error> {
         match self {
             Rest::V0(v0) => v0.exec(),
+            Rest::V1(v1) => v1.exec(),
         }
     }
 }
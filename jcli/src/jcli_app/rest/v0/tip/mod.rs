@@ -9,14 +9,27 @@ pub enum Tip {
         #[structopt(flatten)]
         args: RestArgs,
     },
+    /// Roll the tip back to one of its ancestors, requires the API token
+    /// of the node administration endpoints
+    Rollback {
+        #[structopt(flatten)]
+        args: RestArgs,
+        /// hex-encoded ID of the block to roll the tip back to
+        block_id: String,
+    },
 }
 
 impl Tip {
     pub fn exec(self) -> Result<(), Error> {
-        let args = match self {
-            Tip::Get { args } => args,
+        let response = match self {
+            Tip::Get { args } => args.client()?.get(&["v0", "tip"]).execute()?.text()?,
+            Tip::Rollback { args, block_id } => args
+                .client()?
+                .post(&["v0", "tip", "rollback", &block_id])
+                .admin()
+                .execute()?
+                .text()?,
         };
-        let response = args.client()?.get(&["v0", "tip"]).execute()?.text()?;
         println!("{}", response);
         Ok(())
     }
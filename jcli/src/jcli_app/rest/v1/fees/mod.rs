@@ -0,0 +1,98 @@
+use crate::jcli_app::{
+    rest::{Error, RestArgs},
+    transaction::staging::Staging,
+    utils::OutputFormat,
+};
+use jormungandr_lib::interfaces::{CertificateType, FeeEstimateRequest};
+use std::{convert::TryInto, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum Fees {
+    /// estimate the fee of a transaction under the fee settings of the tip
+    /// of the node. When the node fills the blocks with the fragments paying
+    /// the highest fee per byte first, the fee to pay to be included in the
+    /// next block is given too
+    Estimate {
+        #[structopt(flatten)]
+        args: RestArgs,
+        /// number of inputs of the transaction
+        #[structopt(long, default_value = "1")]
+        inputs: u8,
+        /// number of outputs of the transaction
+        #[structopt(long, default_value = "1")]
+        outputs: u8,
+        /// kind of the certificate of the transaction, e.g. `stake_delegation`
+        #[structopt(long)]
+        certificate: Option<CertificateType>,
+        /// size of the signed transaction in bytes, estimated by the node
+        /// from the number of inputs and outputs if not given
+        #[structopt(long)]
+        size: Option<u32>,
+        /// staging transaction file to take the inputs, outputs and
+        /// certificate from, instead of the options above
+        #[structopt(long, conflicts_with_all = &["inputs", "outputs", "certificate", "size"])]
+        staging: Option<PathBuf>,
+        #[structopt(flatten)]
+        output_format: OutputFormat,
+    },
+}
+
+impl Fees {
+    pub fn exec(self) -> Result<(), Error> {
+        match self {
+            Fees::Estimate {
+                args,
+                inputs,
+                outputs,
+                certificate,
+                size,
+                staging,
+                output_format,
+            } => {
+                let request = match staging {
+                    Some(staging) => request_of_staging(staging)?,
+                    None => FeeEstimateRequest {
+                        inputs,
+                        outputs,
+                        certificate,
+                        size,
+                    },
+                };
+                estimate(args, request, output_format)
+            }
+        }
+    }
+}
+
+fn request_of_staging(path: PathBuf) -> Result<FeeEstimateRequest, Error> {
+    let staging = Staging::load(&Some(path))?;
+    // the size is only known once the transaction is complete
+    let size = staging
+        .fragment()
+        .ok()
+        .and_then(|fragment| fragment.to_raw().size_bytes_plus_size().try_into().ok());
+    Ok(FeeEstimateRequest {
+        inputs: staging.inputs().len() as u8,
+        outputs: staging.outputs().len() as u8,
+        certificate: staging.certificate_type(),
+        size,
+    })
+}
+
+fn estimate(
+    args: RestArgs,
+    request: FeeEstimateRequest,
+    output_format: OutputFormat,
+) -> Result<(), Error> {
+    let response = args
+        .client()?
+        .post(&["v1", "fees", "estimate"])
+        .json(&request)
+        .execute()?
+        .json()?;
+    let formatted = output_format.format_json(response)?;
+    println!("{}", formatted);
+    Ok(())
+}
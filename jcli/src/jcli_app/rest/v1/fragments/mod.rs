@@ -0,0 +1,57 @@
+use crate::jcli_app::{
+    rest::{Error, RestArgs},
+    utils::{io, OutputFormat},
+};
+use chain_core::property::Deserialize;
+use chain_impl_mockchain::fragment::Fragment;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum Fragments {
+    /// check whether the fragment would be accepted by the node, against the
+    /// ledger of its tip, without sending it to the mempool
+    Validate {
+        #[structopt(flatten)]
+        args: RestArgs,
+        /// File containing hex-encoded fragment.
+        /// If not provided, fragment will be read from stdin.
+        #[structopt(short, long)]
+        file: Option<PathBuf>,
+        #[structopt(flatten)]
+        output_format: OutputFormat,
+    },
+}
+
+impl Fragments {
+    pub fn exec(self) -> Result<(), Error> {
+        match self {
+            Fragments::Validate {
+                args,
+                file,
+                output_format,
+            } => validate(args, file, output_format),
+        }
+    }
+}
+
+fn validate(
+    args: RestArgs,
+    file: Option<PathBuf>,
+    output_format: OutputFormat,
+) -> Result<(), Error> {
+    let fragment_hex = io::read_line(&file)?;
+    let fragment_bin = hex::decode(&fragment_hex)?;
+    let _fragment =
+        Fragment::deserialize(fragment_bin.as_slice()).map_err(Error::InputFragmentMalformed)?;
+    let response = args
+        .client()?
+        .post(&["v1", "fragments", "validate"])
+        .json(&vec![fragment_hex])
+        .execute()?
+        .json()?;
+    let formatted = output_format.format_json(response)?;
+    println!("{}", formatted);
+    Ok(())
+}
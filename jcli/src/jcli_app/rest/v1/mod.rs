@@ -0,0 +1,23 @@
+mod fees;
+mod fragments;
+
+use crate::jcli_app::rest::Error;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum V1 {
+    /// Fees estimation
+    Fees(fees::Fees),
+    /// Fragments operations
+    Fragments(fragments::Fragments),
+}
+
+impl V1 {
+    pub fn exec(self) -> Result<(), Error> {
+        match self {
+            V1::Fees(fees) => fees.exec(),
+            V1::Fragments(fragments) => fragments.exec(),
+        }
+    }
+}
@@ -10,7 +10,7 @@ mod info;
 mod mk_witness;
 mod new;
 mod seal;
-mod staging;
+pub mod staging;
 
 use self::staging::StagingKind;
 use crate::jcli_app::{
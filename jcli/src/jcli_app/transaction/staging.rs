@@ -521,6 +521,15 @@ impl Staging {
         Value::sum(self.outputs().iter().map(|output| *output.value().as_ref()))
     }
 
+    /// the kind of the certificate of the transaction, if any
+    pub fn certificate_type(&self) -> Option<interfaces::CertificateType> {
+        let cert_extra = self.extra_authed.clone().map(|cert| cert.strip_auth());
+        cert_extra
+            .as_ref()
+            .or_else(|| self.extra.as_ref())
+            .map(|cert| interfaces::CertificateType::from(&cert.0))
+    }
+
     pub fn fees(&self, fee_algorithm: &impl FeeAlgorithm) -> Value {
         let cert_extra = self.extra_authed.clone().map(|cert| cert.strip_auth());
         let cert_payload = cert_extra
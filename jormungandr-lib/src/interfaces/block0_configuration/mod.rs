@@ -11,20 +11,22 @@ mod number_of_slots_per_epoch;
 mod reward_constraint;
 mod slots_duration;
 
-pub use self::active_slot_coefficient::ActiveSlotCoefficient;
+pub use self::active_slot_coefficient::{ActiveSlotCoefficient, TryFromActiveSlotCoefficientError};
 pub use self::block_content_max_size::BlockContentMaxSize;
 pub use self::default_values::*;
 pub use self::epoch_stability_depth::EpochStabilityDepth;
-pub use self::fees_go_to::FeesGoTo;
+pub use self::fees_go_to::{FeesGoTo, TryFromFeesGoToError};
 pub use self::initial_config::BlockchainConfiguration;
 pub use self::initial_fragment::{
     try_initials_vec_from_messages, Initial, InitialUTxO, LegacyUTxO,
 };
-pub use self::kes_update_speed::KESUpdateSpeed;
+pub use self::kes_update_speed::{KESUpdateSpeed, TryFromKESUpdateSpeedError};
 pub use self::leader_id::ConsensusLeaderId;
-pub use self::number_of_slots_per_epoch::NumberOfSlotsPerEpoch;
+pub use self::number_of_slots_per_epoch::{
+    NumberOfSlotsPerEpoch, TryFromNumberOfSlotsPerEpochError,
+};
 pub use self::reward_constraint::{PoolParticipationCapping, RewardConstraints};
-pub use self::slots_duration::SlotDuration;
+pub use self::slots_duration::{SlotDuration, TryFromSlotDurationError};
 use chain_impl_mockchain::{
     block::{self, Block},
     fragment::{ContentsBuilder, Fragment},
@@ -0,0 +1,30 @@
+use crate::{crypto::hash::Hash, interfaces::BlockDate, time::SystemTime};
+use serde::{Deserialize, Serialize};
+
+/// a branch of the blockchain tracked by the node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct BranchInfo {
+    /// hash of the block at the tip of the branch
+    pub tip: Hash,
+    pub chain_length: u32,
+    pub block_date: BlockDate,
+    /// `true` for the branch of the node's tip
+    pub main: bool,
+    /// where the branch forks from the main branch, `None` if the common
+    /// ancestor of the two branches could not be found
+    pub fork_point: Option<ForkPoint>,
+    /// last time the tip of the branch was updated
+    pub last_updated: SystemTime,
+}
+
+/// the last block a branch has in common with the main branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ForkPoint {
+    pub block: Hash,
+    pub chain_length: u32,
+    /// number of blocks of the main branch after the fork point, i.e. the
+    /// number of blocks to roll back to switch to the branch
+    pub depth: u32,
+}
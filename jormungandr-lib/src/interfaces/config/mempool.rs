@@ -1,3 +1,4 @@
+use crate::time::Duration;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
@@ -6,6 +7,15 @@ pub struct PoolMaxEntries(usize);
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
 pub struct LogMaxEntries(usize);
 
+/// time to live of the pending fragments, either a wall-clock duration
+/// or a number of slots
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum FragmentTtl {
+    Duration(Duration),
+    Slots(u32),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Mempool {
@@ -15,6 +25,36 @@ pub struct Mempool {
     /// maximum number of entries in the fragment logs
     #[serde(default)]
     pub log_max_entries: LogMaxEntries,
+    /// check the fragments against the ledger of the tip before they
+    /// are accepted in the mempool and propagated
+    #[serde(default = "default_ledger_validation")]
+    pub ledger_validation: bool,
+    /// accept the update proposals and votes of the BFT leaders in the
+    /// mempool. They carry no signature, the ledger only checks that the
+    /// proposer or voter id is the one of a BFT leader, so anyone able to submit
+    /// a fragment can propose and vote in the name of the leaders.
+    #[serde(default)]
+    pub accept_update_proposals: bool,
+    /// time after which the pending fragments are removed from the mempool
+    /// and rejected, they are kept until selected or evicted if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment_ttl: Option<FragmentTtl>,
+    /// maximum number of entries of the mempool spending from the same
+    /// account, not limited if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_max_entries: Option<usize>,
+    /// maximum number of entries of the mempool received from the REST
+    /// API, not limited if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rest_max_entries: Option<usize>,
+    /// maximum number of entries of the mempool received from the network,
+    /// not limited if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_max_entries: Option<usize>,
+}
+
+fn default_ledger_validation() -> bool {
+    true
 }
 
 impl Default for PoolMaxEntries {
@@ -34,6 +74,12 @@ impl Default for Mempool {
         Mempool {
             pool_max_entries: PoolMaxEntries::default(),
             log_max_entries: LogMaxEntries::default(),
+            ledger_validation: default_ledger_validation(),
+            accept_update_proposals: false,
+            fragment_ttl: None,
+            account_max_entries: None,
+            rest_max_entries: None,
+            network_max_entries: None,
         }
     }
 }
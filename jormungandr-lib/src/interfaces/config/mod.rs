@@ -4,9 +4,9 @@ mod node;
 mod secret;
 
 pub use log::{Log, LogEntry, LogOutput};
-pub use mempool::{LogMaxEntries, Mempool, PoolMaxEntries};
+pub use mempool::{FragmentTtl, LogMaxEntries, Mempool, PoolMaxEntries};
 pub use node::{
-    Cors, Explorer, LayersConfig, NodeConfig, P2p, Policy, PreferredListConfig, Rest, Tls,
-    TopicsOfInterest, TrustedPeer,
+    Cors, Explorer, LayersConfig, NodeConfig, P2p, P2pTls, Policy, PreferredListConfig, Rest,
+    RestAdmin, Tls, TopicsOfInterest, TrustedCheckpoint, TrustedPeer,
 };
 pub use secret::{Bft, GenesisPraos, NodeSecret};
@@ -1,11 +1,14 @@
 #![allow(deprecated)]
 use crate::{
+    crypto::{hash::Hash, key::Identifier},
     interfaces::{Log, Mempool},
     time::Duration,
 };
+use chain_crypto::Ed25519;
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
 use std::{fmt, net::SocketAddr, path::PathBuf, str::FromStr};
 const DEFAULT_PREFERRED_VIEW_MAX: usize = 20;
+const DEFAULT_MAX_ROLLBACK_DEPTH: u32 = 10;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
@@ -17,6 +20,24 @@ pub struct Rest {
     /// Enables CORS if provided
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cors: Option<Cors>,
+    /// Enables the administration endpoints if provided
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<RestAdmin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RestAdmin {
+    /// Token to be given as a bearer token in the `Authorization` header
+    /// of the requests to the administration endpoints
+    pub api_token: String,
+    /// Maximum number of blocks the tip can be rolled back by
+    #[serde(default = "default_max_rollback_depth")]
+    pub max_rollback_depth: u32,
+}
+
+fn default_max_rollback_depth() -> u32 {
+    DEFAULT_MAX_ROLLBACK_DEPTH
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -28,6 +49,29 @@ pub struct Tls {
     pub priv_key_file: String,
 }
 
+/// TLS settings of the connections between the nodes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct P2pTls {
+    /// Path to the X.509 certificate chain of the node, must be PEM-encoded and contain
+    /// at least 1 item. It is presented to the peers connecting to the node, and to the
+    /// peers the node connects to if they ask for a client certificate
+    pub cert_file: PathBuf,
+    /// Path to the private key of the node, must be a single PEM-encoded, unencrypted key
+    pub priv_key_file: PathBuf,
+    /// Path to the PEM-encoded certificate of the authority the certificates of the
+    /// peers the node connects to are verified with
+    pub ca_file: PathBuf,
+    /// The name the certificates of the peers are issued for. The peers are addressed
+    /// by their IP address, so it cannot be taken from the address
+    pub domain_name: String,
+    /// Path to the PEM-encoded certificate of the authority the certificates of the
+    /// peers connecting to the node are verified with. If provided, the peers must
+    /// present a client certificate (mutual TLS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ca_file: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Cors {
@@ -92,6 +136,18 @@ pub struct P2p {
     pub public_address: poldercast::Address,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub public_id: Option<poldercast::Id>,
+    /// the file of the secret key authenticating the node to its peers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_key_file: Option<PathBuf>,
+    /// the ids of the nodes allowed to authenticate to the node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_node_ids: Option<Vec<Identifier<Ed25519>>>,
+    /// only exchange with the nodes of `allowed_node_ids`
+    #[serde(default)]
+    pub private_network: bool,
+    /// Enables TLS on the connections with the peers if provided
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<P2pTls>,
     /// the rendezvous points for the peer to connect to in order to initiate
     /// the p2p discovery from.
     pub trusted_peers: Vec<TrustedPeer>,
@@ -187,6 +243,17 @@ pub struct NodeConfig {
     pub mempool: Option<Mempool>,
     pub bootstrap_from_trusted_peers: Option<bool>,
     pub skip_bootstrap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+}
+
+/// A block the chain pulled from the peers during the bootstrap must
+/// contain at the given chain length
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TrustedCheckpoint {
+    pub chain_length: u32,
+    pub hash: Hash,
 }
 
 impl P2p {
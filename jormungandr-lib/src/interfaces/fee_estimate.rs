@@ -0,0 +1,28 @@
+use crate::interfaces::{CertificateType, Value};
+use serde::{Deserialize, Serialize};
+
+/// the shape of a transaction to estimate the fee of
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeEstimateRequest {
+    pub inputs: u8,
+    pub outputs: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<CertificateType>,
+    /// the size of the signed fragment in bytes, estimated from the number
+    /// of inputs and outputs if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+}
+
+/// the fees of a transaction under the fee settings of the tip
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// the fee required by the ledger
+    pub fee: Value,
+    /// the fee that places the transaction in the next block given the
+    /// fragments waiting in the mempool, only given when the blocks are
+    /// filled with the fragments paying the highest fee per byte first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_fee: Option<Value>,
+}
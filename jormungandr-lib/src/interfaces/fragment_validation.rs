@@ -0,0 +1,26 @@
+use crate::crypto::hash::Hash;
+use serde::{Deserialize, Serialize};
+
+/// the outcome of the dry-run validation of a fragment against the ledger
+/// of the tip of a node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FragmentValidation {
+    pub fragment_id: Hash,
+    pub status: FragmentValidationStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FragmentValidationStatus {
+    /// the fragment would be accepted in the mempool
+    Valid,
+    /// the fragment would be rejected, `reason` being the chain of the
+    /// errors of the ledger
+    Invalid { reason: String },
+}
+
+impl FragmentValidationStatus {
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self == &FragmentValidationStatus::Valid
+    }
+}
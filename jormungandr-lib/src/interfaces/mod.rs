@@ -3,14 +3,18 @@ mod account_state;
 mod address;
 mod block0_configuration;
 mod blockdate;
+mod branch;
 mod certificate;
 mod committee;
 mod config;
+mod fee_estimate;
 mod fragment_log;
+mod fragment_validation;
 mod leadership_log;
 mod linear_fee;
 mod old_address;
 mod peer_stats;
+mod pending_fragment;
 mod ratio;
 mod reward_parameters;
 mod rewards_info;
@@ -20,9 +24,11 @@ mod stake_distribution;
 mod stake_pool_stats;
 mod stats;
 mod tax_type;
+mod tip_event;
 mod transaction_input;
 mod transaction_output;
 mod transaction_witness;
+mod update_proposal;
 mod utxo_info;
 mod value;
 mod vote;
@@ -32,20 +38,26 @@ pub use self::account_state::AccountState;
 pub use self::address::Address;
 pub use self::block0_configuration::*;
 pub use self::blockdate::BlockDate;
+pub use self::branch::{BranchInfo, ForkPoint};
 pub use self::certificate::{
     Certificate, CertificateFromBech32Error, CertificateFromStrError, CertificateToBech32Error,
     SignedCertificate, CERTIFICATE_HRP, SIGNED_CERTIFICATE_HRP,
 };
 pub use self::committee::CommitteeIdDef;
 pub use self::config::*;
+pub use self::fee_estimate::{FeeEstimate, FeeEstimateRequest};
 pub use self::fragment_log::{FragmentLog, FragmentOrigin, FragmentStatus};
+pub use self::fragment_validation::{FragmentValidation, FragmentValidationStatus};
 pub use self::leadership_log::{
     EnclaveLeaderId, LeadershipLog, LeadershipLogId, LeadershipLogStatus,
 };
 pub use self::linear_fee::LinearFeeDef;
 pub use self::old_address::OldAddress;
 pub use self::peer_stats::{
-    Info, Logs, PeerRecord, PeerStats, Profile, Record, Strike, Subscription, When,
+    Info, Logs, PeerRecord, PeerScore, PeerStats, Profile, Record, Strike, Subscription, When,
+};
+pub use self::pending_fragment::{
+    CertificateType, FragmentType, FromStrCertificateTypeError, PendingFragment,
 };
 pub use self::ratio::{ParseRatioError, Ratio};
 pub use self::reward_parameters::RewardParams;
@@ -56,9 +68,13 @@ pub use self::stake_distribution::{StakeDistribution, StakeDistributionDto};
 pub use self::stake_pool_stats::{Rewards, StakePoolStats};
 pub use self::stats::{NodeState, NodeStats, NodeStatsDto};
 pub use self::tax_type::TaxType;
+pub use self::tip_event::{TipEvent, TipEventKind};
 pub use self::transaction_input::{TransactionInput, TransactionInputType};
 pub use self::transaction_output::TransactionOutput;
 pub use self::transaction_witness::TransactionWitness;
+pub use self::update_proposal::{
+    ProposalChanges, ProposalChangesError, UpdateProposal, UpdateProposalStatus, UpdateVote,
+};
 pub use self::utxo_info::{UTxOInfo, UTxOOutputInfo};
 pub use self::value::{Value, ValueDef};
 pub use self::vote::{
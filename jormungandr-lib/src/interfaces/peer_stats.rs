@@ -13,6 +13,15 @@ pub struct PeerStats {
     pub last_gossip_received: Option<SystemTime>,
 }
 
+/// the score of a peer, raised by its useful behaviour and lowered by its
+/// misbehaviour, decaying towards zero over time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PeerScore {
+    pub address: String,
+    pub score: f64,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct PeerRecord {
@@ -0,0 +1,292 @@
+use crate::{
+    crypto::hash::Hash,
+    interfaces::{FragmentLog, FragmentOrigin, TransactionInput, TransactionOutput},
+    time::SystemTime,
+};
+use chain_crypto::{Curve25519_2HashDH, SecretKey, SumEd25519_12};
+use chain_impl_mockchain::{
+    account::DelegationType,
+    certificate::{
+        Certificate, CertificatePayload, EncryptedVoteTally, OwnerStakeDelegation, PoolPermissions,
+        PoolRegistration, PoolRetirement, PoolUpdate, Proposals, StakeDelegation, VoteCast,
+        VotePlan, VoteTally,
+    },
+    fee::{FeeAlgorithm, LinearFee},
+    fragment::Fragment,
+    header::BlockDate,
+    key::GenesisPraosLeader,
+    rewards::{Ratio, TaxType},
+    transaction::{Transaction, UnspecifiedAccountIdentifier},
+    value::Value,
+    vote::{Choice, Payload, PayloadType},
+};
+use chain_time::DurationSeconds;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU64, str::FromStr};
+use thiserror::Error;
+
+/// the kind of a fragment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FragmentType {
+    Initial,
+    OldUtxoDeclaration,
+    Transaction,
+    OwnerStakeDelegation,
+    StakeDelegation,
+    PoolRegistration,
+    PoolRetirement,
+    PoolUpdate,
+    UpdateProposal,
+    UpdateVote,
+    VotePlan,
+    VoteCast,
+    VoteTally,
+    EncryptedVoteTally,
+}
+
+/// the kind of the certificate carried by a fragment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateType {
+    OwnerStakeDelegation,
+    StakeDelegation,
+    PoolRegistration,
+    PoolRetirement,
+    PoolUpdate,
+    UpdateProposal,
+    UpdateVote,
+    VotePlan,
+    VoteCast,
+    VoteTally,
+    EncryptedVoteTally,
+}
+
+/// a fragment waiting in the mempool of the node, decoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingFragment {
+    pub fragment_id: Hash,
+    #[serde(rename = "type")]
+    pub fragment_type: FragmentType,
+    /// the size of the serialized fragment, in bytes
+    pub size: usize,
+    pub received_from: FragmentOrigin,
+    pub received_at: SystemTime,
+    pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<TransactionOutput>,
+    pub certificate: Option<CertificateType>,
+}
+
+impl PendingFragment {
+    /// decode the fragment, `log` being the fragment log of the fragment
+    pub fn new(fragment: &Fragment, log: &FragmentLog) -> Self {
+        fn transaction<P>(tx: &Transaction<P>) -> (Vec<TransactionInput>, Vec<TransactionOutput>) {
+            let tx = tx.as_slice();
+            let inputs = tx.inputs().iter().map(TransactionInput::from).collect();
+            let outputs = tx.outputs().iter().map(TransactionOutput::from).collect();
+            (inputs, outputs)
+        }
+
+        let (inputs, outputs) = match fragment {
+            Fragment::Transaction(tx) => transaction(tx),
+            Fragment::OwnerStakeDelegation(tx) => transaction(tx),
+            Fragment::StakeDelegation(tx) => transaction(tx),
+            Fragment::PoolRegistration(tx) => transaction(tx),
+            Fragment::PoolRetirement(tx) => transaction(tx),
+            Fragment::PoolUpdate(tx) => transaction(tx),
+            Fragment::VotePlan(tx) => transaction(tx),
+            Fragment::VoteCast(tx) => transaction(tx),
+            Fragment::VoteTally(tx) => transaction(tx),
+            Fragment::EncryptedVoteTally(tx) => transaction(tx),
+            Fragment::Initial(_)
+            | Fragment::OldUtxoDeclaration(_)
+            | Fragment::UpdateProposal(_)
+            | Fragment::UpdateVote(_) => (Vec::new(), Vec::new()),
+        };
+
+        PendingFragment {
+            fragment_id: *log.fragment_id(),
+            fragment_type: FragmentType::from(fragment),
+            size: fragment.to_raw().size_bytes_plus_size(),
+            received_from: *log.received_from(),
+            received_at: *log.received_at(),
+            inputs,
+            outputs,
+            certificate: CertificateType::of(fragment),
+        }
+    }
+}
+
+impl CertificateType {
+    /// the kind of the certificate of the fragment, `None` if it does not
+    /// carry a certificate
+    pub fn of(fragment: &Fragment) -> Option<Self> {
+        match fragment {
+            Fragment::OwnerStakeDelegation(_) => Some(CertificateType::OwnerStakeDelegation),
+            Fragment::StakeDelegation(_) => Some(CertificateType::StakeDelegation),
+            Fragment::PoolRegistration(_) => Some(CertificateType::PoolRegistration),
+            Fragment::PoolRetirement(_) => Some(CertificateType::PoolRetirement),
+            Fragment::PoolUpdate(_) => Some(CertificateType::PoolUpdate),
+            Fragment::UpdateProposal(_) => Some(CertificateType::UpdateProposal),
+            Fragment::UpdateVote(_) => Some(CertificateType::UpdateVote),
+            Fragment::VotePlan(_) => Some(CertificateType::VotePlan),
+            Fragment::VoteCast(_) => Some(CertificateType::VoteCast),
+            Fragment::VoteTally(_) => Some(CertificateType::VoteTally),
+            Fragment::EncryptedVoteTally(_) => Some(CertificateType::EncryptedVoteTally),
+            Fragment::Initial(_) | Fragment::OldUtxoDeclaration(_) | Fragment::Transaction(_) => {
+                None
+            }
+        }
+    }
+
+    /// the fee of a transaction with the given number of inputs and outputs
+    /// carrying a certificate of this kind, as computed by the ledger. `None`
+    /// for the update proposals and votes, which are not carried by a
+    /// transaction and pay no fee
+    pub fn transaction_fee(self, fees: &LinearFee, inputs: u8, outputs: u8) -> Option<Value> {
+        let certificate = CertificatePayload::from(&self.sample()?);
+        Some(fees.calculate(Some(certificate.as_slice()), inputs, outputs))
+    }
+
+    /// a certificate of this kind, the fee algorithm of the ledger only
+    /// looks at the kind of the certificate
+    fn sample(self) -> Option<Certificate> {
+        let id = Hash::from([0; 32]);
+        let certificate = match self {
+            CertificateType::OwnerStakeDelegation => {
+                Certificate::OwnerStakeDelegation(OwnerStakeDelegation {
+                    delegation: DelegationType::NonDelegated,
+                })
+            }
+            CertificateType::StakeDelegation => Certificate::StakeDelegation(StakeDelegation {
+                account_id: UnspecifiedAccountIdentifier::from([0; 32]),
+                delegation: DelegationType::NonDelegated,
+            }),
+            CertificateType::PoolRegistration => {
+                Certificate::PoolRegistration(sample_pool_registration())
+            }
+            CertificateType::PoolRetirement => Certificate::PoolRetirement(PoolRetirement {
+                pool_id: id.into_digest_of(),
+                retirement_time: DurationSeconds::from(0).into(),
+            }),
+            CertificateType::PoolUpdate => Certificate::PoolUpdate(PoolUpdate {
+                pool_id: id.into_digest_of(),
+                last_pool_reg_hash: id.into_digest_of(),
+                new_pool_reg: sample_pool_registration(),
+            }),
+            CertificateType::VotePlan => {
+                let date = BlockDate::first();
+                Certificate::VotePlan(VotePlan::new(
+                    date,
+                    date,
+                    date,
+                    Proposals::new(),
+                    PayloadType::Public,
+                    Vec::new(),
+                ))
+            }
+            CertificateType::VoteCast => Certificate::VoteCast(VoteCast::new(
+                id.into_digest_of(),
+                0,
+                Payload::Public {
+                    choice: Choice::new(0),
+                },
+            )),
+            CertificateType::VoteTally => {
+                Certificate::VoteTally(VoteTally::new_public(id.into_digest_of()))
+            }
+            CertificateType::EncryptedVoteTally => {
+                Certificate::EncryptedVoteTally(EncryptedVoteTally::new(id.into_digest_of()))
+            }
+            CertificateType::UpdateProposal | CertificateType::UpdateVote => return None,
+        };
+        Some(certificate)
+    }
+}
+
+fn sample_pool_registration() -> PoolRegistration {
+    let mut rng = ChaChaRng::from_seed([0; 32]);
+    PoolRegistration {
+        serial: 0,
+        start_validity: DurationSeconds::from(0).into(),
+        permissions: PoolPermissions::new(1),
+        owners: Vec::new(),
+        operators: Vec::new().into(),
+        rewards: TaxType {
+            fixed: Value::zero(),
+            ratio: Ratio {
+                numerator: 0,
+                denominator: NonZeroU64::new(1).unwrap(),
+            },
+            max_limit: None,
+        },
+        reward_account: None,
+        keys: GenesisPraosLeader {
+            kes_public_key: SecretKey::<SumEd25519_12>::generate(&mut rng).to_public(),
+            vrf_public_key: SecretKey::<Curve25519_2HashDH>::generate(&mut rng).to_public(),
+        },
+    }
+}
+
+impl<'a> From<&'a Certificate> for CertificateType {
+    fn from(certificate: &'a Certificate) -> Self {
+        match certificate {
+            Certificate::StakeDelegation(_) => CertificateType::StakeDelegation,
+            Certificate::OwnerStakeDelegation(_) => CertificateType::OwnerStakeDelegation,
+            Certificate::PoolRegistration(_) => CertificateType::PoolRegistration,
+            Certificate::PoolRetirement(_) => CertificateType::PoolRetirement,
+            Certificate::PoolUpdate(_) => CertificateType::PoolUpdate,
+            Certificate::VotePlan(_) => CertificateType::VotePlan,
+            Certificate::VoteCast(_) => CertificateType::VoteCast,
+            Certificate::VoteTally(_) => CertificateType::VoteTally,
+            Certificate::EncryptedVoteTally(_) => CertificateType::EncryptedVoteTally,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid certificate type {0}")]
+pub struct FromStrCertificateTypeError(String);
+
+impl FromStr for CertificateType {
+    type Err = FromStrCertificateTypeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner_stake_delegation" => Ok(CertificateType::OwnerStakeDelegation),
+            "stake_delegation" => Ok(CertificateType::StakeDelegation),
+            "pool_registration" => Ok(CertificateType::PoolRegistration),
+            "pool_retirement" => Ok(CertificateType::PoolRetirement),
+            "pool_update" => Ok(CertificateType::PoolUpdate),
+            "update_proposal" => Ok(CertificateType::UpdateProposal),
+            "update_vote" => Ok(CertificateType::UpdateVote),
+            "vote_plan" => Ok(CertificateType::VotePlan),
+            "vote_cast" => Ok(CertificateType::VoteCast),
+            "vote_tally" => Ok(CertificateType::VoteTally),
+            "encrypted_vote_tally" => Ok(CertificateType::EncryptedVoteTally),
+            _ => Err(FromStrCertificateTypeError(s.to_owned())),
+        }
+    }
+}
+
+impl<'a> From<&'a Fragment> for FragmentType {
+    fn from(fragment: &'a Fragment) -> Self {
+        match fragment {
+            Fragment::Initial(_) => FragmentType::Initial,
+            Fragment::OldUtxoDeclaration(_) => FragmentType::OldUtxoDeclaration,
+            Fragment::Transaction(_) => FragmentType::Transaction,
+            Fragment::OwnerStakeDelegation(_) => FragmentType::OwnerStakeDelegation,
+            Fragment::StakeDelegation(_) => FragmentType::StakeDelegation,
+            Fragment::PoolRegistration(_) => FragmentType::PoolRegistration,
+            Fragment::PoolRetirement(_) => FragmentType::PoolRetirement,
+            Fragment::PoolUpdate(_) => FragmentType::PoolUpdate,
+            Fragment::UpdateProposal(_) => FragmentType::UpdateProposal,
+            Fragment::UpdateVote(_) => FragmentType::UpdateVote,
+            Fragment::VotePlan(_) => FragmentType::VotePlan,
+            Fragment::VoteCast(_) => FragmentType::VoteCast,
+            Fragment::VoteTally(_) => FragmentType::VoteTally,
+            Fragment::EncryptedVoteTally(_) => FragmentType::EncryptedVoteTally,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::time::SystemTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -14,6 +15,8 @@ pub struct NodeStatsDto {
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct NodeStats {
     pub block_recv_cnt: u64,
+    #[serde(default)]
+    pub deep_fork_rejected_cnt: u64,
     pub last_block_content_size: u32,
     pub last_block_date: Option<String>,
     pub last_block_fees: u64,
@@ -23,11 +26,19 @@ pub struct NodeStats {
     pub last_block_time: Option<SystemTime>,
     pub last_block_tx: u64,
     pub last_received_block_time: Option<SystemTime>,
+    /// the bech32 encoded public key authenticating the node to its peers
+    #[serde(default)]
+    pub node_id: String,
     pub peer_available_cnt: usize,
     pub peer_connected_cnt: usize,
     pub peer_quarantined_cnt: usize,
     pub peer_total_cnt: usize,
     pub peer_unreachable_cnt: usize,
+    #[serde(default)]
+    pub reorg_cnt: u64,
+    /// number of reorgs by depth
+    #[serde(default)]
+    pub reorg_depths: BTreeMap<u32, u64>,
     pub tx_recv_cnt: u64,
     pub uptime: Option<u64>,
 }
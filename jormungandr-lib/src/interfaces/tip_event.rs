@@ -0,0 +1,47 @@
+use crate::{crypto::hash::Hash, interfaces::BlockDate};
+use serde::{Deserialize, Serialize};
+
+/// a change of the tip of the node, streamed by the REST API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TipEvent {
+    /// number of the event, incremented by one for every event sent by the
+    /// node. It starts at the time the node started, in microseconds since
+    /// the Unix epoch. A gap between two events received by a client means
+    /// it missed the events in between.
+    pub sequence: u64,
+    #[serde(flatten)]
+    pub kind: TipEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TipEventKind {
+    /// the tip moved to the given block
+    #[serde(rename_all = "camelCase")]
+    TipChanged {
+        hash: Hash,
+        chain_length: u32,
+        date: BlockDate,
+    },
+    /// the blocks of the main branch after `to` were rolled back, either
+    /// because the node switched to another branch forking at `to`, or
+    /// because the tip was rolled back to `to`. It is followed by the
+    /// `TipChanged` event of the new tip.
+    Rollback { from: Hash, to: Hash, depth: u32 },
+    /// the events following the last event seen by a reconnecting client
+    /// are not available, the client has to get the current state of the
+    /// node again
+    Resync,
+}
+
+impl TipEventKind {
+    /// name of the event in the event stream
+    pub fn name(&self) -> &'static str {
+        match self {
+            TipEventKind::TipChanged { .. } => "tipChanged",
+            TipEventKind::Rollback { .. } => "rollback",
+            TipEventKind::Resync => "resync",
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{
+    crypto::hash::Hash,
+    interfaces::{
+        ActiveSlotCoefficient, BlockContentMaxSize, BlockDate, ConsensusLeaderId,
+        EpochStabilityDepth, FeesGoTo, KESUpdateSpeed, LinearFeeDef, NumberOfSlotsPerEpoch,
+        RewardParams, SlotDuration, TaxType, TryFromActiveSlotCoefficientError,
+        TryFromFeesGoToError, TryFromKESUpdateSpeedError, TryFromNumberOfSlotsPerEpochError,
+        TryFromSlotDurationError,
+    },
+};
+use chain_impl_mockchain::{
+    config::ConfigParam,
+    fee::LinearFee,
+    fragment::{config::ConfigParams, Fragment},
+    update::{
+        SignedUpdateProposal, SignedUpdateVote, UpdateProposal as UpdateProposalStd,
+        UpdateProposalWithProposer, UpdateVote as UpdateVoteStd,
+    },
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// the changes of the blockchain parameters proposed by an update proposal
+///
+/// The proposal is adopted at the start of the epoch following the one
+/// in which a majority of the BFT leaders voted for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProposalChanges {
+    /// the new linear fee settings, including the per certificate fees
+    #[serde(
+        default,
+        with = "optional_linear_fee",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub linear_fees: Option<LinearFee>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slots_per_epoch: Option<NumberOfSlotsPerEpoch>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot_duration: Option<SlotDuration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kes_update_speed: Option<KESUpdateSpeed>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consensus_genesis_praos_active_slot_coeff: Option<ActiveSlotCoefficient>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_content_max_size: Option<BlockContentMaxSize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch_stability_depth: Option<EpochStabilityDepth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees_go_to: Option<FeesGoTo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub treasury_parameters: Option<TaxType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward_parameters: Option<RewardParams>,
+    /// the number of epochs the proposals stay open for votes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proposal_expiration: Option<u32>,
+    /// the BFT leaders to add
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_consensus_leader_ids: Vec<ConsensusLeaderId>,
+    /// the BFT leaders to remove
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove_consensus_leader_ids: Vec<ConsensusLeaderId>,
+}
+
+/// an update proposal of a BFT leader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateProposal {
+    pub proposer_id: ConsensusLeaderId,
+    pub changes: ProposalChanges,
+}
+
+/// the vote of a BFT leader for an update proposal, the id of the proposal
+/// being the id of the fragment of the proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateVote {
+    pub proposal_id: Hash,
+    pub voter_id: ConsensusLeaderId,
+}
+
+/// an update proposal pending in the ledger of the tip, waiting to be adopted,
+/// and the votes it got
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateProposalStatus {
+    pub proposal_id: Hash,
+    /// the date of the block including the proposal
+    pub proposal_date: BlockDate,
+    pub changes: ProposalChanges,
+    pub votes: Vec<ConsensusLeaderId>,
+}
+
+#[derive(Debug, Error)]
+pub enum ProposalChangesError {
+    #[error("parameter {name} cannot be changed by an update proposal")]
+    UnsupportedChange { name: &'static str },
+    #[error("update proposal contains duplicate parameter {name}")]
+    Duplicate { name: &'static str },
+    #[error("Invalid number of slots per epoch")]
+    NumberOfSlotsPerEpoch(#[from] TryFromNumberOfSlotsPerEpochError),
+    #[error("Invalid slot duration value")]
+    SlotDuration(#[from] TryFromSlotDurationError),
+    #[error("Invalid active slot coefficient value")]
+    ActiveSlotCoefficient(#[from] TryFromActiveSlotCoefficientError),
+    #[error("Invalid KES Update speed value")]
+    KESUpdateSpeed(#[from] TryFromKESUpdateSpeedError),
+    #[error("Invalid FeesGoTo setting")]
+    FeesGoTo(#[from] TryFromFeesGoToError),
+}
+
+impl From<ProposalChanges> for ConfigParams {
+    fn from(changes: ProposalChanges) -> Self {
+        let ProposalChanges {
+            linear_fees,
+            slots_per_epoch,
+            slot_duration,
+            kes_update_speed,
+            consensus_genesis_praos_active_slot_coeff,
+            block_content_max_size,
+            epoch_stability_depth,
+            fees_go_to,
+            treasury_parameters,
+            reward_parameters,
+            proposal_expiration,
+            add_consensus_leader_ids,
+            remove_consensus_leader_ids,
+        } = changes;
+
+        let mut params = ConfigParams::new();
+
+        if let Some(linear_fees) = linear_fees {
+            params.push(ConfigParam::LinearFee(linear_fees));
+            if !crate::interfaces::linear_fee::per_certificate_fee_is_zero(
+                &linear_fees.per_certificate_fees,
+            ) {
+                params.push(ConfigParam::PerCertificateFees(
+                    linear_fees.per_certificate_fees,
+                ));
+            }
+            if !crate::interfaces::linear_fee::per_vote_certificate_fee_is_zero(
+                &linear_fees.per_vote_certificate_fees,
+            ) {
+                params.push(ConfigParam::PerVoteCertificateFees(
+                    linear_fees.per_vote_certificate_fees,
+                ));
+            }
+        }
+        if let Some(slots_per_epoch) = slots_per_epoch {
+            params.push(ConfigParam::from(slots_per_epoch));
+        }
+        if let Some(slot_duration) = slot_duration {
+            params.push(ConfigParam::from(slot_duration));
+        }
+        if let Some(kes_update_speed) = kes_update_speed {
+            params.push(ConfigParam::from(kes_update_speed));
+        }
+        if let Some(active_slot_coeff) = consensus_genesis_praos_active_slot_coeff {
+            params.push(ConfigParam::from(active_slot_coeff));
+        }
+        if let Some(block_content_max_size) = block_content_max_size {
+            params.push(ConfigParam::BlockContentMaxSize(
+                block_content_max_size.into(),
+            ));
+        }
+        if let Some(epoch_stability_depth) = epoch_stability_depth {
+            params.push(ConfigParam::EpochStabilityDepth(
+                epoch_stability_depth.into(),
+            ));
+        }
+        if let Some(fees_go_to) = fees_go_to {
+            params.push(ConfigParam::from(fees_go_to));
+        }
+        if let Some(treasury_parameters) = treasury_parameters {
+            params.push(ConfigParam::TreasuryParams(treasury_parameters.into()));
+        }
+        if let Some(reward_parameters) = reward_parameters {
+            params.push(ConfigParam::RewardParams(reward_parameters.into()));
+        }
+        if let Some(proposal_expiration) = proposal_expiration {
+            params.push(ConfigParam::ProposalExpiration(proposal_expiration));
+        }
+        for leader_id in add_consensus_leader_ids {
+            params.push(ConfigParam::from(leader_id));
+        }
+        for leader_id in remove_consensus_leader_ids {
+            params.push(ConfigParam::RemoveBftLeader(leader_id.0));
+        }
+
+        params
+    }
+}
+
+impl TryFrom<ConfigParams> for ProposalChanges {
+    type Error = ProposalChangesError;
+    fn try_from(params: ConfigParams) -> Result<Self, Self::Error> {
+        fn unsupported(name: &'static str) -> Result<Option<&'static str>, ProposalChangesError> {
+            Err(ProposalChangesError::UnsupportedChange { name })
+        }
+
+        let mut changes = ProposalChanges::default();
+        let mut per_certificate_fees = None;
+        let mut per_vote_certificate_fees = None;
+
+        for param in params.iter().cloned() {
+            match param {
+                ConfigParam::LinearFee(param) => {
+                    Ok(changes.linear_fees.replace(param).map(|_| "linear_fees"))
+                }
+                ConfigParam::PerCertificateFees(param) => Ok(per_certificate_fees
+                    .replace(param)
+                    .map(|_| "per_certificate_fees")),
+                ConfigParam::PerVoteCertificateFees(param) => Ok(per_vote_certificate_fees
+                    .replace(param)
+                    .map(|_| "per_vote_certificate_fees")),
+                cp @ ConfigParam::SlotsPerEpoch(_) => Ok(changes
+                    .slots_per_epoch
+                    .replace(NumberOfSlotsPerEpoch::try_from(cp)?)
+                    .map(|_| "slots_per_epoch")),
+                cp @ ConfigParam::SlotDuration(_) => Ok(changes
+                    .slot_duration
+                    .replace(SlotDuration::try_from(cp)?)
+                    .map(|_| "slot_duration")),
+                cp @ ConfigParam::KESUpdateSpeed(_) => Ok(changes
+                    .kes_update_speed
+                    .replace(KESUpdateSpeed::try_from(cp)?)
+                    .map(|_| "kes_update_speed")),
+                cp @ ConfigParam::ConsensusGenesisPraosActiveSlotsCoeff(_) => Ok(changes
+                    .consensus_genesis_praos_active_slot_coeff
+                    .replace(ActiveSlotCoefficient::try_from(cp)?)
+                    .map(|_| "consensus_genesis_praos_active_slot_coeff")),
+                ConfigParam::BlockContentMaxSize(param) => Ok(changes
+                    .block_content_max_size
+                    .replace(param.into())
+                    .map(|_| "block_content_max_size")),
+                ConfigParam::EpochStabilityDepth(param) => Ok(changes
+                    .epoch_stability_depth
+                    .replace(param.into())
+                    .map(|_| "epoch_stability_depth")),
+                cp @ ConfigParam::FeesInTreasury(_) => Ok(changes
+                    .fees_go_to
+                    .replace(FeesGoTo::try_from(cp)?)
+                    .map(|_| "fees_go_to")),
+                ConfigParam::TreasuryParams(param) => Ok(changes
+                    .treasury_parameters
+                    .replace(param.into())
+                    .map(|_| "treasury_parameters")),
+                ConfigParam::RewardParams(param) => Ok(changes
+                    .reward_parameters
+                    .replace(param.into())
+                    .map(|_| "reward_parameters")),
+                ConfigParam::ProposalExpiration(param) => Ok(changes
+                    .proposal_expiration
+                    .replace(param)
+                    .map(|_| "proposal_expiration")),
+                ConfigParam::AddBftLeader(leader_id) => {
+                    changes
+                        .add_consensus_leader_ids
+                        .push(ConsensusLeaderId(leader_id));
+                    Ok(None)
+                }
+                ConfigParam::RemoveBftLeader(leader_id) => {
+                    changes
+                        .remove_consensus_leader_ids
+                        .push(ConsensusLeaderId(leader_id));
+                    Ok(None)
+                }
+                ConfigParam::Block0Date(_) => unsupported("block0_date"),
+                ConfigParam::Discrimination(_) => unsupported("discrimination"),
+                ConfigParam::ConsensusVersion(_) => unsupported("block0_consensus"),
+                ConfigParam::TreasuryAdd(_) => unsupported("treasury"),
+                ConfigParam::RewardPot(_) => unsupported("total_reward_supply"),
+                ConfigParam::RewardLimitNone
+                | ConfigParam::RewardLimitByAbsoluteStake(_)
+                | ConfigParam::PoolRewardParticipationCapping(_) => {
+                    unsupported("reward_constraints")
+                }
+                ConfigParam::AddCommitteeId(_) | ConfigParam::RemoveCommitteeId(_) => {
+                    unsupported("committees")
+                }
+            }?
+            .map(|name| Err(ProposalChangesError::Duplicate { name }))
+            .unwrap_or(Ok(()))?;
+        }
+
+        if let Some(linear_fees) = &mut changes.linear_fees {
+            if let Some(per_certificate_fees) = per_certificate_fees {
+                linear_fees.per_certificate_fees(per_certificate_fees);
+            }
+            if let Some(per_vote_certificate_fees) = per_vote_certificate_fees {
+                linear_fees.per_vote_certificate_fees(per_vote_certificate_fees);
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+impl From<UpdateProposal> for Fragment {
+    fn from(update_proposal: UpdateProposal) -> Self {
+        Fragment::UpdateProposal(SignedUpdateProposal {
+            proposal: UpdateProposalWithProposer {
+                proposal: UpdateProposalStd {
+                    changes: update_proposal.changes.into(),
+                },
+                proposer_id: update_proposal.proposer_id.0,
+            },
+        })
+    }
+}
+
+impl From<UpdateVote> for Fragment {
+    fn from(update_vote: UpdateVote) -> Self {
+        Fragment::UpdateVote(SignedUpdateVote {
+            vote: UpdateVoteStd {
+                proposal_id: update_vote.proposal_id.into_hash(),
+                voter_id: update_vote.voter_id.0,
+            },
+        })
+    }
+}
+
+mod optional_linear_fee {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Wrapper(#[serde(with = "LinearFeeDef")] LinearFee);
+
+    pub fn serialize<S>(linear_fee: &Option<LinearFee>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        linear_fee.map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<LinearFee>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<Wrapper>::deserialize(deserializer).map(|wrapper| wrapper.map(|w| w.0))
+    }
+}
@@ -0,0 +1,437 @@
+//! Export of blocks of the storage to a file, and import of such file,
+//! so new nodes can be seeded without pulling the chain from the network.
+//!
+//! The archive starts with a versioned header holding the hash of the
+//! block0 of the chain, so an archive of another chain is rejected before
+//! any block is applied. The header is followed by the blocks, each one
+//! prefixed by its length in bytes (as a big endian `u32`). A length larger
+//! than the block size limit of the chain is rejected before the block is
+//! read.
+//!
+//! The imported blocks go through the same checks as the blocks received
+//! during the network bootstrap, with their header proofs verified ahead by
+//! the `HeaderProofPipeline`.
+
+use crate::{
+    blockcfg::{Block, HeaderHash},
+    blockchain::{
+        process_new_ref, Blockchain, CheckHeaderProof, Error as BlockchainError,
+        HeaderProofPipeline, PreCheckedHeader, Ref, Storage, StorageError, Tip,
+        DEFAULT_VERIFICATION_WINDOW,
+    },
+    intercom,
+};
+use chain_core::property::{Deserialize, Serialize};
+use futures::prelude::*;
+use thiserror::Error;
+
+use std::{
+    convert::TryFrom,
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+const ARCHIVE_MAGIC: &[u8; 8] = b"JORMBLKS";
+const ARCHIVE_VERSION: u32 = 1;
+
+const PROCESS_LOGGING_DISTANCE: u64 = 2500;
+
+/// an upper bound of the size of a serialized block header, the largest
+/// ones being the Genesis-Praos headers with their VRF proof and KES
+/// signature
+const BLOCK_HEADER_MAX_SIZE: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while accessing the block archive")]
+    Io(#[from] io::Error),
+    #[error("the file is not a block archive")]
+    InvalidMagic,
+    #[error("unsupported block archive version {0}, expected {}", ARCHIVE_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("the block archive is for the chain of block0 {got}, expected {expected}")]
+    Block0Mismatch {
+        expected: HeaderHash,
+        got: HeaderHash,
+    },
+    #[error("cannot read the blocks to export from the storage")]
+    Storage(#[from] StorageError),
+    #[error("error while streaming the blocks to export")]
+    Stream(#[source] intercom::Error),
+    #[error("block {0} is too large to be archived")]
+    BlockTooLarge(HeaderHash),
+    #[error("the block archive has a block of {len} bytes, larger than the limit of {max} bytes")]
+    InvalidBlockLength { len: usize, max: usize },
+    #[error("cannot serialize block")]
+    Serialize(#[source] io::Error),
+    #[error("cannot deserialize block")]
+    Deserialize(#[source] io::Error),
+    #[error("imported block {0} is not connected to the block chain")]
+    BlockMissingParent(HeaderHash),
+    #[error("imported block {0} is already present, but is not on a known branch")]
+    BlockNotOnBranch(HeaderHash),
+    #[error("imported block header check failed")]
+    HeaderCheckFailed(#[source] BlockchainError),
+    #[error("failed to apply imported block to the blockchain")]
+    ApplyBlockFailed(#[source] BlockchainError),
+    #[error("failed to select the new tip")]
+    ChainSelectionFailed(#[source] BlockchainError),
+}
+
+/// write the blocks of the storage after `from` up to `to` (included) to
+/// the file at `output`. Returns the number of blocks written.
+pub async fn export_blocks<P: AsRef<Path>>(
+    storage: &Storage,
+    block0: HeaderHash,
+    from: HeaderHash,
+    to: HeaderHash,
+    output: P,
+) -> Result<u64, Error> {
+    let mut blocks = Box::pin(storage.stream_from_to(from, to)?);
+
+    let mut writer = BufWriter::new(fs::File::create(output)?);
+    writer.write_all(ARCHIVE_MAGIC)?;
+    writer.write_all(&ARCHIVE_VERSION.to_be_bytes())?;
+    writer.write_all(block0.as_bytes())?;
+
+    let mut count = 0;
+    while let Some(block) = blocks.next().await {
+        let block = block.map_err(Error::Stream)?;
+        let bytes = block.serialize_as_vec().map_err(Error::Serialize)?;
+        let len =
+            u32::try_from(bytes.len()).map_err(|_| Error::BlockTooLarge(block.header.hash()))?;
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(&bytes)?;
+
+        count += 1;
+        if count % PROCESS_LOGGING_DISTANCE == 0 {
+            tracing::info!(
+                "exporting blocks, currently at {}",
+                block.header.description()
+            );
+        }
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// apply the blocks of the archive at `input` and update the tip with the
+/// last of them. Returns the number of blocks read from the archive.
+///
+/// The blocks that are already in the storage are checked again, so an
+/// archive can be imported in a storage that already has some of its blocks.
+pub async fn import_blocks<P: AsRef<Path>>(
+    mut blockchain: Blockchain,
+    tip: Tip,
+    input: P,
+) -> Result<u64, Error> {
+    let mut reader = BufReader::new(fs::File::open(input)?);
+
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(Error::InvalidMagic);
+    }
+    let mut version = [0; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_be_bytes(version);
+    if version != ARCHIVE_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    let mut hash = [0; 32];
+    reader.read_exact(&mut hash)?;
+    let got = HeaderHash::deserialize(&hash[..]).map_err(Error::Deserialize)?;
+    let block0 = *blockchain.block0();
+    if got != block0 {
+        return Err(Error::Block0Mismatch {
+            expected: block0,
+            got,
+        });
+    }
+
+    let mut count = 0;
+    let mut last_applied = None;
+
+    let tip_ref = tip.get_ref().await;
+    let mut pipeline = HeaderProofPipeline::new(DEFAULT_VERIFICATION_WINDOW);
+    pipeline.applied(&tip_ref);
+    let mut archive_ended = false;
+    let mut read_error = None;
+
+    let result = loop {
+        let max_len = block_size_limit(last_applied.as_ref().unwrap_or(&tip_ref));
+        while !archive_ended && !pipeline.is_full() {
+            match read_block(&mut reader, max_len) {
+                Ok(Some(block)) if block.header.hash() == block0 => {}
+                Ok(Some(block)) => pipeline.push(block),
+                Ok(None) => archive_ended = true,
+                Err(err) => {
+                    archive_ended = true;
+                    read_error = Some(err);
+                }
+            }
+        }
+
+        let (block, check_header_proof) = match pipeline.next().await {
+            Some(Ok(next)) => next,
+            Some(Err(err)) => break Err(Error::HeaderCheckFailed(err)),
+            None => break read_error.map_or(Ok(()), Err),
+        };
+
+        match import_block(&blockchain, block, check_header_proof).await {
+            Ok(block_ref) => {
+                count += 1;
+                if count % PROCESS_LOGGING_DISTANCE == 0 {
+                    tracing::info!(
+                        "importing blocks, currently at {}",
+                        block_ref.header().description()
+                    );
+                }
+                pipeline.applied(&block_ref);
+                last_applied = Some(block_ref);
+            }
+            Err(err) => break Err(err),
+        }
+    };
+
+    // the tip is updated with the blocks applied so far even if the
+    // import failed, the import can then be resumed from there
+    if let Some(block_ref) = last_applied {
+        process_new_ref(&mut blockchain, tip, block_ref, None, None)
+            .await
+            .map_err(Error::ChainSelectionFailed)?;
+    }
+
+    result.map(|()| count)
+}
+
+/// the maximum size of a serialized block following `block_ref`
+fn block_size_limit(block_ref: &Ref) -> usize {
+    BLOCK_HEADER_MAX_SIZE + block_ref.epoch_ledger_parameters().block_content_max_size as usize
+}
+
+/// read the next block of the archive, `None` at the end of the archive.
+/// Blocks longer than `max_len` bytes are rejected.
+fn read_block<R: Read>(reader: &mut R, max_len: usize) -> Result<Option<Block>, Error> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_len {
+        return Err(Error::InvalidBlockLength { len, max: max_len });
+    }
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Block::deserialize(bytes.as_slice())
+        .map(Some)
+        .map_err(Error::Deserialize)
+}
+
+async fn import_block(
+    blockchain: &Blockchain,
+    block: Block,
+    check_header_proof: CheckHeaderProof,
+) -> Result<Arc<Ref>, Error> {
+    let header = block.header.clone();
+    let pre_checked = blockchain
+        .pre_check_header(header, true)
+        .await
+        .map_err(Error::HeaderCheckFailed)?;
+    match pre_checked {
+        PreCheckedHeader::AlreadyPresent {
+            cached_reference: Some(block_ref),
+            ..
+        } => Ok(block_ref),
+        PreCheckedHeader::AlreadyPresent {
+            cached_reference: None,
+            header,
+        } => Err(Error::BlockNotOnBranch(header.hash())),
+        PreCheckedHeader::MissingParent { header } => Err(Error::BlockMissingParent(header.hash())),
+        PreCheckedHeader::HeaderWithCache { header, parent_ref } => {
+            let post_checked = blockchain
+                .post_check_header(header, parent_ref, check_header_proof)
+                .await
+                .map_err(Error::HeaderCheckFailed)?;
+            let applied = blockchain
+                .apply_and_store_block(post_checked, block)
+                .await
+                .map_err(Error::ApplyBlockFailed)?;
+            Ok(applied.cached_ref())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{
+        BlockDate, BlockVersion, ConsensusVersion, ContentsBuilder, HeaderBuilderNew,
+    };
+    use chain_addr::Discrimination;
+    use chain_core::property::ChainLength as _;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{fee::LinearFee, key::BftLeaderId};
+    use jormungandr_lib::interfaces::{
+        Block0Configuration, BlockchainConfiguration, ConsensusLeaderId,
+    };
+    use tempfile::TempDir;
+    use tracing::Span;
+
+    /// build a block0 with a single BFT leader and `length` empty blocks
+    /// signed by this leader
+    fn chain(length: u32) -> (Block, Vec<Block>) {
+        let leader_key = SecretKey::<Ed25519>::generate(rand_core::OsRng);
+        let leader_id = BftLeaderId::from(leader_key.to_public());
+
+        let mut blockchain_configuration = BlockchainConfiguration::new(
+            Discrimination::Test,
+            ConsensusVersion::Bft,
+            LinearFee::new(0, 0, 0),
+        );
+        blockchain_configuration
+            .consensus_leader_ids
+            .push(ConsensusLeaderId(leader_id.clone()));
+        let block0 = Block0Configuration {
+            blockchain_configuration,
+            initial: Vec::new(),
+        }
+        .to_block();
+
+        let mut parent = block0.header.clone();
+        let blocks = (1..=length)
+            .map(|slot_id| {
+                let contents = ContentsBuilder::new().into();
+                let builder = HeaderBuilderNew::new(BlockVersion::Ed25519Signed, &contents)
+                    .set_parent(&parent.hash(), parent.chain_length().next())
+                    .set_date(BlockDate { epoch: 0, slot_id })
+                    .into_bft_builder()
+                    .unwrap()
+                    .set_consensus_data(&leader_id);
+                let signature = leader_key.sign_slice(builder.get_authenticated_data());
+                let header = builder.set_signature(signature.into()).generalize();
+                parent = header.clone();
+                Block { header, contents }
+            })
+            .collect();
+
+        (block0, blocks)
+    }
+
+    async fn new_blockchain(block0: &Block) -> (Blockchain, Tip) {
+        let blockchain = Blockchain::new(
+            block0.header.hash(),
+            Storage::memory(Span::none()).unwrap(),
+            1024,
+            false,
+            None,
+            None,
+        );
+        let branch = blockchain.load_from_block0(block0.clone()).await.unwrap();
+        (blockchain, Tip::new(branch))
+    }
+
+    fn write_archive(path: &Path, block0: HeaderHash, blocks: &[Block]) {
+        let mut writer = BufWriter::new(fs::File::create(path).unwrap());
+        writer.write_all(ARCHIVE_MAGIC).unwrap();
+        writer.write_all(&ARCHIVE_VERSION.to_be_bytes()).unwrap();
+        writer.write_all(block0.as_bytes()).unwrap();
+        for block in blocks {
+            let bytes = block.serialize_as_vec().unwrap();
+            writer
+                .write_all(&(bytes.len() as u32).to_be_bytes())
+                .unwrap();
+            writer.write_all(&bytes).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+
+    #[tokio::test]
+    async fn export_import_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let (block0, blocks) = chain(20);
+        let block0_hash = block0.header.hash();
+        let last = blocks.last().unwrap().header.hash();
+        let archive = dir.path().join("archive");
+        write_archive(&archive, block0_hash, &blocks);
+
+        let (blockchain, tip) = new_blockchain(&block0).await;
+        let imported = import_blocks(blockchain.clone(), tip.clone(), &archive)
+            .await
+            .unwrap();
+        assert_eq!(imported, 20);
+        assert_eq!(tip.get_ref().await.hash(), last);
+
+        let exported_archive = dir.path().join("exported");
+        let exported = export_blocks(
+            blockchain.storage(),
+            block0_hash,
+            block0_hash,
+            last,
+            &exported_archive,
+        )
+        .await
+        .unwrap();
+        assert_eq!(exported, 20);
+        assert_eq!(
+            fs::read(&archive).unwrap(),
+            fs::read(&exported_archive).unwrap()
+        );
+
+        let (blockchain, tip) = new_blockchain(&block0).await;
+        import_blocks(blockchain, tip.clone(), &exported_archive)
+            .await
+            .unwrap();
+        assert_eq!(tip.get_ref().await.hash(), last);
+    }
+
+    #[tokio::test]
+    async fn archive_of_another_block0_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let (block0, _) = chain(0);
+        let (other_block0, other_blocks) = chain(5);
+        let archive = dir.path().join("archive");
+        write_archive(&archive, other_block0.header.hash(), &other_blocks);
+
+        let (blockchain, tip) = new_blockchain(&block0).await;
+        let result = import_blocks(blockchain, tip.clone(), &archive).await;
+
+        assert!(matches!(result, Err(Error::Block0Mismatch { .. })));
+        assert_eq!(tip.get_ref().await.hash(), block0.header.hash());
+    }
+
+    #[tokio::test]
+    async fn truncated_archive_keeps_the_blocks_read() {
+        let dir = TempDir::new().unwrap();
+        let (block0, blocks) = chain(20);
+        let archive = dir.path().join("archive");
+        write_archive(&archive, block0.header.hash(), &blocks);
+        let file = fs::OpenOptions::new().write(true).open(&archive).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - 10).unwrap();
+
+        let (blockchain, tip) = new_blockchain(&block0).await;
+        let result = import_blocks(blockchain, tip.clone(), &archive).await;
+
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert_eq!(tip.get_ref().await.hash(), blocks[18].header.hash());
+    }
+
+    #[test]
+    fn oversized_block_is_rejected_before_reading_it() {
+        let mut archive: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0, 0];
+
+        assert!(matches!(
+            read_block(&mut archive, 1024),
+            Err(Error::InvalidBlockLength {
+                len: 0xffff_ffff,
+                max: 1024
+            })
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::blockchain::Ref;
 use futures::stream::{FuturesUnordered, StreamExt};
-use std::sync::Arc;
+use std::{sync::Arc, time::SystemTime};
 use tokio::sync::RwLock;
 
 #[derive(Clone)]
@@ -21,6 +21,9 @@ pub struct Branch {
 struct BranchData {
     /// reference to the block where the branch points to
     reference: Arc<Ref>,
+
+    /// last time the branch was updated
+    last_updated: SystemTime,
 }
 
 impl Default for Branches {
@@ -56,6 +59,21 @@ impl Branches {
         guard.branches().await
     }
 
+    /// the tips of the branches, along with the last time they were updated
+    pub async fn branches_last_updated(&self) -> Vec<(Arc<Ref>, SystemTime)> {
+        let guard = self.inner.read().await;
+        guard.branches_last_updated().await
+    }
+
+    /// only keep the branches whose tip satisfies the predicate
+    pub async fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Ref) -> bool,
+    {
+        let mut guard = self.inner.write().await;
+        guard.retain(f).await
+    }
+
     async fn apply(&mut self, candidate: Arc<Ref>) -> Option<Branch> {
         let mut guard = self.inner.write().await;
         guard.apply(candidate).await
@@ -85,6 +103,19 @@ impl BranchesData {
         value
     }
 
+    async fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Ref) -> bool,
+    {
+        let mut kept = Vec::with_capacity(self.branches.len());
+        for branch in self.branches.drain(..) {
+            if f(branch.get_ref().await.as_ref()) {
+                kept.push(branch);
+            }
+        }
+        self.branches = kept;
+    }
+
     async fn branches(&self) -> Vec<Arc<Ref>> {
         self.branches
             .iter()
@@ -94,6 +125,15 @@ impl BranchesData {
             .collect()
             .await
     }
+
+    async fn branches_last_updated(&self) -> Vec<(Arc<Ref>, SystemTime)> {
+        self.branches
+            .iter()
+            .map(|b| b.get_ref_last_updated())
+            .collect::<FuturesUnordered<_>>()
+            .collect()
+            .await
+    }
 }
 
 impl Branch {
@@ -108,6 +148,16 @@ impl Branch {
         guard.reference()
     }
 
+    pub async fn last_updated(&self) -> SystemTime {
+        let guard = self.inner.read().await;
+        guard.last_updated
+    }
+
+    async fn get_ref_last_updated(&self) -> (Arc<Ref>, SystemTime) {
+        let guard = self.inner.read().await;
+        (guard.reference(), guard.last_updated)
+    }
+
     pub async fn update_ref(&mut self, new_ref: Arc<Ref>) -> Arc<Ref> {
         let mut guard = self.inner.write().await;
         guard.update(new_ref)
@@ -127,10 +177,14 @@ impl BranchData {
     /// create the branch data with the current `last_updated` to
     /// the current time this function was called
     fn new(reference: Arc<Ref>) -> Self {
-        BranchData { reference }
+        BranchData {
+            reference,
+            last_updated: SystemTime::now(),
+        }
     }
 
     fn update(&mut self, reference: Arc<Ref>) -> Arc<Ref> {
+        self.last_updated = SystemTime::now();
         std::mem::replace(&mut self.reference, reference)
     }
 
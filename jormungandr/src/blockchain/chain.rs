@@ -49,7 +49,13 @@ See Internal documentation for more details: doc/internal_design.md
 [`Branch`]: ./struct.Branch.html
 */
 #![allow(clippy::large_enum_variant)]
-use super::{branch::Branches, reference_cache::RefCache};
+use super::{
+    branch::Branches,
+    reference_cache::RefCache,
+    snapshot::{LedgerSnapshot, LedgerSnapshots, LEDGER_SNAPSHOT_TAG},
+    storage::PRUNING_POINT_TAG,
+    tip_events::TipEvents,
+};
 use crate::{
     blockcfg::{
         Block, Block0Error, BlockDate, ChainLength, Epoch, EpochRewardsInfo, Header, HeaderHash,
@@ -59,7 +65,8 @@ use crate::{
 };
 use chain_impl_mockchain::{leadership::Verification, ledger};
 use chain_time::TimeFrame;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
 use tokio_stream::StreamExt;
 
 // derive
@@ -70,6 +77,7 @@ error_chain! {
         Storage(StorageError);
         Ledger(ledger::Error);
         Block0(Block0Error);
+        LedgerSnapshot(super::snapshot::Error);
     }
 
     errors {
@@ -85,6 +93,19 @@ error_chain! {
             description("Block0 is not yet in the storage")
         }
 
+        Block0PrunedFromStorage {
+            description("Block0 has been pruned from the storage")
+        }
+
+        PrunedStorageWithoutSnapshot {
+            description("No usable ledger snapshot in the pruned storage"),
+            display(
+                "The storage has been pruned but none of its ledger snapshots \
+                 can be used to restore the ledger state, the storage needs \
+                 to be removed and the node bootstrapped again",
+            ),
+        }
+
         MissingParentBlock(hash: HeaderHash) {
             description("missing a parent block from the storage"),
             display(
@@ -113,6 +134,26 @@ error_chain! {
         CannotApplyBlock {
             description("Block cannot be applied on top of the previous block's ledger state"),
         }
+
+        RollbackTargetNotAncestor (hash: HeaderHash) {
+            description("The block to roll back to is not an ancestor of the tip"),
+            display("Block {} is not an ancestor of the current tip", hash),
+        }
+
+        RollbackTooDeep (depth: u32, max_depth: u32) {
+            description("The rollback is deeper than allowed"),
+            display(
+                "Rolling back to the block would remove {} blocks from the main branch, \
+                 the maximum allowed is {}",
+                depth,
+                max_depth,
+            ),
+        }
+
+        RollbackTargetNotLoaded (hash: HeaderHash) {
+            description("The state at the block to roll back to cannot be rebuilt"),
+            display("The ledger state at block {} cannot be rebuilt", hash),
+        }
     }
 }
 
@@ -183,6 +224,20 @@ pub struct Blockchain {
     block0: HeaderHash,
 
     rewards_report_all: bool,
+
+    /// the ledger snapshots of the storage, only available when
+    /// the storage is persisted on disk
+    snapshots: Option<LedgerSnapshots>,
+
+    /// number of epochs of blocks to keep when the storage is pruned
+    pruning_keep_epochs: Option<u32>,
+
+    /// notifications of the changes of the tip
+    tip_events: TipEvents,
+
+    /// the first blocks of the branches rolled back by the node operator,
+    /// with their chain length
+    rejected_branches: Arc<RwLock<HashMap<HeaderHash, u32>>>,
 }
 
 pub enum PreCheckedHeader {
@@ -224,6 +279,7 @@ pub enum PreCheckedHeader {
 pub struct PostCheckedHeader {
     header: Header,
     epoch_leadership_schedule: Arc<Leadership>,
+    epoch_leadership_state: Arc<Ledger>,
     epoch_ledger_parameters: Arc<LedgerParameters>,
     parent_ledger_state: Arc<Ledger>,
     time_frame: Arc<TimeFrame>,
@@ -261,6 +317,12 @@ impl AppliedBlock {
 #[derive(Clone, PartialEq, Eq)]
 pub enum CheckHeaderProof {
     SkipFromStorage,
+    /// the proof was already verified, ahead of the block application,
+    /// by the `HeaderProofPipeline`
+    Verified,
+    /// the block is below the trusted checkpoint, and is authenticated by
+    /// the chain of hashes leading to it
+    SkipBelowTrustedCheckpoint,
     Enabled,
 }
 
@@ -270,6 +332,8 @@ impl Blockchain {
         storage: Storage,
         cache_capacity: usize,
         rewards_report_all: bool,
+        snapshots: Option<LedgerSnapshots>,
+        pruning_keep_epochs: Option<u32>,
     ) -> Self {
         Blockchain {
             branches: Branches::new(),
@@ -278,6 +342,10 @@ impl Blockchain {
             storage,
             block0,
             rewards_report_all,
+            snapshots,
+            pruning_keep_epochs,
+            tip_events: TipEvents::default(),
+            rejected_branches: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -285,6 +353,42 @@ impl Blockchain {
         &self.block0
     }
 
+    pub fn tip_events(&self) -> &TipEvents {
+        &self.tip_events
+    }
+
+    /// reject the branch starting at `first_block`, the chain selection does
+    /// not switch to a block of this branch again
+    pub async fn reject_branch(&self, first_block: HeaderHash, chain_length: ChainLength) {
+        self.rejected_branches
+            .write()
+            .await
+            .insert(first_block, u32::from(chain_length));
+    }
+
+    /// whether `candidate` is in a branch rejected with `reject_branch`
+    ///
+    /// The rejected branches forking from `tip` deeper than the epoch
+    /// stability depth are forgotten, the chain selection does not switch
+    /// to them anyway.
+    pub async fn is_rejected(&self, tip: &Ref, candidate: &Ref) -> bool {
+        let depth = tip.epoch_ledger_parameters().epoch_stability_depth;
+        let tip_length = u32::from(tip.chain_length());
+        let mut rejected_branches = self.rejected_branches.write().await;
+        rejected_branches
+            .retain(|_, chain_length| tip_length.saturating_sub(*chain_length) < depth);
+        if rejected_branches.is_empty() {
+            return false;
+        }
+
+        let first_blocks = rejected_branches.keys().copied().collect();
+        matches!(
+            self.storage
+                .find_closest_ancestor(first_blocks, candidate.hash()),
+            Ok(Some(_))
+        )
+    }
+
     pub fn storage(&self) -> &Storage {
         &self.storage
     }
@@ -304,6 +408,215 @@ impl Blockchain {
         Ok(())
     }
 
+    /// write a snapshot of the ledger state at the last block of the most
+    /// recent epoch that is deeper than the epoch stability depth from the
+    /// given tip, and tag it in the storage.
+    ///
+    /// When the storage is pruned, the snapshot is taken at the last block
+    /// before the epochs of blocks to keep instead, and that block is tagged
+    /// as the pruning point of the storage.
+    ///
+    /// Nothing is done if the storage is not persisted on disk or if the
+    /// snapshot of that block was already written.
+    pub async fn snapshot_ledger(&self, tip: Arc<Ref>) -> Result<()> {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots,
+            None => return Ok(()),
+        };
+
+        let depth = tip.epoch_ledger_parameters().epoch_stability_depth;
+        let stable_chain_length = match u32::from(tip.chain_length()).checked_sub(depth) {
+            Some(chain_length) => chain_length,
+            None => return Ok(()),
+        };
+
+        let last_epoch = match self.pruning_keep_epochs {
+            Some(keep_epochs) => tip.block_date().epoch.saturating_sub(keep_epochs),
+            None => tip.block_date().epoch,
+        };
+
+        let mut current = tip.last_ref_previous_epoch().cloned();
+        let stable = loop {
+            match current {
+                None => return Ok(()),
+                Some(r)
+                    if u32::from(r.chain_length()) <= stable_chain_length
+                        && r.block_date().epoch <= last_epoch =>
+                {
+                    break r
+                }
+                Some(r) => current = r.last_ref_previous_epoch().cloned(),
+            }
+        };
+
+        if self.storage.get_tag(LEDGER_SNAPSHOT_TAG)? == Some(stable.hash()) {
+            return Ok(());
+        }
+
+        let snapshot = LedgerSnapshot {
+            block_hash: stable.hash(),
+            ledger: stable.ledger().as_ref().clone(),
+            epoch_leadership_state: stable.epoch_leadership_state().as_ref().clone(),
+            previous_epoch: stable.last_ref_previous_epoch().map(|previous| {
+                (
+                    previous.header().clone(),
+                    previous.ledger().as_ref().clone(),
+                )
+            }),
+        };
+
+        // serializing and syncing the whole ledger takes a while, so it is
+        // done out of the runtime workers
+        let snapshots = snapshots.clone();
+        let block0 = self.block0;
+        let chain_length = stable.chain_length();
+        tokio::task::spawn_blocking(move || snapshots.write(&block0, chain_length, &snapshot))
+            .await
+            .map_err(|e| Error::from(format!("the ledger snapshot task failed: {}", e)))??;
+        self.storage
+            .put_tag(LEDGER_SNAPSHOT_TAG, stable.hash())
+            .map_err(|e| Error::with_chain(e, "Cannot tag the ledger snapshot"))?;
+        if self.pruning_keep_epochs.is_some() {
+            self.storage
+                .put_tag(PRUNING_POINT_TAG, stable.hash())
+                .map_err(|e| Error::with_chain(e, "Cannot tag the pruning point"))?;
+        }
+
+        tracing::info!(
+            "ledger snapshot written at {}",
+            stable.header().description()
+        );
+
+        Ok(())
+    }
+
+    /// remove from the storage the blocks before the pruning point tagged
+    /// by `snapshot_ledger`. Nothing is done if the storage is not pruned.
+    pub async fn prune_storage(&self) -> Result<()> {
+        if self.pruning_keep_epochs.is_none() {
+            return Ok(());
+        }
+
+        // the blocks are copied to a new storage, which takes a while
+        let storage = self.storage.clone();
+        tokio::task::spawn_blocking(move || storage.prune())
+            .await
+            .map_err(|e| Error::from(format!("the storage pruning task failed: {}", e)))?
+            .map_err(|e| Error::with_chain(e, "Cannot prune the storage"))
+    }
+
+    /// rebuild the `Ref` of the most recent valid ledger snapshot that is
+    /// an ancestor of `head_hash`. The snapshot tagged in the storage is
+    /// tried first, then the other snapshots from the most recent.
+    ///
+    /// Returns `None` if no snapshot can be used, in which case the
+    /// chain needs to be replayed from the block0.
+    pub(super) async fn load_ledger_snapshot(
+        &self,
+        block0_ref: &Arc<Ref>,
+        head_hash: HeaderHash,
+    ) -> Option<Arc<Ref>> {
+        let snapshots = self.snapshots.as_ref()?;
+
+        let mut candidates = match snapshots.list() {
+            Ok(candidates) => candidates,
+            Err(err) => {
+                tracing::warn!(reason = %err, "cannot list the ledger snapshots");
+                return None;
+            }
+        };
+        if let Ok(Some(tagged)) = self.storage.get_tag(LEDGER_SNAPSHOT_TAG) {
+            candidates.retain(|hash| hash != &tagged);
+            candidates.insert(0, tagged);
+        }
+
+        for block_hash in candidates {
+            match self
+                .restore_ledger_snapshot(snapshots, block0_ref, block_hash, head_hash)
+                .await
+            {
+                Ok(reference) => return Some(reference),
+                Err(err) => tracing::warn!(
+                    reason = %err,
+                    "cannot use the ledger snapshot of block {}",
+                    block_hash
+                ),
+            }
+        }
+
+        None
+    }
+
+    async fn restore_ledger_snapshot(
+        &self,
+        snapshots: &LedgerSnapshots,
+        block0_ref: &Arc<Ref>,
+        block_hash: HeaderHash,
+        head_hash: HeaderHash,
+    ) -> Result<Arc<Ref>> {
+        let on_main_branch = self
+            .storage
+            .find_closest_ancestor(vec![block_hash], head_hash)?
+            .is_some();
+        if !on_main_branch {
+            return Err("the snapshot block is not an ancestor of HEAD".into());
+        }
+
+        let snapshot = snapshots.read(&self.block0, &block_hash)?;
+        let header = self
+            .storage
+            .get(block_hash)?
+            .ok_or_else(|| ErrorKind::MissingParentBlock(block_hash))?
+            .header;
+
+        let time_frame = Arc::clone(block0_ref.time_frame());
+        let leadership = Arc::new(Leadership::new(
+            header.block_date().epoch,
+            &snapshot.epoch_leadership_state,
+        ));
+        let ledger_parameters = Arc::new(leadership.ledger_parameters().clone());
+        let leadership_state = Arc::new(snapshot.epoch_leadership_state);
+
+        let previous_epoch_state = match snapshot.previous_epoch {
+            None => None,
+            Some((previous_header, previous_ledger)) => {
+                // only the ledger state of the previous epoch is used when
+                // computing the next epoch transition, so the leadership
+                // schedule of the snapshot is reused here
+                let previous = self
+                    .create_and_store_reference(
+                        previous_header.hash(),
+                        previous_header,
+                        previous_ledger,
+                        Arc::clone(&time_frame),
+                        Arc::clone(&leadership),
+                        Arc::clone(&leadership_state),
+                        None,
+                        Arc::clone(&ledger_parameters),
+                        None,
+                    )
+                    .await;
+                Some(previous)
+            }
+        };
+
+        // the rewards info of the snapshot's epoch are not persisted, they
+        // will be available again after the next epoch transition
+        Ok(self
+            .create_and_store_reference(
+                block_hash,
+                header,
+                snapshot.ledger,
+                time_frame,
+                leadership,
+                leadership_state,
+                None,
+                ledger_parameters,
+                previous_epoch_state,
+            )
+            .await)
+    }
+
     /// create and store a reference of this leader to the new
     #[allow(clippy::too_many_arguments)]
     async fn create_and_store_reference(
@@ -313,6 +626,7 @@ impl Blockchain {
         ledger: Ledger,
         time_frame: Arc<TimeFrame>,
         leadership: Arc<Leadership>,
+        leadership_state: Arc<Ledger>,
         epoch_rewards_info: Option<Arc<EpochRewardsInfo>>,
         ledger_parameters: Arc<LedgerParameters>,
         previous_epoch_state: Option<Arc<Ref>>,
@@ -327,6 +641,7 @@ impl Blockchain {
             ledger_ref,
             time_frame,
             leadership,
+            leadership_state,
             ledger_parameters,
             epoch_rewards_info,
             header,
@@ -435,6 +750,7 @@ impl Blockchain {
         let EpochLeadership {
             state: parent_ledger_state,
             leadership: epoch_leadership_schedule,
+            leadership_state: epoch_leadership_state,
             ledger_parameters: epoch_ledger_parameters,
             rewards_info: epoch_rewards_info,
             time_frame,
@@ -453,6 +769,7 @@ impl Blockchain {
         Ok(PostCheckedHeader {
             header,
             epoch_leadership_schedule,
+            epoch_leadership_state,
             epoch_ledger_parameters,
             epoch_rewards_info,
             parent_ledger_state,
@@ -510,6 +827,7 @@ impl Blockchain {
         let header = post_checked_header.header;
         let block_id = header.hash();
         let epoch_leadership_schedule = post_checked_header.epoch_leadership_schedule;
+        let epoch_leadership_state = post_checked_header.epoch_leadership_state;
         let epoch_rewards_info = post_checked_header.epoch_rewards_info;
         let epoch_ledger_parameters = post_checked_header.epoch_ledger_parameters;
         let time_frame = post_checked_header.time_frame;
@@ -521,6 +839,7 @@ impl Blockchain {
             new_ledger,
             time_frame,
             epoch_leadership_schedule,
+            epoch_leadership_state,
             epoch_rewards_info,
             epoch_ledger_parameters,
             previous_epoch_state,
@@ -598,6 +917,7 @@ impl Blockchain {
             .map_err(|err| Error::with_chain(err, ErrorKind::Block0InitialLedgerError))?;
         let block0_leadership = Leadership::new(block0_date.epoch, &block0_ledger);
         let ledger_parameters = block0_leadership.ledger_parameters().clone();
+        let block0_leadership_state = Arc::new(block0_ledger.clone());
 
         let b = self
             .create_and_store_reference(
@@ -606,6 +926,7 @@ impl Blockchain {
                 block0_ledger,
                 Arc::new(time_frame),
                 Arc::new(block0_leadership),
+                block0_leadership_state,
                 None, // block0 has no reward distribution
                 Arc::new(ledger_parameters),
                 None,
@@ -628,6 +949,7 @@ impl Blockchain {
     /// The resulted future may fail if
     ///
     /// * the block0 already exists in the storage: `ErrorKind::Block0AlreadyInStorage`;
+    /// * the block0 has been pruned from the storage: `ErrorKind::Block0PrunedFromStorage`;
     /// * the block0 does build a valid `Ledger`: `ErrorKind::Block0InitialLedgerError`;
     /// * other errors while interacting with the storage (IO errors)
     ///
@@ -645,6 +967,15 @@ impl Blockchain {
             return Err(ErrorKind::Block0AlreadyInStorage.into());
         }
 
+        let pruned = self
+            .storage
+            .is_pruned()
+            .map_err(|e| Error::with_chain(e, "Cannot check if the storage is pruned"))?;
+
+        if pruned {
+            return Err(ErrorKind::Block0PrunedFromStorage.into());
+        }
+
         let block0_branch = self.apply_block0(&block0).await?;
 
         self.storage
@@ -660,6 +991,10 @@ impl Blockchain {
     /// from the block0 to the `Head` of the storage (the last known block which
     /// made consensus).
     ///
+    /// If a valid ledger snapshot of a block of the main branch is available,
+    /// the states are restored from it and only the blocks after the snapshot
+    /// are replayed. A pruned storage can only be loaded this way.
+    ///
     /// The Future will returns a branch pointing to the `Head`.
     ///
     /// # Errors
@@ -667,6 +1002,8 @@ impl Blockchain {
     /// The resulted future may fail if
     ///
     /// * the block0 is not already in the storage: `ErrorKind::Block0NotAlreadyInStorage`;
+    /// * the storage is pruned and has no usable ledger snapshot:
+    ///   `ErrorKind::PrunedStorageWithoutSnapshot`;
     /// * the block0 does build a valid `Ledger`: `ErrorKind::Block0InitialLedgerError`;
     /// * other errors while interacting with the storage (IO errors)
     ///
@@ -678,7 +1015,13 @@ impl Blockchain {
             .map_err(|e| Error::with_chain(e, "Cannot check if block0 is in storage"))?;
 
         if !already_exist {
-            return Err(ErrorKind::Block0NotAlreadyInStorage.into());
+            let pruned = self
+                .storage
+                .is_pruned()
+                .map_err(|e| Error::with_chain(e, "Cannot check if the storage is pruned"))?;
+            if !pruned {
+                return Err(ErrorKind::Block0NotAlreadyInStorage.into());
+            }
         }
 
         let opt = self
@@ -692,15 +1035,29 @@ impl Blockchain {
             return Err(ErrorKind::NoTag(MAIN_BRANCH_TAG.to_owned()).into());
         };
 
-        let block0_branch = self.apply_block0(&block0).await?;
+        let mut branch = self.apply_block0(&block0).await?;
+
+        let block0_ref = branch.get_ref().await;
+        let from = match self.load_ledger_snapshot(&block0_ref, head_hash).await {
+            Some(snapshot_ref) => {
+                tracing::info!(
+                    "resuming from the ledger snapshot at {}",
+                    snapshot_ref.header().description()
+                );
+                let from = snapshot_ref.hash();
+                let _: Arc<Ref> = branch.update_ref(snapshot_ref).await;
+                from
+            }
+            None if already_exist => block0_id,
+            None => return Err(ErrorKind::PrunedStorageWithoutSnapshot.into()),
+        };
 
         let mut block_stream = self
             .storage
-            .stream_from_to(block0_id, head_hash)
+            .stream_from_to(from, head_hash)
             .map(Box::pin)
             .map_err(|e| Error::with_chain(e, "Cannot iterate blocks from block0 to HEAD"))?;
 
-        let mut branch = block0_branch;
         let mut count = 0u64;
 
         let mut block_processing = std::time::Duration::from_secs(0);
@@ -714,7 +1071,7 @@ impl Blockchain {
                     ))
                 }
                 Ok(block) => {
-                    let header = block.header.clone();
+                    let header = &block.header;
 
                     const PROCESS_LOGGING_DISTANCE: u64 = 2500;
                     if count % PROCESS_LOGGING_DISTANCE == 0 {
@@ -729,36 +1086,7 @@ impl Blockchain {
 
                     let block_process_start = std::time::SystemTime::now();
 
-                    let pre_checked_header: PreCheckedHeader =
-                        self.pre_check_header(header, true).await?;
-
-                    let post_checked_header = match pre_checked_header {
-                        PreCheckedHeader::HeaderWithCache { header, parent_ref } => {
-                            self.post_check_header(
-                                header,
-                                parent_ref,
-                                CheckHeaderProof::SkipFromStorage,
-                            )
-                            .await?
-                        }
-                        PreCheckedHeader::AlreadyPresent {
-                            header,
-                            cached_reference: _cached_reference,
-                        } => unreachable!(
-                            "block already present, this should not happen. {:#?}",
-                            header
-                        ),
-                        PreCheckedHeader::MissingParent { header } => {
-                            return Err(
-                                ErrorKind::MissingParentBlock(header.block_parent_hash()).into()
-                            )
-                        }
-                    };
-
-                    let new_ledger = self.apply_block_dry_run(&post_checked_header, &block)?;
-                    let new_ref = self
-                        .apply_block_finalize(post_checked_header, new_ledger)
-                        .await;
+                    let new_ref = self.apply_stored_block(&block).await?;
 
                     count += 1;
                     let _: Arc<Ref> = branch.update_ref(new_ref).await;
@@ -774,6 +1102,79 @@ impl Blockchain {
         Ok(branch)
     }
 
+    /// apply again a block of the storage, on top of the `Ref` of its parent
+    /// which must be in the reference cache. The block was checked when it
+    /// was stored, so its header proof is not checked again.
+    async fn apply_stored_block(&self, block: &Block) -> Result<Arc<Ref>> {
+        let pre_checked_header = self.pre_check_header(block.header.clone(), true).await?;
+
+        let post_checked_header = match pre_checked_header {
+            PreCheckedHeader::HeaderWithCache { header, parent_ref } => {
+                self.post_check_header(header, parent_ref, CheckHeaderProof::SkipFromStorage)
+                    .await?
+            }
+            PreCheckedHeader::AlreadyPresent {
+                header,
+                cached_reference: _cached_reference,
+            } => unreachable!(
+                "block already present, this should not happen. {:#?}",
+                header
+            ),
+            PreCheckedHeader::MissingParent { header } => {
+                return Err(ErrorKind::MissingParentBlock(header.block_parent_hash()).into())
+            }
+        };
+
+        let new_ledger = self.apply_block_dry_run(&post_checked_header, block)?;
+        Ok(self
+            .apply_block_finalize(post_checked_header, new_ledger)
+            .await)
+    }
+
+    /// get the `Ref` of a block of the storage, even if it is no longer in
+    /// the reference cache: the `Ref` is then rebuilt by applying again the
+    /// blocks from the closest ancestor that is still in the cache.
+    ///
+    /// Returns `None` if the block is not in the storage, or if no ancestor
+    /// within `max_depth` blocks of it is in the cache.
+    pub async fn load_ref(
+        &self,
+        header_hash: HeaderHash,
+        max_depth: u32,
+    ) -> Result<Option<Arc<Ref>>> {
+        if let Some(reference) = self.ref_cache.get(header_hash).await {
+            return Ok(Some(reference));
+        }
+
+        let mut blocks = Vec::new();
+        let mut current = header_hash;
+        loop {
+            if blocks.len() as u32 > max_depth {
+                return Ok(None);
+            }
+            let block = match self
+                .storage
+                .get(current)
+                .map_err(|e| Error::with_chain(e, "Cannot read the block from the storage"))?
+            {
+                Some(block) => block,
+                None => return Ok(None),
+            };
+            let parent = block.header.block_parent_hash();
+            blocks.push(block);
+            if self.ref_cache.get(parent).await.is_some() {
+                break;
+            }
+            current = parent;
+        }
+
+        let mut reference = None;
+        for block in blocks.iter().rev() {
+            reference = Some(self.apply_stored_block(block).await?);
+        }
+        Ok(reference)
+    }
+
     pub async fn get_checkpoints(&self, branch: &Branch) -> Checkpoints {
         Checkpoints::new_from(branch.get_ref().await)
     }
@@ -829,6 +1230,7 @@ fn write_reward_info(
 pub struct EpochLeadership {
     pub state: Arc<Ledger>,
     pub leadership: Arc<Leadership>,
+    pub leadership_state: Arc<Ledger>,
     pub ledger_parameters: Arc<LedgerParameters>,
     pub rewards_info: Option<Arc<EpochRewardsInfo>>,
     pub time_frame: Arc<TimeFrame>,
@@ -902,6 +1304,7 @@ pub fn new_epoch_leadership_from(
         EpochLeadership {
             state: transition_state,
             leadership,
+            leadership_state: epoch_state,
             ledger_parameters,
             rewards_info: epoch_rewards_info,
             time_frame: parent_time_frame,
@@ -911,6 +1314,7 @@ pub fn new_epoch_leadership_from(
         EpochLeadership {
             state: parent_ledger_state,
             leadership: parent_epoch_leadership_schedule,
+            leadership_state: parent.epoch_leadership_state().clone(),
             ledger_parameters: parent_epoch_ledger_parameters,
             rewards_info: parent_epoch_rewards_info,
             time_frame: parent_time_frame,
@@ -1,9 +1,15 @@
-use crate::blockchain::{Ref, Storage};
+use crate::{
+    blockcfg::{ChainLength, HeaderHash},
+    blockchain::{Ref, Storage, StorageError},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
 pub enum ComparisonResult {
     PreferCurrent,
     PreferCandidate,
+    /// the candidate would have been preferred but switching to it
+    /// requires rolling back more blocks than the epoch stability depth
+    RollbackTooDeep,
 }
 
 /// chose which of the two Ref is the most interesting to keep as a branch
@@ -14,17 +20,16 @@ pub enum ComparisonResult {
 /// work to make a choice as to which of these two Ref is the right choice.
 ///
 pub fn compare_against(storage: &Storage, current: &Ref, candidate: &Ref) -> ComparisonResult {
-    let epoch_stability_depth = current.epoch_ledger_parameters().epoch_stability_depth;
-
-    let rollback_possible =
-        check_rollback_up_to(epoch_stability_depth, storage, current, candidate);
+    if current.chain_length() >= candidate.chain_length() || is_in_future(candidate) {
+        return ComparisonResult::PreferCurrent;
+    }
 
-    let not_in_future = !is_in_future(candidate);
+    let epoch_stability_depth = current.epoch_ledger_parameters().epoch_stability_depth;
 
-    if rollback_possible && not_in_future && current.chain_length() < candidate.chain_length() {
+    if check_rollback_up_to(epoch_stability_depth, storage, current, candidate) {
         ComparisonResult::PreferCandidate
     } else {
-        ComparisonResult::PreferCurrent
+        ComparisonResult::RollbackTooDeep
     }
 }
 
@@ -34,11 +39,312 @@ fn is_in_future(node: &Ref) -> bool {
     node.elapsed().is_err()
 }
 
+/// returns `true` if switching from the `current` branch to the `candidate`
+/// branch does not roll back more than `epoch_stability_depth` blocks of the
+/// current branch.
 fn check_rollback_up_to(
-    _epoch_stability_depth: u32,
-    _: &Storage,
-    _ref1: &Ref,
-    _ref2: &Ref,
+    epoch_stability_depth: u32,
+    storage: &Storage,
+    current: &Ref,
+    candidate: &Ref,
 ) -> bool {
-    true
+    if candidate.block_parent_hash() == current.hash() {
+        return true;
+    }
+
+    rollback_within(
+        epoch_stability_depth,
+        storage,
+        current.hash(),
+        current.chain_length(),
+        candidate.hash(),
+    )
+    .unwrap_or_else(|err| {
+        tracing::warn!(
+            reason = %err,
+            "cannot find the common ancestor of {} and {}, assuming the rollback is too deep",
+            current.hash(),
+            candidate.hash()
+        );
+        false
+    })
+}
+
+/// The common ancestor of the two branches is within the allowed depth if,
+/// and only if, the block of the current branch at `depth` blocks from its
+/// tip is also an ancestor of the candidate.
+fn rollback_within(
+    depth: u32,
+    storage: &Storage,
+    current: HeaderHash,
+    current_chain_length: ChainLength,
+    candidate: HeaderHash,
+) -> Result<bool, StorageError> {
+    if u32::from(current_chain_length) <= depth {
+        return Ok(true);
+    }
+
+    let deepest = storage.get_nth_ancestor(current, depth)?;
+
+    Ok(storage
+        .find_closest_ancestor(vec![deepest], candidate)?
+        .is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{
+        Block, BlockDate, BlockVersion, ConsensusVersion, ContentsBuilder, HeaderBuilderNew,
+    };
+    use crate::blockchain::{Blockchain, CheckHeaderProof, PreCheckedHeader};
+    use chain_addr::Discrimination;
+    use chain_core::property::ChainLength as _;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{fee::LinearFee, key::BftLeaderId};
+    use jormungandr_lib::interfaces::{
+        Block0Configuration, BlockchainConfiguration, ConsensusLeaderId,
+    };
+    use std::sync::Arc;
+    use tracing::Span;
+
+    const DEPTH: u32 = 5;
+
+    fn genesis(storage: &Storage) -> Block {
+        let contents = ContentsBuilder::new().into();
+        let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+            .set_genesis()
+            .set_date(BlockDate::first())
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        let block = Block { header, contents };
+        storage.put_block(&block).unwrap();
+        block
+    }
+
+    /// append `length` blocks after `parent`, the `fork` number is used to
+    /// give different dates, hence different hashes, to competing branches
+    fn branch(storage: &Storage, parent: &Block, length: u32, fork: u32) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for _ in 0..length {
+            let parent = blocks.last().unwrap_or(parent);
+            let contents = ContentsBuilder::new().into();
+            let date = BlockDate {
+                epoch: 0,
+                slot_id: parent.header.block_date().slot_id + 1 + fork * 1_000,
+            };
+            let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+                .set_parent(&parent.header.hash(), parent.header.chain_length().next())
+                .set_date(date)
+                .into_unsigned_header()
+                .unwrap()
+                .generalize();
+            let block = Block { header, contents };
+            storage.put_block(&block).unwrap();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    fn rollback_allowed(storage: &Storage, current: &Block, candidate: &Block) -> bool {
+        rollback_within(
+            DEPTH,
+            storage,
+            current.header.hash(),
+            current.header.chain_length(),
+            candidate.header.hash(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn fork_within_stability_depth_is_accepted() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 10, 0);
+        // forks from the 8th block: 2 blocks rolled back
+        let fork = branch(&storage, &main[7], 5, 1);
+
+        assert!(rollback_allowed(&storage, &main[9], &fork[4]));
+    }
+
+    #[test]
+    fn fork_at_stability_depth_is_accepted() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 10, 0);
+        // forks from the 5th block: 5 blocks rolled back
+        let fork = branch(&storage, &main[4], 7, 1);
+
+        assert!(rollback_allowed(&storage, &main[9], &fork[6]));
+    }
+
+    #[test]
+    fn fork_below_stability_depth_is_rejected() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 10, 0);
+        // forks from the 2nd block: 8 blocks rolled back
+        let fork = branch(&storage, &main[1], 12, 1);
+
+        assert!(!rollback_allowed(&storage, &main[9], &fork[11]));
+    }
+
+    #[test]
+    fn fork_from_block0_of_short_chain_is_accepted() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, DEPTH, 0);
+        let fork = branch(&storage, &block0, DEPTH + 2, 1);
+
+        assert!(rollback_allowed(
+            &storage,
+            &main[DEPTH as usize - 1],
+            &fork[DEPTH as usize + 1]
+        ));
+    }
+
+    /// a blockchain of a BFT block0 with a single leader and an epoch
+    /// stability depth of `DEPTH`, with the key of the leader
+    async fn bft_blockchain() -> (Blockchain, Arc<Ref>, SecretKey<Ed25519>) {
+        let leader_key = SecretKey::<Ed25519>::generate(rand_core::OsRng);
+        let mut blockchain_configuration = BlockchainConfiguration::new(
+            Discrimination::Test,
+            ConsensusVersion::Bft,
+            LinearFee::new(0, 0, 0),
+        );
+        blockchain_configuration
+            .consensus_leader_ids
+            .push(ConsensusLeaderId(BftLeaderId::from(leader_key.to_public())));
+        blockchain_configuration.epoch_stability_depth = DEPTH.into();
+        let block0 = Block0Configuration {
+            blockchain_configuration,
+            initial: Vec::new(),
+        }
+        .to_block();
+
+        let blockchain = Blockchain::new(
+            block0.header.hash(),
+            Storage::memory(Span::none()).unwrap(),
+            1024,
+            false,
+            None,
+            None,
+        );
+        let block0_ref = blockchain
+            .load_from_block0(block0)
+            .await
+            .unwrap()
+            .get_ref()
+            .await;
+        (blockchain, block0_ref, leader_key)
+    }
+
+    /// apply `length` signed blocks after `parent` and return their refs
+    async fn bft_branch(
+        blockchain: &Blockchain,
+        leader_key: &SecretKey<Ed25519>,
+        parent: &Arc<Ref>,
+        length: u32,
+        fork: u32,
+    ) -> Vec<Arc<Ref>> {
+        let leader_id = BftLeaderId::from(leader_key.to_public());
+        let mut refs: Vec<Arc<Ref>> = Vec::new();
+        for _ in 0..length {
+            // only the first block of the branch is shifted, the dates must
+            // stay within the first epoch
+            let shift = if refs.is_empty() { fork * 100 } else { 0 };
+            let parent = refs.last().unwrap_or(parent).clone();
+            let contents = ContentsBuilder::new().into();
+            let date = BlockDate {
+                epoch: 0,
+                slot_id: parent.block_date().slot_id + 1 + shift,
+            };
+            let builder = HeaderBuilderNew::new(BlockVersion::Ed25519Signed, &contents)
+                .set_parent(&parent.hash(), parent.chain_length().next())
+                .set_date(date)
+                .into_bft_builder()
+                .unwrap()
+                .set_consensus_data(&leader_id);
+            let signature = leader_key.sign_slice(builder.get_authenticated_data());
+            let header = builder.set_signature(signature.into()).generalize();
+            let post_checked = match blockchain
+                .pre_check_header(header.clone(), false)
+                .await
+                .unwrap()
+            {
+                PreCheckedHeader::HeaderWithCache { header, parent_ref } => blockchain
+                    .post_check_header(header, parent_ref, CheckHeaderProof::Enabled)
+                    .await
+                    .unwrap(),
+                _ => panic!("the parent of the block should be in the reference cache"),
+            };
+            let block_ref = blockchain
+                .apply_and_store_block(post_checked, Block { header, contents })
+                .await
+                .unwrap()
+                .cached_ref();
+            refs.push(block_ref);
+        }
+        refs
+    }
+
+    #[tokio::test]
+    async fn candidate_forking_within_stability_depth_is_preferred() {
+        let (blockchain, block0, leader_key) = bft_blockchain().await;
+        let main = bft_branch(&blockchain, &leader_key, &block0, 10, 0).await;
+        let fork = bft_branch(&blockchain, &leader_key, &main[7], 5, 1).await;
+
+        assert_eq!(
+            compare_against(blockchain.storage(), &main[9], &fork[4]),
+            ComparisonResult::PreferCandidate
+        );
+    }
+
+    #[tokio::test]
+    async fn candidate_forking_below_stability_depth_is_too_deep() {
+        let (blockchain, block0, leader_key) = bft_blockchain().await;
+        let main = bft_branch(&blockchain, &leader_key, &block0, 10, 0).await;
+        let fork = bft_branch(&blockchain, &leader_key, &main[1], 12, 1).await;
+
+        assert_eq!(
+            compare_against(blockchain.storage(), &main[9], &fork[11]),
+            ComparisonResult::RollbackTooDeep
+        );
+        // a shorter candidate is never selected, however deep the fork
+        assert_eq!(
+            compare_against(blockchain.storage(), &main[9], &fork[3]),
+            ComparisonResult::PreferCurrent
+        );
+    }
+
+    #[tokio::test]
+    async fn descendants_of_a_rejected_branch_are_rejected() {
+        let (blockchain, block0, leader_key) = bft_blockchain().await;
+        let main = bft_branch(&blockchain, &leader_key, &block0, 5, 0).await;
+        // the tip is rolled back to the 3rd block, the 4th and 5th are rejected
+        blockchain
+            .reject_branch(main[3].hash(), main[3].chain_length())
+            .await;
+        let rejected = bft_branch(&blockchain, &leader_key, &main[4], 2, 0).await;
+        let fork = bft_branch(&blockchain, &leader_key, &main[2], 4, 1).await;
+
+        assert!(blockchain.is_rejected(&main[2], &main[4]).await);
+        assert!(blockchain.is_rejected(&main[2], &rejected[1]).await);
+        assert!(!blockchain.is_rejected(&main[2], &fork[3]).await);
+    }
+
+    #[tokio::test]
+    async fn rejected_branch_is_forgotten_below_stability_depth() {
+        let (blockchain, block0, leader_key) = bft_blockchain().await;
+        let main = bft_branch(&blockchain, &leader_key, &block0, 10, 0).await;
+        blockchain
+            .reject_branch(main[3].hash(), main[3].chain_length())
+            .await;
+
+        // the branch forks from the tip deeper than the stability depth
+        assert!(!blockchain.is_rejected(&main[9], &main[4]).await);
+        assert!(!blockchain.is_rejected(&main[2], &main[4]).await);
+    }
 }
@@ -1,14 +1,19 @@
+mod archive;
 mod branch;
 mod candidate;
 mod chain;
 mod chain_selection;
 mod checkpoints;
 mod multiverse;
+mod pipeline;
 mod process;
 mod reference;
 mod reference_cache;
+mod snapshot;
 mod storage;
+mod storage_check;
 mod tip;
+mod tip_events;
 
 // Constants
 
@@ -25,6 +30,7 @@ mod chunk_sizes {
 // Re-exports
 
 pub use self::{
+    archive::{export_blocks, import_blocks, Error as ArchiveError},
     branch::Branch,
     chain::{
         new_epoch_leadership_from, Blockchain, CheckHeaderProof, EpochLeadership, Error, ErrorKind,
@@ -33,8 +39,12 @@ pub use self::{
     chain_selection::{compare_against, ComparisonResult},
     checkpoints::Checkpoints,
     multiverse::Multiverse,
+    pipeline::{HeaderProofPipeline, DEFAULT_VERIFICATION_WINDOW},
     process::{process_new_ref, Process},
     reference::Ref,
-    storage::{Error as StorageError, Storage},
+    snapshot::{Error as LedgerSnapshotError, LedgerSnapshots, LEDGER_SNAPSHOT_TAG},
+    storage::{Error as StorageError, Storage, PRUNED_STORAGE_START_TAG, PRUNING_POINT_TAG},
+    storage_check::{check_storage, StorageCheckReport},
     tip::Tip,
+    tip_events::TipEvents,
 };
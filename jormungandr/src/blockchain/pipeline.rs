@@ -0,0 +1,307 @@
+//! Pipelined verification of the header proofs of a sequence of blocks,
+//! used to bootstrap from the network and to import blocks from an archive.
+//!
+//! Checking the signature (and the VRF proof with Genesis Praos) of a header
+//! only needs the leadership schedule of its epoch, not the ledger state of
+//! its parent. So while the blocks are applied to the ledger one after the
+//! other, the proofs of the next blocks are verified ahead on the blocking
+//! thread pool of the runtime.
+//!
+//! The leadership schedule of an epoch is only known once the first block of
+//! the epoch is applied: this block has its proof checked when it is applied,
+//! as with the sequential path, and the verification of the next blocks of
+//! the epoch starts right after.
+//!
+//! Loading the blocks from the storage does not go through the pipeline, the
+//! proofs of the stored blocks are not checked again.
+
+use crate::{
+    blockcfg::{Block, Epoch, HeaderHash, Leadership},
+    blockchain::{CheckHeaderProof, Error, ErrorKind, Ref},
+};
+use chain_impl_mockchain::leadership::Verification;
+use tokio::task::JoinHandle;
+
+use std::{collections::VecDeque, sync::Arc};
+
+/// the default number of blocks whose proofs can be verified ahead of
+/// the block being applied
+pub const DEFAULT_VERIFICATION_WINDOW: usize = 64;
+
+enum Proof {
+    /// the leadership schedule of the epoch of the block is not known yet,
+    /// or the block does not follow the blocks applied so far
+    Pending,
+    Verifying(JoinHandle<Result<(), String>>),
+}
+
+struct Entry {
+    block: Block,
+    proof: Proof,
+}
+
+pub struct HeaderProofPipeline {
+    window: usize,
+    blocks: VecDeque<Entry>,
+    /// the leadership schedule of the epoch of the last applied block
+    leadership: Option<(Epoch, Arc<Leadership>)>,
+    /// the last of the queued blocks that descends from the last applied
+    /// block, `None` if the queued blocks do not form a chain
+    tail: Option<HeaderHash>,
+}
+
+impl HeaderProofPipeline {
+    pub fn new(window: usize) -> Self {
+        HeaderProofPipeline {
+            window: window.max(1),
+            blocks: VecDeque::with_capacity(window),
+            leadership: None,
+            tail: None,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.blocks.len() >= self.window
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// queue a block. The verification of its proof starts right away if it
+    /// is in the epoch of the last applied block, and descends from it
+    /// through the blocks already queued.
+    pub fn push(&mut self, block: Block) {
+        let proof = if self.tail == Some(block.header.block_parent_hash()) {
+            self.tail = Some(block.header.hash());
+            match &self.leadership {
+                Some((epoch, leadership)) if *epoch == block.header.block_date().epoch => {
+                    verify(leadership, &block)
+                }
+                _ => Proof::Pending,
+            }
+        } else {
+            self.tail = None;
+            Proof::Pending
+        };
+
+        self.blocks.push_back(Entry { block, proof });
+    }
+
+    /// take the next queued block, with how its header proof is to be
+    /// checked when it is applied. Returns an error if the proof was
+    /// verified ahead and is invalid.
+    pub async fn next(&mut self) -> Option<Result<(Block, CheckHeaderProof), Error>> {
+        let Entry { block, proof } = self.blocks.pop_front()?;
+        let result = match proof {
+            Proof::Pending => Ok((block, CheckHeaderProof::Enabled)),
+            Proof::Verifying(handle) => match handle.await {
+                Ok(Ok(())) => Ok((block, CheckHeaderProof::Verified)),
+                Ok(Err(reason)) => Err(ErrorKind::BlockHeaderVerificationFailed(reason).into()),
+                Err(join_error) => std::panic::resume_unwind(join_error.into_panic()),
+            },
+        };
+        Some(result)
+    }
+
+    /// to be called with the `Ref` of every block applied, in order, and
+    /// of the block the sequence starts from if it is known. The proofs
+    /// of the queued blocks of its epoch are then verified.
+    pub fn applied(&mut self, block_ref: &Ref) {
+        let epoch = block_ref.block_date().epoch;
+        let same_epoch = matches!(&self.leadership, Some((current, _)) if *current == epoch);
+        if same_epoch && self.tail.is_some() {
+            // the queued blocks of this epoch are already verified
+            return;
+        }
+
+        let leadership = Arc::clone(block_ref.epoch_leadership_schedule());
+        let mut tail = Some(block_ref.hash());
+        for entry in self.blocks.iter_mut() {
+            if tail != Some(entry.block.header.block_parent_hash()) {
+                tail = None;
+                break;
+            }
+            if matches!(entry.proof, Proof::Pending)
+                && entry.block.header.block_date().epoch == epoch
+            {
+                entry.proof = verify(&leadership, &entry.block);
+            }
+            tail = Some(entry.block.header.hash());
+        }
+
+        self.tail = tail;
+        self.leadership = Some((epoch, leadership));
+    }
+}
+
+fn verify(leadership: &Arc<Leadership>, block: &Block) -> Proof {
+    let leadership = Arc::clone(leadership);
+    let header = block.header.clone();
+    Proof::Verifying(tokio::task::spawn_blocking(move || {
+        match leadership.verify(&header) {
+            Verification::Success => Ok(()),
+            Verification::Failure(error) => Err(error.to_string()),
+        }
+    }))
+}
+
+/// Compares the application of a chain of BFT blocks with the proofs checked
+/// one block after the other, and with the pipeline. Run with:
+///
+/// ```sh
+/// cargo test --release --features with-bench -p jormungandr pipeline::bench -- --nocapture
+/// ```
+#[cfg(all(test, feature = "with-bench"))]
+mod bench {
+    use super::*;
+    use crate::{
+        blockcfg::{BlockDate, BlockVersion, ConsensusVersion, ContentsBuilder, HeaderBuilderNew},
+        blockchain::{Blockchain, PreCheckedHeader, Storage},
+    };
+    use chain_addr::Discrimination;
+    use chain_core::property::ChainLength as _;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{fee::LinearFee, key::BftLeaderId};
+    use jormungandr_lib::interfaces::{
+        Block0Configuration, BlockchainConfiguration, ConsensusLeaderId,
+    };
+    use tracing::Span;
+
+    use std::time::{Duration, Instant};
+
+    const BLOCKS: u32 = 10_000;
+    const SLOTS_PER_EPOCH: u32 = 720;
+
+    /// build a block0 with a single BFT leader and `length` empty blocks
+    /// signed by this leader
+    fn chain(length: u32) -> (Block, Vec<Block>) {
+        let leader_key = SecretKey::<Ed25519>::generate(rand_core::OsRng);
+        let leader_id = BftLeaderId::from(leader_key.to_public());
+
+        let mut blockchain_configuration = BlockchainConfiguration::new(
+            Discrimination::Test,
+            ConsensusVersion::Bft,
+            LinearFee::new(0, 0, 0),
+        );
+        blockchain_configuration
+            .consensus_leader_ids
+            .push(ConsensusLeaderId(leader_id.clone()));
+        let block0 = Block0Configuration {
+            blockchain_configuration,
+            initial: Vec::new(),
+        }
+        .to_block();
+
+        let mut parent = block0.header.clone();
+        let blocks = (1..=length)
+            .map(|i| {
+                let contents = ContentsBuilder::new().into();
+                let date = BlockDate {
+                    epoch: i / SLOTS_PER_EPOCH,
+                    slot_id: i % SLOTS_PER_EPOCH,
+                };
+                let builder = HeaderBuilderNew::new(BlockVersion::Ed25519Signed, &contents)
+                    .set_parent(&parent.hash(), parent.chain_length().next())
+                    .set_date(date)
+                    .into_bft_builder()
+                    .unwrap()
+                    .set_consensus_data(&leader_id);
+                let signature = leader_key.sign_slice(builder.get_authenticated_data());
+                let header = builder.set_signature(signature.into()).generalize();
+                parent = header.clone();
+                Block { header, contents }
+            })
+            .collect();
+
+        (block0, blocks)
+    }
+
+    async fn blockchain(block0: &Block) -> (Blockchain, Arc<Ref>) {
+        let blockchain = Blockchain::new(
+            block0.header.hash(),
+            Storage::memory(Span::none()).unwrap(),
+            1024,
+            false,
+            None,
+            None,
+        );
+        let branch = blockchain.load_from_block0(block0.clone()).await.unwrap();
+        let block0_ref = branch.get_ref().await;
+        (blockchain, block0_ref)
+    }
+
+    async fn apply(
+        blockchain: &Blockchain,
+        block: Block,
+        check_header_proof: CheckHeaderProof,
+    ) -> Arc<Ref> {
+        match blockchain
+            .pre_check_header(block.header.clone(), true)
+            .await
+            .unwrap()
+        {
+            PreCheckedHeader::HeaderWithCache { header, parent_ref } => {
+                let post_checked = blockchain
+                    .post_check_header(header, parent_ref, check_header_proof)
+                    .await
+                    .unwrap();
+                blockchain
+                    .apply_and_store_block(post_checked, block)
+                    .await
+                    .unwrap()
+                    .cached_ref()
+            }
+            _ => panic!("the parent of the block should be in the reference cache"),
+        }
+    }
+
+    async fn sequential(block0: &Block, blocks: Vec<Block>) -> Duration {
+        let (blockchain, _) = blockchain(block0).await;
+        let start = Instant::now();
+        for block in blocks {
+            apply(&blockchain, block, CheckHeaderProof::Enabled).await;
+        }
+        start.elapsed()
+    }
+
+    async fn pipelined(block0: &Block, blocks: Vec<Block>) -> Duration {
+        let (blockchain, block0_ref) = blockchain(block0).await;
+        let start = Instant::now();
+        let mut pipeline = HeaderProofPipeline::new(DEFAULT_VERIFICATION_WINDOW);
+        pipeline.applied(&block0_ref);
+        let mut blocks = blocks.into_iter();
+        loop {
+            while !pipeline.is_full() {
+                match blocks.next() {
+                    Some(block) => pipeline.push(block),
+                    None => break,
+                }
+            }
+            let (block, check_header_proof) = match pipeline.next().await {
+                Some(next) => next.unwrap(),
+                None => break,
+            };
+            let block_ref = apply(&blockchain, block, check_header_proof).await;
+            pipeline.applied(&block_ref);
+        }
+        start.elapsed()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn header_proof_pipeline() {
+        let (block0, blocks) = chain(BLOCKS);
+
+        let sequential = sequential(&block0, blocks.clone()).await;
+        let pipelined = pipelined(&block0, blocks).await;
+
+        println!(
+            "applied {} blocks: sequential {:?} ({:?} per block), pipelined {:?} ({:?} per block)",
+            BLOCKS,
+            sequential,
+            sequential / BLOCKS,
+            pipelined,
+            pipelined / BLOCKS,
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     blockcfg::{Block, FragmentId, Header, HeaderHash},
     blockchain::Checkpoints,
     intercom::{self, BlockMsg, ExplorerMsg, NetworkMsg, PropagateMsg, TransactionMsg},
-    network::p2p::Address,
+    network::p2p::{Address, PeerEvent},
     stats_counter::StatsCounter,
     utils::{
         async_msg::{self, MessageBox, MessageQueue},
@@ -22,10 +22,15 @@ use chain_core::property::{Block as _, Fragment as _, HasHeader as _, Header as
 use jormungandr_lib::interfaces::FragmentStatus;
 
 use futures::prelude::*;
+use lru::LruCache;
+use tokio::sync::broadcast;
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 type PullHeadersScheduler = FireForgetScheduler<HeaderHash, Address, Checkpoints>;
 type GetNextBlockScheduler = FireForgetScheduler<HeaderHash, Address, ()>;
@@ -51,6 +56,31 @@ const GET_NEXT_BLOCK_SCHEDULER_CONFIG: FireForgetSchedulerConfig = FireForgetSch
     timeout: Duration::from_millis(500),
 };
 
+const MAX_ANNOUNCED_BLOCKS: usize = 1024;
+
+/// the peers which were the first to announce the blocks not yet in storage
+#[derive(Clone)]
+struct Announcers(Arc<Mutex<LruCache<HeaderHash, Address>>>);
+
+impl Announcers {
+    fn new() -> Self {
+        Announcers(Arc::new(Mutex::new(LruCache::new(MAX_ANNOUNCED_BLOCKS))))
+    }
+
+    /// record the peer announcing the block, unless another peer did it first
+    fn record(&self, hash: HeaderHash, node_address: Address) {
+        let mut announcers = self.0.lock().unwrap();
+        if !announcers.contains(&hash) {
+            announcers.put(hash, node_address);
+        }
+    }
+
+    /// take the first peer that announced the block, if any
+    fn take(&self, hash: &HeaderHash) -> Option<Address> {
+        self.0.lock().unwrap().pop(hash)
+    }
+}
+
 pub struct Process {
     pub blockchain: Blockchain,
     pub blockchain_tip: Tip,
@@ -69,14 +99,17 @@ impl Process {
     ) {
         self.start_branch_reprocessing(&service_info);
         self.start_garbage_collector(&service_info);
+        self.start_ledger_snapshots(&service_info);
         let pull_headers_scheduler = self.spawn_pull_headers_scheduler(&service_info);
         let get_next_block_scheduler = self.spawn_get_next_block_scheduler(&service_info);
+        let announcers = Announcers::new();
         while let Some(msg) = input.next().await {
             self.handle_input(
                 &service_info,
                 msg,
                 &pull_headers_scheduler,
                 &get_next_block_scheduler,
+                &announcers,
             );
         }
     }
@@ -87,6 +120,7 @@ impl Process {
         input: BlockMsg,
         pull_headers_scheduler: &PullHeadersScheduler,
         get_next_block_scheduler: &GetNextBlockScheduler,
+        announcers: &Announcers,
     ) {
         let blockchain = self.blockchain.clone();
         let blockchain_tip = self.blockchain_tip.clone();
@@ -146,10 +180,11 @@ impl Process {
                         node_id,
                         pull_headers_scheduler.clone(),
                         get_next_block_scheduler.clone(),
+                        announcers.clone(),
                     ),
                 )
             }
-            BlockMsg::NetworkBlocks(handle) => {
+            BlockMsg::NetworkBlocks(node_address, handle) => {
                 tracing::info!("receiving block stream from network");
 
                 let get_next_block_scheduler = get_next_block_scheduler.clone();
@@ -164,6 +199,8 @@ impl Process {
                         network_msg_box,
                         explorer_msg_box,
                         get_next_block_scheduler,
+                        announcers.clone(),
+                        node_address,
                         handle,
                         stats_counter,
                     ),
@@ -186,6 +223,38 @@ impl Process {
                     ),
                 );
             }
+            BlockMsg::RollbackTip(target, max_depth, reply_handle) => {
+                let span = span!(
+                    parent: info.span(),
+                    Level::TRACE,
+                    "rollback_tip",
+                    target = %target.to_string(),
+                );
+                let _enter = span.enter();
+                tracing::warn!("rolling back the tip on request of the node administrator");
+
+                let mut blockchain = blockchain;
+                info.spawn(
+                    "rollback tip",
+                    async move {
+                        let result = rollback_tip(
+                            &mut blockchain,
+                            blockchain_tip,
+                            target,
+                            max_depth,
+                            tx_msg_box,
+                            explorer_msg_box,
+                        )
+                        .await;
+                        reply_handle.reply(
+                            result
+                                .map(|new_tip| new_tip.hash())
+                                .map_err(rollback_error_into_reply),
+                        );
+                    }
+                    .instrument(span.clone()),
+                );
+            }
         }
     }
 
@@ -216,6 +285,40 @@ impl Process {
         )
     }
 
+    /// write a ledger snapshot every time the tip enters a new epoch, and
+    /// prune the storage up to the new pruning point if the storage is pruned
+    fn start_ledger_snapshots(&self, info: &TokioServiceInfo) {
+        let blockchain = self.blockchain.clone();
+        let tip = self.blockchain_tip.clone();
+        let (_, mut tip_changes) = blockchain.tip_events().subscribe(None);
+
+        info.spawn("ledger snapshot", async move {
+            let mut snapshot_epoch = None;
+            loop {
+                let tip_ref = tip.get_ref().await;
+                let epoch = tip_ref.block_date().epoch;
+                if snapshot_epoch != Some(epoch) {
+                    match blockchain.snapshot_ledger(tip_ref).await {
+                        Ok(()) => {
+                            snapshot_epoch = Some(epoch);
+                            if let Err(err) = blockchain.prune_storage().await {
+                                tracing::error!(reason = %err, "cannot prune the storage")
+                            }
+                        }
+                        // tried again at the next change of the tip
+                        Err(err) => {
+                            tracing::error!(reason = %err, "cannot write the ledger snapshot")
+                        }
+                    }
+                }
+                match tip_changes.recv().await {
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
     fn spawn_pull_headers_scheduler(&self, info: &TokioServiceInfo) -> PullHeadersScheduler {
         let network_msgbox = self.network_msgbox.clone();
         let scheduler_future = FireForgetSchedulerFuture::new(
@@ -256,6 +359,19 @@ impl Process {
                     });
             },
         );
+        let network_msgbox = self.network_msgbox.clone();
+        let scheduler_future =
+            scheduler_future.with_timeout_handler(Box::new(move |_header_id, node_address| {
+                network_msgbox
+                    .clone()
+                    .try_send(NetworkMsg::ReportPeer {
+                        node_address,
+                        event: PeerEvent::Timeout,
+                    })
+                    .unwrap_or_else(
+                        |e| tracing::error!(reason = ?e, "cannot report a peer to the network"),
+                    );
+            }));
         let scheduler = scheduler_future.scheduler();
         let future = scheduler_future
             .map_err(move |e| tracing::error!(reason = ?e, "get next block scheduling failed"));
@@ -278,6 +394,10 @@ fn try_request_fragment_removal(
 /// this function will re-process the tip against the different branches
 /// this is because a branch may have become more interesting with time
 /// moving forward and branches may have been dismissed
+///
+/// The branches forking from the tip deeper than the epoch stability depth
+/// were already rejected when their block was processed and can only get
+/// deeper, so they are skipped.
 async fn reprocess_tip(
     mut blockchain: Blockchain,
     tip: Tip,
@@ -290,14 +410,20 @@ async fn reprocess_tip(
     let others = branches
         .iter()
         .filter(|r| !Arc::ptr_eq(&r, &tip_as_ref))
+        .filter(|r| {
+            chain_selection::compare_against(blockchain.storage(), &tip_as_ref, r)
+                != ComparisonResult::RollbackTooDeep
+        })
+        .cloned()
         .collect::<Vec<_>>();
 
     for other in others {
         process_new_ref(
             &mut blockchain,
             tip.clone(),
-            Arc::clone(other),
+            other,
             explorer_msg_box.clone(),
+            None,
         )
         .await?
     }
@@ -314,15 +440,28 @@ async fn reprocess_tip(
 /// If the current tip is not the one being updated we will then trigger
 /// chain selection after updating that other branch as it may be possible that
 /// this branch just became more interesting for the current consensus algorithm.
+///
+/// Candidates forking from the current tip deeper than the epoch stability depth
+/// are never selected; they are counted in the `stats_counter` if one is given.
 pub async fn process_new_ref(
     blockchain: &mut Blockchain,
     mut tip: Tip,
     candidate: Arc<Ref>,
     explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
+    stats_counter: Option<&StatsCounter>,
 ) -> Result<(), Error> {
     let candidate_hash = candidate.hash();
     let tip_ref = tip.get_ref().await;
 
+    if blockchain.is_rejected(&tip_ref, &candidate).await {
+        tracing::warn!(
+            "ignoring branch with tip {} | current-tip {}: the branch was rolled back",
+            candidate.header().description(),
+            tip_ref.header().description(),
+        );
+        return Ok(());
+    }
+
     match chain_selection::compare_against(blockchain.storage(), &tip_ref, &candidate) {
         ComparisonResult::PreferCurrent => {
             tracing::info!(
@@ -331,6 +470,17 @@ pub async fn process_new_ref(
                 tip_ref.header().description(),
             );
         }
+        ComparisonResult::RollbackTooDeep => {
+            tracing::warn!(
+                "rejecting branch with tip {} | current-tip {}: the fork is deeper than the epoch stability depth ({})",
+                candidate.header().description(),
+                tip_ref.header().description(),
+                tip_ref.epoch_ledger_parameters().epoch_stability_depth,
+            );
+            if let Some(stats_counter) = stats_counter {
+                stats_counter.add_deep_fork_rejected_cnt(1);
+            }
+        }
         ComparisonResult::PreferCandidate => {
             if tip_ref.hash() == candidate.block_parent_hash() {
                 tracing::info!(
@@ -344,7 +494,7 @@ pub async fn process_new_ref(
                     .put_tag(MAIN_BRANCH_TAG, candidate_hash)
                     .map_err(|e| Error::with_chain(e, "Cannot update the main storage's tip"))?;
 
-                tip.update_ref(candidate).await;
+                tip.update_ref(Arc::clone(&candidate)).await;
             } else {
                 tracing::info!(
                     "switching branch from {} to {}",
@@ -357,10 +507,34 @@ pub async fn process_new_ref(
                     .put_tag(MAIN_BRANCH_TAG, candidate_hash)
                     .map_err(|e| Error::with_chain(e, "Cannot update the main storage's tip"))?;
 
-                let branch = blockchain.branches_mut().apply_or_create(candidate).await;
+                match blockchain.storage().find_common_ancestor(
+                    (tip_ref.hash(), u32::from(tip_ref.chain_length())),
+                    (candidate_hash, u32::from(candidate.chain_length())),
+                ) {
+                    Ok((fork_point, fork_chain_length)) => {
+                        let depth = u32::from(tip_ref.chain_length()) - fork_chain_length;
+                        if let Some(stats_counter) = stats_counter {
+                            stats_counter.add_reorg(depth);
+                        }
+                        blockchain
+                            .tip_events()
+                            .rollback(&tip_ref, fork_point, depth);
+                    }
+                    Err(err) => tracing::warn!(
+                        reason = %err,
+                        "cannot find the fork point of the new branch, the reorg is not counted nor notified"
+                    ),
+                }
+
+                let branch = blockchain
+                    .branches_mut()
+                    .apply_or_create(Arc::clone(&candidate))
+                    .await;
                 tip.swap(branch).await;
             }
 
+            blockchain.tip_events().tip_changed(&candidate);
+
             if let Some(mut msg_box) = explorer_msg_box {
                 msg_box
                     .send(ExplorerMsg::NewTip(candidate_hash))
@@ -375,17 +549,127 @@ pub async fn process_new_ref(
     Ok(())
 }
 
+/// roll the tip back to `target`, an ancestor of the current tip at most
+/// `max_depth` blocks, and no more than the epoch stability depth, below it,
+/// as if the node switched to a branch ending at `target`.
+///
+/// The branch of the removed blocks is rejected, so the chain selection does
+/// not switch back to it when one of its blocks or descendants is received,
+/// and the fragments of the removed blocks are sent back to the fragment
+/// pool.
+pub async fn rollback_tip(
+    blockchain: &mut Blockchain,
+    mut tip: Tip,
+    target: HeaderHash,
+    max_depth: u32,
+    mut tx_msg_box: MessageBox<TransactionMsg>,
+    explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
+) -> Result<Arc<Ref>, Error> {
+    let tip_ref = tip.get_ref().await;
+    if tip_ref.hash() == target {
+        return Ok(tip_ref);
+    }
+
+    let depth = blockchain
+        .storage()
+        .find_closest_ancestor(vec![target], tip_ref.hash())
+        .map_err(|e| Error::with_chain(e, "Cannot check if the block is an ancestor of the tip"))?
+        .map(|ancestor| ancestor.distance)
+        .ok_or(ErrorKind::RollbackTargetNotAncestor(target))?;
+    // a deeper rollback would not be allowed by the chain selection
+    let max_depth = max_depth.min(tip_ref.epoch_ledger_parameters().epoch_stability_depth);
+    if depth > max_depth {
+        return Err(ErrorKind::RollbackTooDeep(depth, max_depth).into());
+    }
+
+    let target_ref = blockchain
+        .load_ref(target, max_depth)
+        .await?
+        .ok_or(ErrorKind::RollbackTargetNotLoaded(target))?;
+
+    let removed_blocks: Vec<Block> = blockchain
+        .storage()
+        .stream_from_to(target, tip_ref.hash())
+        .map_err(|e| Error::with_chain(e, "Cannot iterate over the rolled back blocks"))?
+        .try_collect()
+        .await
+        .map_err(|e| Error::with_chain(e, "Cannot read the rolled back blocks"))?;
+
+    tracing::warn!(
+        "rolling back the tip by {} blocks: {} -> {}",
+        depth,
+        tip_ref.header().description(),
+        target_ref.header().description(),
+    );
+
+    blockchain
+        .storage()
+        .put_tag(MAIN_BRANCH_TAG, target)
+        .map_err(|e| Error::with_chain(e, "Cannot update the main storage's tip"))?;
+    tip.update_ref(Arc::clone(&target_ref)).await;
+    blockchain.tip_events().rollback(&tip_ref, target, depth);
+    blockchain.tip_events().tip_changed(&target_ref);
+
+    if let Some(first_removed) = removed_blocks.first() {
+        let first_removed_hash = first_removed.header.hash();
+        blockchain
+            .reject_branch(first_removed_hash, first_removed.header.chain_length())
+            .await;
+        let storage = blockchain.storage().clone();
+        blockchain
+            .branches_mut()
+            .retain(|branch| {
+                !matches!(
+                    storage.find_closest_ancestor(vec![first_removed_hash], branch.hash()),
+                    Ok(Some(_))
+                )
+            })
+            .await;
+    }
+
+    let fragments = removed_blocks
+        .iter()
+        .flat_map(|block| block.fragments().cloned())
+        .collect();
+    tx_msg_box
+        .send(TransactionMsg::RestoreTransactions(fragments))
+        .await
+        .unwrap_or_else(|err| {
+            tracing::error!(
+                "cannot send the fragments of the rolled back blocks to the fragment pool: {}",
+                err
+            )
+        });
+
+    if let Some(mut msg_box) = explorer_msg_box {
+        msg_box
+            .send(ExplorerMsg::NewTip(target))
+            .await
+            .unwrap_or_else(|err| tracing::error!("cannot send new tip to explorer: {}", err));
+    }
+
+    Ok(target_ref)
+}
+
 async fn process_and_propagate_new_ref(
     blockchain: &mut Blockchain,
     tip: Tip,
     new_block_ref: Arc<Ref>,
     mut network_msg_box: MessageBox<NetworkMsg>,
     explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
+    stats_counter: &StatsCounter,
 ) -> Result<(), Error> {
     let header = new_block_ref.header().clone();
     tracing::debug!("processing the new block and propagating");
 
-    process_new_ref(blockchain, tip, new_block_ref, explorer_msg_box).await?;
+    process_new_ref(
+        blockchain,
+        tip,
+        new_block_ref,
+        explorer_msg_box,
+        Some(stats_counter),
+    )
+    .await?;
 
     tracing::debug!("propagating block to the network");
     network_msg_box
@@ -419,6 +703,7 @@ async fn process_leadership_block(
         Arc::clone(&new_block_ref),
         network_msg_box,
         explorer_msg_box.clone(),
+        &stats_counter,
     )
     .await?;
 
@@ -473,6 +758,7 @@ async fn process_block_announcement(
     node_id: Address,
     mut pull_headers_scheduler: PullHeadersScheduler,
     mut get_next_block_scheduler: GetNextBlockScheduler,
+    announcers: Announcers,
 ) -> Result<(), Error> {
     let pre_checked = blockchain
         .pre_check_header(header, false)
@@ -486,6 +772,7 @@ async fn process_block_announcement(
         PreCheckedHeader::MissingParent { header, .. } => {
             tracing::debug!("block is missing a locally stored parent");
             let to = header.hash();
+            announcers.record(to, node_id.clone());
             let from = blockchain.get_checkpoints(blockchain_tip.branch()).await;
             pull_headers_scheduler
                 .schedule(to, node_id, from)
@@ -502,6 +789,7 @@ async fn process_block_announcement(
             parent_ref: _,
         } => {
             tracing::debug!("Announced block has a locally stored parent, fetch it");
+            announcers.record(header.hash(), node_id.clone());
             get_next_block_scheduler
                 .schedule(header.id(), node_id, ())
                 .unwrap_or_else(move |err| {
@@ -520,9 +808,11 @@ async fn process_network_blocks(
     mut blockchain: Blockchain,
     blockchain_tip: Tip,
     mut tx_msg_box: MessageBox<TransactionMsg>,
-    network_msg_box: MessageBox<NetworkMsg>,
+    mut network_msg_box: MessageBox<NetworkMsg>,
     mut explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
     mut get_next_block_scheduler: GetNextBlockScheduler,
+    announcers: Announcers,
+    node_address: Option<Address>,
     handle: intercom::RequestStreamHandle<Block, ()>,
     stats_counter: StatsCounter,
 ) -> Result<(), Error> {
@@ -543,6 +833,11 @@ async fn process_network_blocks(
                     &mut get_next_block_scheduler,
                 )
                 .await;
+                if let (Some(node_address), Err(e)) = (&node_address, &res) {
+                    if let Some(event) = network_block_error_peer_event(e) {
+                        report_peer(&mut network_msg_box, node_address.clone(), event).await;
+                    }
+                }
                 match res {
                     Ok(Some(r)) => {
                         stats_counter.add_block_recv_cnt(1);
@@ -574,13 +869,22 @@ async fn process_network_blocks(
         Some(new_block_ref) => {
             process_and_propagate_new_ref(
                 &mut blockchain,
-                blockchain_tip,
+                blockchain_tip.clone(),
                 Arc::clone(&new_block_ref),
-                network_msg_box,
+                network_msg_box.clone(),
                 explorer_msg_box,
+                &stats_counter,
             )
             .await?;
 
+            // only the peer that was the first to announce the new tip is
+            // rewarded, not the blocks requested by the node itself
+            if blockchain_tip.get_ref().await.hash() == new_block_ref.hash() {
+                if let Some(announcer) = announcers.take(&new_block_ref.hash()) {
+                    report_peer(&mut network_msg_box, announcer, PeerEvent::NewBlock).await;
+                }
+            }
+
             // Add block if found
             if let Some(b) = latest_block {
                 stats_counter.set_tip_block(b);
@@ -728,6 +1032,32 @@ async fn process_chain_headers(
     }
 }
 
+/// the behaviour of the peer that sent a block failing with the error,
+/// `None` if the peer is not at fault
+fn network_block_error_peer_event(err: &chain::Error) -> Option<PeerEvent> {
+    use super::chain::ErrorKind::*;
+
+    match &err.0 {
+        BlockHeaderVerificationFailed(_) => Some(PeerEvent::InvalidHeader),
+        CannotApplyBlock | Ledger(_) => Some(PeerEvent::InvalidBlock),
+        _ => None,
+    }
+}
+
+async fn report_peer(
+    network_msg_box: &mut MessageBox<NetworkMsg>,
+    node_address: Address,
+    event: PeerEvent,
+) {
+    network_msg_box
+        .send(NetworkMsg::ReportPeer {
+            node_address,
+            event,
+        })
+        .await
+        .unwrap_or_else(|e| tracing::error!(reason = %e, "cannot report a peer to the network"));
+}
+
 fn network_block_error_into_reply(err: chain::Error) -> intercom::Error {
     use super::chain::ErrorKind::*;
 
@@ -741,6 +1071,17 @@ fn network_block_error_into_reply(err: chain::Error) -> intercom::Error {
     }
 }
 
+fn rollback_error_into_reply(err: chain::Error) -> intercom::Error {
+    use super::chain::ErrorKind::*;
+
+    match err.0 {
+        RollbackTargetNotAncestor(_) => intercom::Error::invalid_argument(err.to_string()),
+        RollbackTooDeep(..) => intercom::Error::out_of_range(err.to_string()),
+        RollbackTargetNotLoaded(_) => intercom::Error::failed_precondition(err.to_string()),
+        _ => intercom::Error::failed(err.to_string()),
+    }
+}
+
 fn chain_header_error_into_reply(err: candidate::Error) -> intercom::Error {
     use super::candidate::Error::*;
 
@@ -26,6 +26,11 @@ pub struct Ref {
     /// this object will be shared between different Ref of the same epoch
     epoch_leadership_schedule: Arc<Leadership>,
 
+    /// the ledger state the epoch's leadership schedule was built from
+    ///
+    /// this object will be shared between different Ref of the same epoch
+    epoch_leadership_state: Arc<Ledger>,
+
     /// pointer to the current ledger parameters
     ///
     /// The object will be shared between different Ref of the same epoch
@@ -48,10 +53,12 @@ pub struct Ref {
 
 impl Ref {
     /// create a new `Ref`
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ledger: multiverse::Ref<Ledger>,
         time_frame: Arc<TimeFrame>,
         epoch_leadership_schedule: Arc<Leadership>,
+        epoch_leadership_state: Arc<Ledger>,
         epoch_ledger_parameters: Arc<LedgerParameters>,
         epoch_rewards_info: Option<Arc<EpochRewardsInfo>>,
         header: Header,
@@ -67,6 +74,7 @@ impl Ref {
             ledger,
             time_frame,
             epoch_leadership_schedule,
+            epoch_leadership_state,
             epoch_ledger_parameters,
             epoch_rewards_info,
             header,
@@ -114,6 +122,11 @@ impl Ref {
         &self.epoch_leadership_schedule
     }
 
+    /// access the ledger state the epoch's leadership schedule was built from
+    pub fn epoch_leadership_state(&self) -> &Arc<Ledger> {
+        &self.epoch_leadership_state
+    }
+
     pub fn epoch_ledger_parameters(&self) -> &Arc<LedgerParameters> {
         &self.epoch_ledger_parameters
     }
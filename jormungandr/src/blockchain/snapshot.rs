@@ -0,0 +1,474 @@
+//! Persistent snapshots of the ledger state at stable points of the chain.
+//!
+//! A snapshot is taken on the last block of an epoch that is deeper than
+//! the `epoch_stability_depth`. It contains everything that is needed to
+//! rebuild the [`Ref`] of that block without replaying the chain from the
+//! block0:
+//!
+//! * the ledger state after the block;
+//! * the ledger state the epoch's leadership schedule was built from;
+//! * the header and the ledger state of the last block of the previous
+//!   epoch, if any, used by the next epoch transition.
+//!
+//! As the snapshot does not rely on the blocks before it, it is also what
+//! a pruned storage is started from (see the `storage.pruning` setting).
+//!
+//! Snapshots are written in the `ledger-snapshots` directory of the node's
+//! storage. The file starts with a versioned header holding the hash of the
+//! block0 and a checksum of the content, so snapshots that are corrupt or
+//! written by an incompatible version of the node can be detected and ignored.
+//!
+//! [`Ref`]: ./struct.Ref.html
+
+use crate::blockcfg::{ChainLength, Header, HeaderHash, Ledger};
+use chain_core::property::{Deserialize, Serialize};
+use thiserror::Error;
+
+use std::{
+    convert::TryInto,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// the storage tag pointing to the block of the latest written snapshot
+pub const LEDGER_SNAPSHOT_TAG: &str = "LEDGER_SNAPSHOT";
+
+const SNAPSHOT_DIRECTORY: &str = "ledger-snapshots";
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+const SNAPSHOT_MAGIC: &[u8; 8] = b"JORMLSNP";
+const SNAPSHOT_VERSION: u32 = 2;
+
+/// number of snapshots kept on disk, older ones are removed when a
+/// new snapshot is written.
+const SNAPSHOTS_TO_KEEP: usize = 2;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while accessing the ledger snapshot")]
+    Io(#[from] io::Error),
+    #[error("the file is not a ledger snapshot")]
+    InvalidMagic,
+    #[error(
+        "unsupported ledger snapshot version {0}, expected {}",
+        SNAPSHOT_VERSION
+    )]
+    UnsupportedVersion(u32),
+    #[error("the ledger snapshot was made for block0 {got}, expected {expected}")]
+    Block0Mismatch {
+        expected: HeaderHash,
+        got: HeaderHash,
+    },
+    #[error("the ledger snapshot is for block {got}, expected {expected}")]
+    BlockMismatch {
+        expected: HeaderHash,
+        got: HeaderHash,
+    },
+    #[error("the ledger snapshot checksum does not match its content")]
+    ChecksumMismatch,
+    #[error("cannot serialize the ledger")]
+    Serialize(#[source] io::Error),
+    #[error("cannot deserialize the ledger")]
+    Deserialize(#[source] io::Error),
+}
+
+/// the content of a ledger snapshot
+pub struct LedgerSnapshot {
+    pub block_hash: HeaderHash,
+    /// the ledger state after the block was applied
+    pub ledger: Ledger,
+    /// the ledger state used to build the leadership schedule of
+    /// the block's epoch
+    pub epoch_leadership_state: Ledger,
+    /// the header and ledger state of the last block of the previous epoch
+    pub previous_epoch: Option<(Header, Ledger)>,
+}
+
+/// access to the snapshots directory of a storage
+#[derive(Clone)]
+pub struct LedgerSnapshots {
+    dir: PathBuf,
+}
+
+impl LedgerSnapshots {
+    /// open (and create if needed) the snapshots directory in the
+    /// given storage directory
+    pub fn new<P: AsRef<Path>>(storage_dir: P) -> Result<Self, Error> {
+        let dir = storage_dir.as_ref().join(SNAPSHOT_DIRECTORY);
+        fs::create_dir_all(&dir)?;
+        Ok(LedgerSnapshots { dir })
+    }
+
+    fn file_path(&self, chain_length: ChainLength, block_hash: &HeaderHash) -> PathBuf {
+        // the chain length is zero padded so the lexicographic order of
+        // the file names is the order of the snapshots on the chain
+        self.dir.join(format!(
+            "{:010}-{}.{}",
+            u32::from(chain_length),
+            block_hash,
+            SNAPSHOT_EXTENSION
+        ))
+    }
+
+    /// list the snapshot files, the most recent first
+    fn files(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut files = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == SNAPSHOT_EXTENSION)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files.reverse();
+        Ok(files)
+    }
+
+    /// list the hashes of the blocks with a snapshot, the most recent first
+    pub fn list(&self) -> Result<Vec<HeaderHash>, Error> {
+        Ok(self
+            .files()?
+            .iter()
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?;
+                let (_, hash) = stem.split_at(stem.find('-')? + 1);
+                hash.parse().ok()
+            })
+            .collect())
+    }
+
+    /// write the snapshot to disk and remove the older snapshots
+    ///
+    /// The snapshot is first written to a temporary file that is then
+    /// renamed, so an interrupted write never leaves a partial snapshot.
+    pub fn write(
+        &self,
+        block0: &HeaderHash,
+        chain_length: ChainLength,
+        snapshot: &LedgerSnapshot,
+    ) -> Result<(), Error> {
+        let mut body = Vec::new();
+        write_ledger(&mut body, &snapshot.ledger)?;
+        write_ledger(&mut body, &snapshot.epoch_leadership_state)?;
+        match &snapshot.previous_epoch {
+            None => body.push(0),
+            Some((header, ledger)) => {
+                body.push(1);
+                write_bytes(
+                    &mut body,
+                    &header.serialize_as_vec().map_err(Error::Serialize)?,
+                );
+                write_ledger(&mut body, ledger)?;
+            }
+        }
+
+        let path = self.file_path(chain_length, &snapshot.block_hash);
+        let tmp_path = path.with_extension("tmp");
+        {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+            file.write_all(SNAPSHOT_MAGIC)?;
+            file.write_all(&SNAPSHOT_VERSION.to_be_bytes())?;
+            file.write_all(block0.as_bytes())?;
+            file.write_all(snapshot.block_hash.as_bytes())?;
+            file.write_all(HeaderHash::hash_bytes(&body).as_bytes())?;
+            file.write_all(&body)?;
+            file.flush()?;
+            file.get_ref().sync_all()?;
+        }
+        fs::rename(tmp_path, path)?;
+
+        for old in self.files()?.into_iter().skip(SNAPSHOTS_TO_KEEP) {
+            if let Err(err) = fs::remove_file(&old) {
+                tracing::warn!(
+                    reason = %err,
+                    "cannot remove old ledger snapshot {}",
+                    old.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// read the snapshot of the given block, checking it was made for
+    /// the given block0 by a compatible version of the node
+    pub fn read(
+        &self,
+        block0: &HeaderHash,
+        block_hash: &HeaderHash,
+    ) -> Result<LedgerSnapshot, Error> {
+        let path = self
+            .files()?
+            .into_iter()
+            .find(|path| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map_or(false, |stem| stem.ends_with(&block_hash.to_string()))
+            })
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+
+        let mut reader = io::BufReader::new(fs::File::open(path)?);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version != SNAPSHOT_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let got = read_hash(&mut reader)?;
+        if &got != block0 {
+            return Err(Error::Block0Mismatch {
+                expected: *block0,
+                got,
+            });
+        }
+        let got = read_hash(&mut reader)?;
+        if &got != block_hash {
+            return Err(Error::BlockMismatch {
+                expected: *block_hash,
+                got,
+            });
+        }
+        let checksum = read_hash(&mut reader)?;
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body)?;
+        if HeaderHash::hash_bytes(&body) != checksum {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        let mut body = body.as_slice();
+        let ledger = read_ledger(&mut body)?;
+        let epoch_leadership_state = read_ledger(&mut body)?;
+        let mut flag = [0; 1];
+        body.read_exact(&mut flag)?;
+        let previous_epoch = if flag[0] == 0 {
+            None
+        } else {
+            let header = Header::deserialize(read_bytes(&mut body)?).map_err(Error::Deserialize)?;
+            Some((header, read_ledger(&mut body)?))
+        };
+
+        Ok(LedgerSnapshot {
+            block_hash: *block_hash,
+            ledger,
+            epoch_leadership_state,
+            previous_epoch,
+        })
+    }
+}
+
+fn write_bytes(body: &mut Vec<u8>, bytes: &[u8]) {
+    body.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+    body.extend_from_slice(bytes);
+}
+
+fn read_bytes<'a>(body: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let mut len = [0; 8];
+    body.read_exact(&mut len)?;
+    let len: usize = u64::from_be_bytes(len)
+        .try_into()
+        .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    if body.len() < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    let (bytes, rest) = body.split_at(len);
+    *body = rest;
+    Ok(bytes)
+}
+
+fn write_ledger(body: &mut Vec<u8>, ledger: &Ledger) -> Result<(), Error> {
+    let bytes = ledger.serialize_as_vec().map_err(Error::Serialize)?;
+    write_bytes(body, &bytes);
+    Ok(())
+}
+
+fn read_ledger(body: &mut &[u8]) -> Result<Ledger, Error> {
+    Ledger::deserialize(read_bytes(body)?).map_err(Error::Deserialize)
+}
+
+fn read_hash<R: Read>(reader: &mut R) -> Result<HeaderHash, Error> {
+    let mut bytes = [0; 32];
+    reader.read_exact(&mut bytes)?;
+    HeaderHash::deserialize(&bytes[..]).map_err(Error::Deserialize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{
+        Block, BlockDate, BlockVersion, ConsensusVersion, ContentsBuilder, HeaderBuilderNew,
+    };
+    use crate::blockchain::{Blockchain, Storage};
+    use chain_addr::Discrimination;
+    use chain_core::property::ChainLength as _;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{fee::LinearFee, key::BftLeaderId};
+    use jormungandr_lib::interfaces::{
+        Block0Configuration, BlockchainConfiguration, ConsensusLeaderId,
+    };
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom};
+    use tempfile::TempDir;
+    use tracing::Span;
+
+    fn block0() -> Block {
+        let leader_key = SecretKey::<Ed25519>::generate(rand_core::OsRng);
+        let mut blockchain_configuration = BlockchainConfiguration::new(
+            Discrimination::Test,
+            ConsensusVersion::Bft,
+            LinearFee::new(0, 0, 0),
+        );
+        blockchain_configuration
+            .consensus_leader_ids
+            .push(ConsensusLeaderId(BftLeaderId::from(leader_key.to_public())));
+        Block0Configuration {
+            blockchain_configuration,
+            initial: Vec::new(),
+        }
+        .to_block()
+    }
+
+    /// a snapshot of the ledger of the block0, labelled as the snapshot of
+    /// the block `block_hash`
+    fn snapshot(block0: &Block, block_hash: HeaderHash) -> LedgerSnapshot {
+        let ledger = Ledger::new(block0.header.hash(), block0.contents.iter()).unwrap();
+        LedgerSnapshot {
+            block_hash,
+            ledger: ledger.clone(),
+            epoch_leadership_state: ledger.clone(),
+            previous_epoch: Some((block0.header.clone(), ledger)),
+        }
+    }
+
+    fn corrupt(path: &Path) {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .unwrap();
+        let end = file.seek(SeekFrom::End(-1)).unwrap();
+        let mut byte = [0; 1];
+        file.read_exact(&mut byte).unwrap();
+        file.seek(SeekFrom::Start(end)).unwrap();
+        file.write_all(&[byte[0] ^ 0xff]).unwrap();
+    }
+
+    #[test]
+    fn write_read_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let snapshots = LedgerSnapshots::new(dir.path()).unwrap();
+        let block0 = block0();
+        let block0_hash = block0.header.hash();
+        let block_hash = HeaderHash::hash_bytes(b"block");
+        let written = snapshot(&block0, block_hash);
+
+        snapshots
+            .write(&block0_hash, ChainLength::from(10), &written)
+            .unwrap();
+        let read = snapshots.read(&block0_hash, &block_hash).unwrap();
+
+        assert_eq!(snapshots.list().unwrap(), vec![block_hash]);
+        assert_eq!(read.block_hash, block_hash);
+        assert!(read.ledger == written.ledger);
+        assert!(read.epoch_leadership_state == written.epoch_leadership_state);
+        let (header, ledger) = read.previous_epoch.unwrap();
+        assert_eq!(header, block0.header);
+        assert!(ledger == written.ledger);
+    }
+
+    #[test]
+    fn snapshot_of_another_block0_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let snapshots = LedgerSnapshots::new(dir.path()).unwrap();
+        let block0 = block0();
+        let block_hash = HeaderHash::hash_bytes(b"block");
+
+        snapshots
+            .write(
+                &block0.header.hash(),
+                ChainLength::from(10),
+                &snapshot(&block0, block_hash),
+            )
+            .unwrap();
+        let other_block0 = HeaderHash::hash_bytes(b"other block0");
+
+        assert!(matches!(
+            snapshots.read(&other_block0, &block_hash),
+            Err(Error::Block0Mismatch { .. })
+        ));
+    }
+
+    /// an unsigned block following `parent`, stored in `storage`
+    fn child(storage: &Storage, parent: &Block) -> Block {
+        let contents = ContentsBuilder::new().into();
+        let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+            .set_parent(&parent.header.hash(), parent.header.chain_length().next())
+            .set_date(BlockDate {
+                epoch: 0,
+                slot_id: parent.header.block_date().slot_id + 1,
+            })
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        let block = Block { header, contents };
+        storage.put_block(&block).unwrap();
+        block
+    }
+
+    #[tokio::test]
+    async fn corrupt_snapshot_falls_back_to_the_previous_one() {
+        let dir = TempDir::new().unwrap();
+        let snapshots = LedgerSnapshots::new(dir.path()).unwrap();
+        let block0 = block0();
+        let block0_hash = block0.header.hash();
+        let blockchain = Blockchain::new(
+            block0_hash,
+            Storage::memory(Span::none()).unwrap(),
+            1024,
+            false,
+            Some(snapshots.clone()),
+            None,
+        );
+        let block0_ref = blockchain
+            .load_from_block0(block0.clone())
+            .await
+            .unwrap()
+            .get_ref()
+            .await;
+        let head = child(blockchain.storage(), &block0);
+        let head_hash = head.header.hash();
+
+        snapshots
+            .write(
+                &block0_hash,
+                ChainLength::from(0),
+                &snapshot(&block0, block0_hash),
+            )
+            .unwrap();
+        snapshots
+            .write(
+                &block0_hash,
+                ChainLength::from(1),
+                &snapshot(&block0, head_hash),
+            )
+            .unwrap();
+        corrupt(&snapshots.files().unwrap()[0]);
+        assert!(matches!(
+            snapshots.read(&block0_hash, &head_hash),
+            Err(Error::ChecksumMismatch)
+        ));
+
+        // the snapshots are tried from the most recent, the first one that
+        // can be read is used
+        let restored = blockchain
+            .load_ledger_snapshot(&block0_ref, head_hash)
+            .await
+            .unwrap();
+        assert_eq!(restored.hash(), block0_hash);
+    }
+}
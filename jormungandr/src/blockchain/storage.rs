@@ -1,5 +1,6 @@
 use crate::{
     blockcfg::{Block, HeaderHash},
+    blockchain::{LEDGER_SNAPSHOT_TAG, MAIN_BRANCH_TAG},
     intercom::{self, ReplySendError, ReplyStreamHandle},
 };
 use chain_core::property::{Deserialize, Serialize};
@@ -9,10 +10,34 @@ use thiserror::Error;
 use tracing::Span;
 
 use std::convert::identity;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 
 const MINIMUM_BLOCKS_TO_FLUSH: usize = 256;
 
+/// the storage tag pointing to the block below which the blocks are
+/// removed when the storage is pruned
+pub const PRUNING_POINT_TAG: &str = "PRUNING_POINT";
+
+/// the storage tag pointing to the first block of a pruned storage
+pub const PRUNED_STORAGE_START_TAG: &str = "PRUNED_STORAGE_START";
+
+/// the tags copied to the new storage when the storage is rebuilt
+const REBUILD_TAGS: &[&str] = &[
+    LEDGER_SNAPSHOT_TAG,
+    PRUNING_POINT_TAG,
+    PRUNED_STORAGE_START_TAG,
+];
+
+/// the directory of the storage directory where the block store is
+/// rebuilt
+const REBUILD_NEW_DIRECTORY: &str = "rebuild.new";
+
+/// the directory of the storage directory where the files of the replaced
+/// block store are moved while the rebuilt one is put in place
+const REBUILD_OLD_DIRECTORY: &str = "rebuild.old";
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("block not found")]
@@ -29,6 +54,8 @@ pub enum Error {
     MissingParent,
     #[error("cannot iterate between the 2 given blocks")]
     CannotIterate,
+    #[error("I/O error while rebuilding the storage")]
+    Io(#[from] std::io::Error),
 }
 
 impl From<StorageError> for Error {
@@ -42,9 +69,13 @@ impl From<StorageError> for Error {
     }
 }
 
+/// The block store is behind a lock so the storage can be rebuilt while
+/// the node is running, see `Storage::rebuild`.
 #[derive(Clone)]
 pub struct Storage {
-    storage: BlockStore,
+    storage: Arc<RwLock<BlockStore>>,
+    /// the directory of the storage, `None` if the storage is in memory
+    dir: Option<Arc<PathBuf>>,
     span: Span,
 }
 
@@ -63,17 +94,35 @@ enum StreamingError {
 
 impl Storage {
     pub fn file<P: AsRef<Path>>(path: P, span: Span) -> Result<Self, Error> {
-        let storage = BlockStore::file(path, HeaderHash::zero_hash().as_bytes().to_vec())?;
-        Ok(Storage { storage, span })
+        let storage = BlockStore::file(&path, HeaderHash::zero_hash().as_bytes().to_vec())?;
+        Ok(Storage {
+            storage: Arc::new(RwLock::new(storage)),
+            dir: Some(Arc::new(path.as_ref().to_path_buf())),
+            span,
+        })
     }
 
     pub fn memory(span: Span) -> Result<Self, Error> {
         let storage = BlockStore::memory(HeaderHash::zero_hash().as_bytes().to_vec())?;
-        Ok(Storage { storage, span })
+        Ok(Storage::from_store(storage, span))
     }
 
-    pub fn get_tag(&self, tag: &str) -> Result<Option<HeaderHash>, Error> {
+    fn from_store(storage: BlockStore, span: Span) -> Self {
+        Storage {
+            storage: Arc::new(RwLock::new(storage)),
+            dir: None,
+            span,
+        }
+    }
+
+    fn store(&self) -> RwLockReadGuard<'_, BlockStore> {
         self.storage
+            .read()
+            .expect("the block store lock is poisoned")
+    }
+
+    pub fn get_tag(&self, tag: &str) -> Result<Option<HeaderHash>, Error> {
+        self.store()
             .get_tag(tag)
             .map_err(Into::into)
             .and_then(|maybe_block_id| {
@@ -86,13 +135,13 @@ impl Storage {
     }
 
     pub fn put_tag(&self, tag: &str, header_hash: HeaderHash) -> Result<(), Error> {
-        self.storage
+        self.store()
             .put_tag(tag, header_hash.as_bytes())
             .map_err(Into::into)
     }
 
     pub fn get(&self, header_hash: HeaderHash) -> Result<Option<Block>, Error> {
-        match self.storage.get_block(header_hash.as_bytes()) {
+        match self.store().get_block(header_hash.as_bytes()) {
             Ok(block) => Block::deserialize(block.as_ref())
                 .map(Some)
                 .map_err(Error::Deserialize),
@@ -102,13 +151,32 @@ impl Storage {
     }
 
     pub fn block_exists(&self, header_hash: HeaderHash) -> Result<bool, Error> {
-        self.storage
+        self.store()
             .block_exists(header_hash.as_ref())
             .map_err(Into::into)
     }
 
+    /// returns the first block kept in the storage if the blocks before it
+    /// have been removed by the storage pruning
+    pub fn pruned_start(&self) -> Result<Option<HeaderHash>, Error> {
+        self.get_tag(PRUNED_STORAGE_START_TAG)
+    }
+
+    pub fn is_pruned(&self) -> Result<bool, Error> {
+        self.pruned_start().map(|start| start.is_some())
+    }
+
+    /// the hashes of the blocks without children
+    pub fn get_tips(&self) -> Result<Vec<HeaderHash>, Error> {
+        self.store()
+            .get_tips_ids()?
+            .into_iter()
+            .map(|id| HeaderHash::deserialize(id.as_ref()).map_err(Error::Deserialize))
+            .collect()
+    }
+
     pub fn get_blocks_by_chain_length(&self, chain_length: u32) -> Result<Vec<Block>, Error> {
-        self.storage
+        self.store()
             .get_blocks_by_chain_length(chain_length)
             .map_err(Into::into)
             .and_then(|blocks| {
@@ -133,7 +201,7 @@ impl Storage {
             .map_err(Error::Serialize)?;
         let chain_length = block.header.chain_length().into();
         let block_info = BlockInfo::new(id, parent_id, chain_length);
-        self.storage
+        self.store()
             .put_block(
                 &block.serialize_as_vec().map_err(Error::Serialize)?[..],
                 block_info,
@@ -152,11 +220,11 @@ impl Storage {
         to: HeaderHash,
     ) -> Result<impl Stream<Item = Result<Block, intercom::Error>>, Error> {
         let distance = self
-            .storage
+            .store()
             .is_ancestor(from.as_bytes(), to.as_bytes())?
             .ok_or(Error::CannotIterate)?;
 
-        let stream = futures::stream::iter(self.storage.iter(to.as_bytes(), distance)?)
+        let stream = futures::stream::iter(self.store().iter(to.as_bytes(), distance)?)
             .map_err(Into::into)
             .and_then(|raw_block| async move {
                 Block::deserialize(raw_block.as_ref()).map_err(Error::Deserialize)
@@ -166,6 +234,220 @@ impl Storage {
         Ok(stream)
     }
 
+    /// Copy the blocks from `from` to `to`, both included, to a new storage
+    /// created at `path`. `from` is the first block of the new storage: none
+    /// of its ancestors is copied, nor any block that is not an ancestor of
+    /// `to`.
+    fn copy_branch_to(
+        &self,
+        path: &Path,
+        from: HeaderHash,
+        to: HeaderHash,
+    ) -> Result<Storage, Error> {
+        let first = self.get(from)?.ok_or(Error::BlockNotFound)?;
+        let distance = self
+            .store()
+            .is_ancestor(from.as_bytes(), to.as_bytes())?
+            .ok_or(Error::CannotIterate)?;
+
+        let storage = BlockStore::file(path, first.header.block_parent_hash().as_bytes().to_vec())?;
+        let copy = Storage::from_store(storage, self.span.clone());
+
+        copy.put_block(&first)?;
+        if distance > 0 {
+            for raw_block in self.store().iter(to.as_bytes(), distance)? {
+                let block = Block::deserialize(raw_block?.as_ref()).map_err(Error::Deserialize)?;
+                match copy.put_block(&block) {
+                    Ok(()) | Err(Error::BlockAlreadyPresent) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        tracing::debug!("copied {} blocks from {} to {}", distance + 1, from, to);
+
+        Ok(copy)
+    }
+
+    /// copy to `copy` the blocks of the branches that join the blocks
+    /// already copied, the blocks of `copy` starting at `from_length`
+    fn copy_branches_to(&self, copy: &Storage, from_length: u32) -> Result<(), Error> {
+        for tip in self.get_tips()? {
+            // the blocks are walked down from the tip until one of `copy`,
+            // which is only long for a branch that does not join them
+            let mut blocks = Vec::new();
+            let mut current = tip;
+            let joined = loop {
+                if copy.block_exists(current)? {
+                    break true;
+                }
+                match self.get(current)? {
+                    Some(block) if u32::from(block.header.chain_length()) > from_length => {
+                        current = block.header.block_parent_hash();
+                        blocks.push(block);
+                    }
+                    // removed in the meantime, or below the first block
+                    _ => break false,
+                }
+            };
+            if !joined || blocks.is_empty() {
+                continue;
+            }
+            for block in blocks.iter().rev() {
+                match copy.put_block(block) {
+                    Ok(()) | Err(Error::BlockAlreadyPresent) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            tracing::debug!("copied the branch with head {}", tip);
+        }
+        Ok(())
+    }
+
+    /// replace the blocks of the storage with its blocks from `from` to `to`.
+    /// If `pruned` is set, the new storage is tagged as pruned from `from`.
+    /// If `keep_branches` is set, the other branches descending from `from`
+    /// are kept as well, including the blocks added while the storage is
+    /// rebuilt, and `HEAD` is kept; otherwise `to` becomes the `HEAD`.
+    ///
+    /// The blocks are copied to a new block store in a directory of the
+    /// storage directory, while the storage can still be used. The storage
+    /// is then locked for a moment to copy the blocks added meanwhile and to
+    /// put the files of the new block store in place of the current ones.
+    /// The other files of the storage directory are left untouched. An
+    /// interrupted rebuild is recovered from at the next start (see
+    /// `Storage::recover_rebuild`).
+    ///
+    /// Nothing is done if the storage is in memory.
+    pub fn rebuild(
+        &self,
+        from: HeaderHash,
+        to: HeaderHash,
+        pruned: bool,
+        keep_branches: bool,
+    ) -> Result<(), Error> {
+        let _enter = self.span.enter();
+        let dir = match &self.dir {
+            Some(dir) => dir.as_path(),
+            None => return Ok(()),
+        };
+        let new_dir = dir.join(REBUILD_NEW_DIRECTORY);
+        if new_dir.exists() {
+            fs::remove_dir_all(&new_dir)?;
+        }
+
+        // a handle on the current block store, so the blocks are copied
+        // without holding the lock of the storage
+        let current = Storage::from_store(self.store().clone(), self.span.clone());
+        let from_length = current
+            .get(from)?
+            .map(|block| u32::from(block.header.chain_length()))
+            .ok_or(Error::BlockNotFound)?;
+        let copy = current.copy_branch_to(&new_dir, from, to)?;
+        if keep_branches {
+            current.copy_branches_to(&copy, from_length)?;
+        }
+
+        let mut store = self
+            .storage
+            .write()
+            .expect("the block store lock is poisoned");
+        let mut head = to;
+        if keep_branches {
+            // only the blocks added since the copy are left to be copied
+            current.copy_branches_to(&copy, from_length)?;
+            if let Some(current_head) = current.get_tag(MAIN_BRANCH_TAG)? {
+                if copy.block_exists(current_head)? {
+                    head = current_head;
+                }
+            }
+        }
+        for tag in REBUILD_TAGS {
+            if let Some(header_hash) = current.get_tag(tag)? {
+                if copy.block_exists(header_hash)? {
+                    copy.put_tag(tag, header_hash)?;
+                }
+            }
+        }
+        copy.put_tag(MAIN_BRANCH_TAG, head)?;
+        if pruned {
+            copy.put_tag(PRUNED_STORAGE_START_TAG, from)?;
+        }
+
+        // both block stores need to be closed before moving their files
+        std::mem::drop(copy);
+        std::mem::drop(current);
+        let placeholder = BlockStore::memory(HeaderHash::zero_hash().as_bytes().to_vec())?;
+        std::mem::drop(std::mem::replace(&mut *store, placeholder));
+
+        fs::create_dir_all(dir.join(REBUILD_OLD_DIRECTORY))?;
+        if let Err(err) = finish_block_store_swap(dir) {
+            // the swap is finished at the next start, the block store is
+            // not usable until then
+            tracing::error!(
+                reason = %err,
+                "cannot put the rebuilt block store in place, the node needs to be restarted"
+            );
+            return Err(err.into());
+        }
+        *store = BlockStore::file(dir, HeaderHash::zero_hash().as_bytes().to_vec())?;
+
+        Ok(())
+    }
+
+    /// remove from the storage the blocks before its pruning point, see
+    /// `Storage::rebuild`. Nothing is done if there is no pruning point or
+    /// if the storage is already pruned from it.
+    pub fn prune(&self) -> Result<(), Error> {
+        let pruning_point = match self.get_tag(PRUNING_POINT_TAG)? {
+            Some(pruning_point) => pruning_point,
+            None => return Ok(()),
+        };
+        if self.pruned_start()? == Some(pruning_point) {
+            return Ok(());
+        }
+        if !self.block_exists(pruning_point)? {
+            tracing::warn!(
+                "the pruning point {} is not in the storage, see `--storage-check`",
+                pruning_point
+            );
+            return Ok(());
+        }
+        let head = match self.get_tag(MAIN_BRANCH_TAG)? {
+            Some(head) => head,
+            None => return Ok(()),
+        };
+
+        tracing::info!(
+            "pruning the storage, keeping the blocks from {} to {}",
+            pruning_point,
+            head
+        );
+        // the recent forks are kept for the chain selection
+        self.rebuild(pruning_point, head, true, true)?;
+        tracing::info!("the storage is pruned");
+
+        Ok(())
+    }
+
+    /// finish, or discard, a rebuild of the storage in `dir` that was
+    /// interrupted, see `Storage::rebuild`
+    pub fn recover_rebuild(dir: &Path) -> Result<(), Error> {
+        if dir.join(REBUILD_OLD_DIRECTORY).exists() {
+            // the new block store was complete when its files started to
+            // be moved in place
+            tracing::warn!("the last rebuild of the storage was interrupted, finishing it");
+            finish_block_store_swap(dir)?;
+        } else {
+            let new_dir = dir.join(REBUILD_NEW_DIRECTORY);
+            if new_dir.exists() {
+                tracing::info!("removing the block store left over by an interrupted rebuild");
+                fs::remove_dir_all(new_dir)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Stream a branch ending at `to` and starting from the ancestor
     /// at `depth` or at the first ancestor since genesis block
     /// if `depth` is given as `None`.
@@ -195,7 +477,7 @@ impl Storage {
         F: Send + 'static,
         T: Send + 'static,
     {
-        let iter_result = self.storage.iter(to.as_bytes(), depth.unwrap_or(1));
+        let iter_result = self.store().iter(to.as_bytes(), depth.unwrap_or(1));
 
         let iter = match iter_result {
             Ok(iter) => iter,
@@ -219,6 +501,48 @@ impl Storage {
         handle.start_sending().send_all(&mut stream).await
     }
 
+    /// get the hash of the ancestor of `descendant` at `distance` blocks from it
+    pub fn get_nth_ancestor(
+        &self,
+        descendant: HeaderHash,
+        distance: u32,
+    ) -> Result<HeaderHash, Error> {
+        let block_info = self
+            .store()
+            .get_nth_ancestor(descendant.as_bytes(), distance)?;
+        HeaderHash::deserialize(block_info.id().as_ref()).map_err(Error::Deserialize)
+    }
+
+    /// find the last block the branches of the two given blocks have in
+    /// common, the blocks being given with their chain length. Returns the
+    /// hash and the chain length of the common ancestor.
+    pub fn find_common_ancestor(
+        &self,
+        (a, a_length): (HeaderHash, u32),
+        (b, b_length): (HeaderHash, u32),
+    ) -> Result<(HeaderHash, u32), Error> {
+        let length = a_length.min(b_length);
+        let a = self.get_nth_ancestor(a, a_length - length)?;
+        let b = self.get_nth_ancestor(b, b_length - length)?;
+        if a == b {
+            return Ok((a, length));
+        }
+
+        // the ancestors of `a` and `b` at the same distance differ up to the
+        // fork point and are the same from there, down to the block0
+        let (mut differ, mut same) = (0, length);
+        while same - differ > 1 {
+            let distance = differ + (same - differ) / 2;
+            if self.get_nth_ancestor(a, distance)? == self.get_nth_ancestor(b, distance)? {
+                same = distance;
+            } else {
+                differ = distance;
+            }
+        }
+
+        Ok((self.get_nth_ancestor(a, same)?, length - same))
+    }
+
     pub fn find_closest_ancestor(
         &self,
         checkpoints: Vec<HeaderHash>,
@@ -231,7 +555,7 @@ impl Storage {
             // Checkpoints sent by a peer may not
             // be present locally, so we need to ignore certain errors
             match self
-                .storage
+                .store()
                 .is_ancestor(checkpoint.as_bytes(), descendant.as_bytes())
             {
                 Ok(None) => {}
@@ -264,7 +588,7 @@ impl Storage {
 
     pub fn gc(&self, threshold_depth: u32, main_branch_tip: &[u8]) -> Result<(), Error> {
         let _enter = self.span.enter();
-        let main_info = self.storage.get_block_info(main_branch_tip)?;
+        let main_info = self.store().get_block_info(main_branch_tip)?;
         let threshold_length = match main_info.chain_length().checked_sub(threshold_depth) {
             Some(result) => result,
             None => return Ok(()),
@@ -276,16 +600,16 @@ impl Storage {
             threshold_length
         );
 
-        let tips_ids = self.storage.get_tips_ids()?;
+        let tips_ids = self.store().get_tips_ids()?;
 
         for id in tips_ids {
-            let info = self.storage.get_block_info(id.as_ref())?;
+            let info = self.store().get_block_info(id.as_ref())?;
 
             if info.chain_length() > threshold_length {
                 continue;
             }
 
-            self.storage.prune_branch(id.as_ref())?;
+            self.store().prune_branch(id.as_ref())?;
 
             tracing::debug!(
                 "removed branch with head {}",
@@ -294,10 +618,10 @@ impl Storage {
         }
 
         let to_block_info = self
-            .storage
+            .store()
             .get_nth_ancestor(main_branch_tip, threshold_depth)?;
         let blocks_flushed = self
-            .storage
+            .store()
             .flush_to_permanent_store(to_block_info.id().as_ref(), MINIMUM_BLOCKS_TO_FLUSH)?;
 
         tracing::debug!(
@@ -309,3 +633,204 @@ impl Storage {
         Ok(())
     }
 }
+
+/// move the files of the rebuilt block store in place of the ones of the
+/// current block store, which are moved out of the way first. Only the files
+/// the rebuilt block store is made of are moved.
+///
+/// Each file is moved at most once, so an interrupted swap is finished by
+/// running it again.
+fn finish_block_store_swap(dir: &Path) -> std::io::Result<()> {
+    let new_dir = dir.join(REBUILD_NEW_DIRECTORY);
+    let old_dir = dir.join(REBUILD_OLD_DIRECTORY);
+    if new_dir.exists() {
+        for entry in fs::read_dir(&new_dir)? {
+            let name = entry?.file_name();
+            let path = dir.join(&name);
+            if path.exists() {
+                fs::rename(&path, old_dir.join(&name))?;
+            }
+            fs::rename(new_dir.join(&name), &path)?;
+        }
+        fs::remove_dir(&new_dir)?;
+    }
+    fs::remove_dir_all(&old_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{BlockDate, BlockVersion, ContentsBuilder, HeaderBuilderNew};
+    use chain_core::property::ChainLength as _;
+    use tempfile::TempDir;
+
+    fn genesis(storage: &Storage) -> Block {
+        let contents = ContentsBuilder::new().into();
+        let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+            .set_genesis()
+            .set_date(BlockDate::first())
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        let block = Block { header, contents };
+        storage.put_block(&block).unwrap();
+        block
+    }
+
+    /// append `length` blocks after `parent`, the `fork` number is used to
+    /// give different dates, hence different hashes, to competing branches
+    fn branch(storage: &Storage, parent: &Block, length: u32, fork: u32) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for _ in 0..length {
+            let parent = blocks.last().unwrap_or(parent);
+            let contents = ContentsBuilder::new().into();
+            let date = BlockDate {
+                epoch: 0,
+                slot_id: parent.header.block_date().slot_id + 1 + fork * 1_000,
+            };
+            let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+                .set_parent(&parent.header.hash(), parent.header.chain_length().next())
+                .set_date(date)
+                .into_unsigned_header()
+                .unwrap()
+                .generalize();
+            let block = Block { header, contents };
+            storage.put_block(&block).unwrap();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    fn with_length(block: &Block) -> (HeaderHash, u32) {
+        (block.header.hash(), block.header.chain_length().into())
+    }
+
+    #[test]
+    fn common_ancestor_of_forks() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 20, 0);
+        // forks from the 8th block, with branches of different lengths
+        let fork = branch(&storage, &main[7], 5, 1);
+
+        for (a, b) in &[
+            (&main[19], &fork[4]),
+            (&fork[4], &main[19]),
+            (&main[8], &fork[0]),
+        ] {
+            assert_eq!(
+                storage
+                    .find_common_ancestor(with_length(a), with_length(b))
+                    .unwrap(),
+                with_length(&main[7])
+            );
+        }
+    }
+
+    #[test]
+    fn common_ancestor_of_the_same_branch() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 20, 0);
+
+        assert_eq!(
+            storage
+                .find_common_ancestor(with_length(&main[19]), with_length(&main[4]))
+                .unwrap(),
+            with_length(&main[4])
+        );
+        assert_eq!(
+            storage
+                .find_common_ancestor(with_length(&main[19]), with_length(&main[19]))
+                .unwrap(),
+            with_length(&main[19])
+        );
+    }
+
+    #[test]
+    fn common_ancestor_of_forks_from_block0() {
+        let storage = Storage::memory(Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 20, 0);
+        let fork = branch(&storage, &block0, 3, 1);
+
+        assert_eq!(
+            storage
+                .find_common_ancestor(with_length(&main[19]), with_length(&fork[2]))
+                .unwrap(),
+            with_length(&block0)
+        );
+    }
+
+    #[test]
+    fn pruning_keeps_the_other_files_of_the_storage_directory() {
+        let dir = TempDir::new().unwrap();
+        let storage = Storage::file(dir.path(), Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 10, 0);
+        let fork = branch(&storage, &main[7], 2, 1);
+        storage
+            .put_tag(MAIN_BRANCH_TAG, main[9].header.hash())
+            .unwrap();
+        storage
+            .put_tag(PRUNING_POINT_TAG, main[4].header.hash())
+            .unwrap();
+        fs::write(dir.path().join("mempool"), b"pending fragments").unwrap();
+
+        storage.prune().unwrap();
+
+        assert_eq!(
+            fs::read(dir.path().join("mempool")).unwrap(),
+            b"pending fragments"
+        );
+        assert!(!dir.path().join(REBUILD_NEW_DIRECTORY).exists());
+        assert!(!dir.path().join(REBUILD_OLD_DIRECTORY).exists());
+        assert!(!storage.block_exists(main[3].header.hash()).unwrap());
+        assert!(storage.block_exists(main[4].header.hash()).unwrap());
+        assert!(storage.block_exists(fork[1].header.hash()).unwrap());
+        assert_eq!(
+            storage.get_tag(MAIN_BRANCH_TAG).unwrap(),
+            Some(main[9].header.hash())
+        );
+        assert_eq!(storage.pruned_start().unwrap(), Some(main[4].header.hash()));
+    }
+
+    #[test]
+    fn interrupted_swap_is_finished() {
+        let dir = TempDir::new().unwrap();
+        let block0 = genesis(&Storage::file(dir.path(), Span::none()).unwrap());
+        let rebuilt = {
+            let storage =
+                Storage::file(dir.path().join(REBUILD_NEW_DIRECTORY), Span::none()).unwrap();
+            genesis(&storage);
+            branch(&storage, &block0, 3, 0)
+        };
+        fs::create_dir(dir.path().join(REBUILD_OLD_DIRECTORY)).unwrap();
+
+        Storage::recover_rebuild(dir.path()).unwrap();
+
+        assert!(!dir.path().join(REBUILD_NEW_DIRECTORY).exists());
+        assert!(!dir.path().join(REBUILD_OLD_DIRECTORY).exists());
+        let storage = Storage::file(dir.path(), Span::none()).unwrap();
+        assert!(storage.block_exists(rebuilt[2].header.hash()).unwrap());
+    }
+
+    #[test]
+    fn incomplete_rebuild_is_discarded() {
+        let dir = TempDir::new().unwrap();
+        let block0 = genesis(&Storage::file(dir.path(), Span::none()).unwrap());
+        let discarded = {
+            let storage =
+                Storage::file(dir.path().join(REBUILD_NEW_DIRECTORY), Span::none()).unwrap();
+            genesis(&storage);
+            branch(&storage, &block0, 3, 0)
+        };
+
+        Storage::recover_rebuild(dir.path()).unwrap();
+
+        assert!(!dir.path().join(REBUILD_NEW_DIRECTORY).exists());
+        let storage = Storage::file(dir.path(), Span::none()).unwrap();
+        assert!(storage.block_exists(block0.header.hash()).unwrap());
+        assert!(!storage.block_exists(discarded[2].header.hash()).unwrap());
+    }
+}
@@ -0,0 +1,486 @@
+//! Integrity check of the block storage, used by the `--storage-check` and
+//! `--storage-repair` modes of the node.
+//!
+//! The main branch is walked from the `HEAD` tag down to the first block of
+//! the storage (the block0, or the first block kept by a pruned storage).
+//! Every block is checked to deserialize, to be stored under the key of its
+//! hash and to have a chain length following the one of its parent. If the
+//! branch is broken, the chain is walked up from the first block to find
+//! the last block that can be reached through consistent blocks: this is
+//! where the storage is truncated when repaired.
+
+use crate::{
+    blockcfg::{Block, HeaderHash},
+    blockchain::{
+        storage::{PRUNED_STORAGE_START_TAG, PRUNING_POINT_TAG},
+        Storage, StorageError, LEDGER_SNAPSHOT_TAG, MAIN_BRANCH_TAG,
+    },
+};
+use chain_core::property::ChainLength as _;
+
+use std::{collections::HashSet, fmt};
+
+/// the tags checked to point to a block of the storage, in addition
+/// to `MAIN_BRANCH_TAG`
+const TAGS: &[&str] = &[
+    LEDGER_SNAPSHOT_TAG,
+    PRUNING_POINT_TAG,
+    PRUNED_STORAGE_START_TAG,
+];
+
+pub enum Issue {
+    NoHead,
+    CorruptBlock {
+        hash: HeaderHash,
+        reason: StorageError,
+    },
+    HashMismatch {
+        key: HeaderHash,
+        hash: HeaderHash,
+    },
+    ChainLengthMismatch {
+        hash: HeaderHash,
+        expected: u32,
+        got: u32,
+    },
+    MissingBlock {
+        hash: HeaderHash,
+        child: HeaderHash,
+    },
+    DanglingTag {
+        tag: String,
+        hash: HeaderHash,
+    },
+    Orphan {
+        tip: HeaderHash,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::NoHead => write!(f, "the storage has no `{}` tag", MAIN_BRANCH_TAG),
+            Issue::CorruptBlock { hash, reason } => {
+                write!(f, "block {} cannot be read: {}", hash, reason)
+            }
+            Issue::HashMismatch { key, hash } => write!(
+                f,
+                "the block stored under the key {} has the hash {}",
+                key, hash
+            ),
+            Issue::ChainLengthMismatch {
+                hash,
+                expected,
+                got,
+            } => write!(
+                f,
+                "block {} has the chain length {}, expected {}",
+                hash, got, expected
+            ),
+            Issue::MissingBlock { hash, child } => write!(
+                f,
+                "block {}, parent of block {}, is missing from the storage",
+                hash, child
+            ),
+            Issue::DanglingTag { tag, hash } => write!(
+                f,
+                "the `{}` tag points to block {} which is not in the storage",
+                tag, hash
+            ),
+            Issue::Orphan { tip } => write!(
+                f,
+                "the branch of block {} is not connected to the first block of the storage",
+                tip
+            ),
+        }
+    }
+}
+
+pub struct StorageCheckReport {
+    /// the block pointed by the `HEAD` tag
+    pub head: Option<HeaderHash>,
+    /// the first block of the storage, the block0 unless the storage is pruned
+    pub first_block: Option<HeaderHash>,
+    /// the last block of the main branch that can be reached from the first
+    /// block through consistent blocks
+    pub last_consistent: Option<HeaderHash>,
+    /// the number of blocks of the main branch that were checked
+    pub blocks_checked: u64,
+    pub issues: Vec<Issue>,
+}
+
+impl StorageCheckReport {
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// check the integrity of the storage, see the module documentation.
+///
+/// An error is only returned if the storage cannot be accessed at all,
+/// the inconsistencies that are found are listed in the report.
+pub fn check_storage(storage: &Storage) -> Result<StorageCheckReport, StorageError> {
+    let mut issues = Vec::new();
+
+    let first_block = match storage.pruned_start()? {
+        Some(start) => Some(start),
+        None => storage
+            .get_blocks_by_chain_length(0)
+            .ok()
+            .and_then(|blocks| blocks.first().map(|block| block.header.hash())),
+    };
+
+    let head = storage.get_tag(MAIN_BRANCH_TAG)?;
+    let (main_branch, blocks_checked, reached_first_block) = match head {
+        Some(head) => walk_down(storage, head, first_block, &mut issues)?,
+        // a new storage has no block yet, hence no `HEAD`
+        None if first_block.is_none() && storage.get_tips()?.is_empty() => {
+            (HashSet::new(), 0, true)
+        }
+        None => {
+            issues.push(Issue::NoHead);
+            (HashSet::new(), 0, false)
+        }
+    };
+
+    let last_consistent = if reached_first_block {
+        head
+    } else {
+        match first_block {
+            Some(first_block) => walk_up(storage, first_block, &main_branch)?,
+            None => None,
+        }
+    };
+
+    for tag in TAGS {
+        if let Some(hash) = storage.get_tag(tag)? {
+            if !storage.block_exists(hash)? {
+                issues.push(Issue::DanglingTag {
+                    tag: tag.to_string(),
+                    hash,
+                });
+            }
+        }
+    }
+
+    if let Some(first_block) = first_block {
+        for tip in storage.get_tips()? {
+            if storage
+                .find_closest_ancestor(vec![first_block], tip)?
+                .is_none()
+            {
+                issues.push(Issue::Orphan { tip });
+            }
+        }
+    }
+
+    Ok(StorageCheckReport {
+        head,
+        first_block,
+        last_consistent,
+        blocks_checked,
+        issues,
+    })
+}
+
+/// walk the main branch from `head` down to the first block of the storage,
+/// returns the hashes of the consistent blocks that were walked through,
+/// their number and whether the first block was reached.
+fn walk_down(
+    storage: &Storage,
+    head: HeaderHash,
+    first_block: Option<HeaderHash>,
+    issues: &mut Vec<Issue>,
+) -> Result<(HashSet<HeaderHash>, u64, bool), StorageError> {
+    let mut branch = HashSet::new();
+    let mut current = head;
+    let mut child: Option<(HeaderHash, u32)> = None;
+
+    let reached_first_block = loop {
+        let block = match read_block(storage, current)? {
+            BlockRead::Valid(block) => block,
+            BlockRead::Missing => {
+                issues.push(match child {
+                    Some((child, _)) => Issue::MissingBlock {
+                        hash: current,
+                        child,
+                    },
+                    None => Issue::DanglingTag {
+                        tag: MAIN_BRANCH_TAG.to_owned(),
+                        hash: current,
+                    },
+                });
+                break false;
+            }
+            BlockRead::Invalid(issue) => {
+                issues.push(issue);
+                break false;
+            }
+        };
+
+        let chain_length = u32::from(block.header.chain_length());
+        if let Some((child, child_chain_length)) = child {
+            let expected = u32::from(block.header.chain_length().next());
+            if expected != child_chain_length {
+                issues.push(Issue::ChainLengthMismatch {
+                    hash: child,
+                    expected,
+                    got: child_chain_length,
+                });
+                break false;
+            }
+        }
+
+        branch.insert(current);
+
+        let parent = block.header.block_parent_hash();
+        if Some(current) == first_block || parent == HeaderHash::zero_hash() {
+            break true;
+        }
+        child = Some((current, chain_length));
+        current = parent;
+    };
+
+    let blocks_checked = branch.len() as u64;
+    Ok((branch, blocks_checked, reached_first_block))
+}
+
+/// walk the chain up from the first block of the storage, following the
+/// main branch when there are forks, and return the last block reached
+/// through consistent blocks.
+fn walk_up(
+    storage: &Storage,
+    first_block: HeaderHash,
+    main_branch: &HashSet<HeaderHash>,
+) -> Result<Option<HeaderHash>, StorageError> {
+    // the issues found here were already reported while walking down the
+    // main branch, only the consistent blocks matter
+    let mut current = match read_block(storage, first_block)? {
+        BlockRead::Valid(block) => block,
+        _ => return Ok(None),
+    };
+
+    loop {
+        let hash = current.header.hash();
+        let next_chain_length = u32::from(current.header.chain_length().next());
+        let mut children = match storage.get_blocks_by_chain_length(next_chain_length) {
+            Ok(blocks) => blocks,
+            Err(StorageError::Deserialize(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        children.retain(|block| block.header.block_parent_hash() == hash);
+        children.sort_by_key(|block| !main_branch.contains(&block.header.hash()));
+
+        let mut next = None;
+        for child in children {
+            if let BlockRead::Valid(block) = read_block(storage, child.header.hash())? {
+                next = Some(block);
+                break;
+            }
+        }
+
+        match next {
+            Some(block) => current = block,
+            None => return Ok(Some(hash)),
+        }
+    }
+}
+
+enum BlockRead {
+    Valid(Block),
+    Missing,
+    Invalid(Issue),
+}
+
+/// read the block stored under the given hash
+fn read_block(storage: &Storage, key: HeaderHash) -> Result<BlockRead, StorageError> {
+    let block = match storage.get(key) {
+        Ok(Some(block)) => block,
+        Ok(None) => return Ok(BlockRead::Missing),
+        Err(reason @ StorageError::Deserialize(_)) => {
+            return Ok(BlockRead::Invalid(Issue::CorruptBlock {
+                hash: key,
+                reason,
+            }))
+        }
+        Err(e) => return Err(e),
+    };
+
+    let hash = block.header.hash();
+    if hash != key {
+        return Ok(BlockRead::Invalid(Issue::HashMismatch { key, hash }));
+    }
+
+    Ok(BlockRead::Valid(block))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{BlockDate, BlockVersion, ContentsBuilder, HeaderBuilderNew};
+    use tempfile::TempDir;
+    use tracing::Span;
+
+    fn genesis(storage: &Storage) -> Block {
+        let contents = ContentsBuilder::new().into();
+        let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+            .set_genesis()
+            .set_date(BlockDate::first())
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        let block = Block { header, contents };
+        storage.put_block(&block).unwrap();
+        storage
+            .put_tag(MAIN_BRANCH_TAG, block.header.hash())
+            .unwrap();
+        block
+    }
+
+    /// append `length` blocks after `parent`, the `fork` number is used to
+    /// give different dates, hence different hashes, to competing branches
+    fn branch(storage: &Storage, parent: &Block, length: u32, fork: u32) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for _ in 0..length {
+            let parent = blocks.last().unwrap_or(parent);
+            let contents = ContentsBuilder::new().into();
+            let date = BlockDate {
+                epoch: 0,
+                slot_id: parent.header.block_date().slot_id + 1 + fork * 1_000,
+            };
+            let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+                .set_parent(&parent.header.hash(), parent.header.chain_length().next())
+                .set_date(date)
+                .into_unsigned_header()
+                .unwrap()
+                .generalize();
+            let block = Block { header, contents };
+            storage.put_block(&block).unwrap();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    /// a storage with a main branch of 10 blocks after the block0, with
+    /// `HEAD` on its last block, and a side branch of 2 blocks forking
+    /// from the first block
+    fn storage(dir: &TempDir) -> (Storage, Vec<Block>, Vec<Block>) {
+        let path = dir.path().join("storage");
+        std::fs::create_dir_all(&path).unwrap();
+        let storage = Storage::file(path, Span::none()).unwrap();
+        let block0 = genesis(&storage);
+        let main = branch(&storage, &block0, 10, 0);
+        let side = branch(&storage, &main[0], 2, 1);
+        let mut blocks = vec![block0];
+        blocks.extend(main);
+        storage
+            .put_tag(MAIN_BRANCH_TAG, blocks[10].header.hash())
+            .unwrap();
+        (storage, blocks, side)
+    }
+
+    #[test]
+    fn empty_storage_is_consistent() {
+        let storage = Storage::memory(Span::none()).unwrap();
+
+        let report = check_storage(&storage).unwrap();
+
+        assert!(report.is_consistent());
+        assert_eq!(report.blocks_checked, 0);
+    }
+
+    #[test]
+    fn consistent_storage() {
+        let dir = TempDir::new().unwrap();
+        let (storage, blocks, _) = storage(&dir);
+
+        let report = check_storage(&storage).unwrap();
+
+        assert!(report.is_consistent());
+        assert_eq!(report.blocks_checked, 11);
+        assert_eq!(report.first_block, Some(blocks[0].header.hash()));
+        assert_eq!(report.last_consistent, Some(blocks[10].header.hash()));
+    }
+
+    #[test]
+    fn dangling_tag_is_reported_and_repaired() {
+        let dir = TempDir::new().unwrap();
+        let (storage, blocks, side) = storage(&dir);
+        storage
+            .put_tag(MAIN_BRANCH_TAG, side[1].header.hash())
+            .unwrap();
+        // the side branch is deeper than 5 blocks from the main branch tip
+        storage.gc(5, blocks[10].header.hash().as_ref()).unwrap();
+
+        let report = check_storage(&storage).unwrap();
+
+        assert!(matches!(
+            report.issues.as_slice(),
+            [Issue::DanglingTag { tag, hash }]
+                if tag.as_str() == MAIN_BRANCH_TAG && *hash == side[1].header.hash()
+        ));
+        assert_eq!(report.last_consistent, Some(blocks[10].header.hash()));
+
+        storage
+            .rebuild(
+                report.first_block.unwrap(),
+                report.last_consistent.unwrap(),
+                false,
+                false,
+            )
+            .unwrap();
+        let report = check_storage(&storage).unwrap();
+
+        assert!(report.is_consistent());
+        assert_eq!(report.head, Some(blocks[10].header.hash()));
+        assert_eq!(report.blocks_checked, 11);
+    }
+
+    #[test]
+    fn missing_block_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let (storage, blocks, _) = storage(&dir);
+        // the blocks before the 5th one are removed, without the storage
+        // being tagged as pruned
+        storage
+            .rebuild(
+                blocks[5].header.hash(),
+                blocks[10].header.hash(),
+                false,
+                false,
+            )
+            .unwrap();
+
+        let report = check_storage(&storage).unwrap();
+
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            Issue::MissingBlock { hash, child }
+                if *hash == blocks[4].header.hash() && *child == blocks[5].header.hash()
+        )));
+        assert_eq!(report.blocks_checked, 6);
+        // there is no first block to repair the storage from
+        assert_eq!(report.first_block, None);
+        assert_eq!(report.last_consistent, None);
+    }
+
+    #[test]
+    fn pruned_storage_is_consistent() {
+        let dir = TempDir::new().unwrap();
+        let (storage, blocks, _) = storage(&dir);
+        storage
+            .rebuild(
+                blocks[5].header.hash(),
+                blocks[10].header.hash(),
+                true,
+                false,
+            )
+            .unwrap();
+
+        let report = check_storage(&storage).unwrap();
+
+        assert!(report.is_consistent());
+        assert_eq!(report.first_block, Some(blocks[5].header.hash()));
+        assert_eq!(report.blocks_checked, 6);
+    }
+}
@@ -0,0 +1,230 @@
+//! Notifications of the changes of the tip, streamed to the clients of the
+//! REST API.
+//!
+//! Every event gets a sequence number. The most recent events are kept, so a
+//! client reconnecting with the sequence number of the last event it received
+//! gets the events it missed, as long as they are still kept.
+//!
+//! The sequence numbers start at the time the node started, in microseconds
+//! since the Unix epoch, so the numbers of the events of a run of the node
+//! are larger than the ones of the previous runs. A client reconnecting with
+//! a sequence number that is not followed by kept events, because the events
+//! were dropped or were sent by a previous run of the node, gets a `Resync`
+//! event instead: it has to get the current state of the node again.
+
+use crate::{blockcfg::HeaderHash, blockchain::Ref};
+use jormungandr_lib::interfaces::{TipEvent, TipEventKind};
+use tokio::sync::broadcast;
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// number of the most recent events kept to be sent again to reconnecting
+/// clients, this is also the number of events a slow client can lag behind
+const RECENT_EVENTS_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+pub struct TipEvents {
+    inner: Arc<Mutex<Inner>>,
+    sender: broadcast::Sender<TipEvent>,
+}
+
+struct Inner {
+    next_sequence: u64,
+    recent: VecDeque<TipEvent>,
+}
+
+impl Default for TipEvents {
+    fn default() -> Self {
+        let first_sequence = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_micros() as u64);
+        TipEvents::starting_at(first_sequence)
+    }
+}
+
+impl TipEvents {
+    fn starting_at(first_sequence: u64) -> Self {
+        let (sender, _) = broadcast::channel(RECENT_EVENTS_CAPACITY);
+        TipEvents {
+            inner: Arc::new(Mutex::new(Inner {
+                next_sequence: first_sequence,
+                recent: VecDeque::with_capacity(RECENT_EVENTS_CAPACITY),
+            })),
+            sender,
+        }
+    }
+
+    pub fn tip_changed(&self, tip: &Ref) {
+        self.publish(TipEventKind::TipChanged {
+            hash: tip.hash().into(),
+            chain_length: tip.chain_length().into(),
+            date: tip.block_date().into(),
+        })
+    }
+
+    /// the `depth` blocks of the main branch after `to` are rolled back,
+    /// `from` being the tip before the rollback
+    pub fn rollback(&self, from: &Ref, to: HeaderHash, depth: u32) {
+        self.publish(TipEventKind::Rollback {
+            from: from.hash().into(),
+            to: to.into(),
+            depth,
+        })
+    }
+
+    fn publish(&self, kind: TipEventKind) {
+        // the event is sent with the lock held so the events are received
+        // in the order of their sequence numbers
+        let mut inner = self.inner.lock().unwrap();
+        let event = TipEvent {
+            sequence: inner.next_sequence,
+            kind,
+        };
+        inner.next_sequence += 1;
+        if inner.recent.len() == RECENT_EVENTS_CAPACITY {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(event.clone());
+        // an error only means that no client is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// subscribe to the events, returns the kept events following the
+    /// one with the sequence number `last_seen`, if given, and the receiver
+    /// of the next events.
+    ///
+    /// If the events following `last_seen` are not kept, or `last_seen` was
+    /// not sent yet, a `Resync` event is returned instead of the missed
+    /// events. It has the sequence number of the last event sent, so a
+    /// client reconnecting with it gets the following events.
+    pub fn subscribe(
+        &self,
+        last_seen: Option<u64>,
+    ) -> (Vec<TipEvent>, broadcast::Receiver<TipEvent>) {
+        let inner = self.inner.lock().unwrap();
+        let first_kept = inner
+            .recent
+            .front()
+            .map_or(inner.next_sequence, |event| event.sequence);
+        let missed = match last_seen {
+            None => Vec::new(),
+            Some(last_seen)
+                if last_seen < inner.next_sequence && last_seen >= first_kept.saturating_sub(1) =>
+            {
+                inner
+                    .recent
+                    .iter()
+                    .filter(|event| event.sequence > last_seen)
+                    .cloned()
+                    .collect()
+            }
+            Some(_) => vec![TipEvent {
+                sequence: inner.next_sequence.saturating_sub(1),
+                kind: TipEventKind::Resync,
+            }],
+        };
+        (missed, self.sender.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST_SEQUENCE: u64 = 1_000;
+
+    fn publish(events: &TipEvents, count: u32) {
+        for depth in 0..count {
+            events.publish(TipEventKind::Rollback {
+                from: HeaderHash::zero_hash().into(),
+                to: HeaderHash::zero_hash().into(),
+                depth,
+            });
+        }
+    }
+
+    fn sequences(events: &[TipEvent]) -> Vec<u64> {
+        events.iter().map(|event| event.sequence).collect()
+    }
+
+    fn is_resync(events: &[TipEvent], sequence: u64) -> bool {
+        matches!(events, [TipEvent { sequence: s, kind: TipEventKind::Resync }] if *s == sequence)
+    }
+
+    #[test]
+    fn resume_after_kept_event() {
+        let events = TipEvents::starting_at(FIRST_SEQUENCE);
+        publish(&events, 5);
+
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE + 2));
+
+        assert_eq!(
+            sequences(&missed),
+            vec![FIRST_SEQUENCE + 3, FIRST_SEQUENCE + 4]
+        );
+    }
+
+    #[test]
+    fn resume_after_last_event() {
+        let events = TipEvents::starting_at(FIRST_SEQUENCE);
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE - 1));
+        assert!(missed.is_empty());
+
+        publish(&events, 5);
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE + 4));
+        assert!(missed.is_empty());
+    }
+
+    #[test]
+    fn resume_after_event_not_sent_yet() {
+        let events = TipEvents::starting_at(FIRST_SEQUENCE);
+        publish(&events, 5);
+
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE + 10));
+
+        assert!(is_resync(&missed, FIRST_SEQUENCE + 4));
+    }
+
+    #[test]
+    fn resume_after_dropped_event() {
+        let events = TipEvents::starting_at(FIRST_SEQUENCE);
+        publish(&events, RECENT_EVENTS_CAPACITY as u32 + 10);
+        let last = FIRST_SEQUENCE + RECENT_EVENTS_CAPACITY as u64 + 9;
+
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE + 8));
+        assert!(is_resync(&missed, last));
+
+        // the event following the last one dropped is still kept
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE + 9));
+        assert_eq!(missed.len(), RECENT_EVENTS_CAPACITY);
+        assert_eq!(missed.last().unwrap().sequence, last);
+    }
+
+    #[test]
+    fn resume_after_event_of_previous_run() {
+        let previous_run = TipEvents::starting_at(FIRST_SEQUENCE);
+        publish(&previous_run, 5);
+        let events = TipEvents::starting_at(FIRST_SEQUENCE + 100);
+        publish(&events, 2);
+
+        let (missed, _) = events.subscribe(Some(FIRST_SEQUENCE + 4));
+
+        assert!(is_resync(&missed, FIRST_SEQUENCE + 101));
+    }
+
+    #[test]
+    fn subscriber_receives_next_events() {
+        let events = TipEvents::starting_at(FIRST_SEQUENCE);
+        publish(&events, 2);
+
+        let (missed, mut receiver) = events.subscribe(None);
+        publish(&events, 1);
+
+        assert!(missed.is_empty());
+        assert_eq!(receiver.try_recv().unwrap().sequence, FIRST_SEQUENCE + 2);
+    }
+}
@@ -129,6 +129,35 @@ async fn handle_get_headers_range(
     }
 }
 
+/// The error returned for a block that is not in the storage.
+///
+/// If the storage is pruned, the block may have been removed from it:
+/// the error then has the `OutOfRange` code so the peers can tell it apart
+/// from an unknown block and look for the block elsewhere.
+fn block_not_found(storage: &Storage, id: HeaderHash) -> Error {
+    match storage.pruned_start() {
+        Ok(Some(start)) => Error::out_of_range(format!(
+            "block {} is not known to this node, the blocks before {} have been pruned",
+            id, start
+        )),
+        Ok(None) => Error::not_found(format!("block {} is not known to this node", id)),
+        Err(e) => e.into(),
+    }
+}
+
+/// The error returned when none of the checkpoints sent by a peer
+/// is known, see `block_not_found`.
+fn checkpoints_not_found(storage: &Storage) -> Error {
+    match storage.pruned_start() {
+        Ok(Some(start)) => Error::out_of_range(format!(
+            "`from` not found, the blocks before {} have been pruned",
+            start
+        )),
+        Ok(None) => Error::not_found("`from` not found"),
+        Err(e) => e.into(),
+    }
+}
+
 async fn handle_get_blocks(
     storage: Storage,
     ids: Vec<HeaderHash>,
@@ -138,10 +167,7 @@ async fn handle_get_blocks(
     for id in ids {
         let res = match storage.get(id) {
             Ok(Some(block)) => Ok(block),
-            Ok(None) => Err(Error::not_found(format!(
-                "block {} is not known to this node",
-                id
-            ))),
+            Ok(None) => Err(block_not_found(&storage, id)),
             Err(e) => Err(e.into()),
         };
         let is_err = res.is_err();
@@ -162,10 +188,7 @@ async fn handle_get_headers(
     for id in ids {
         let res = match storage.get(id) {
             Ok(Some(block)) => Ok(block.header()),
-            Ok(None) => Err(Error::not_found(format!(
-                "block {} is not known to this node",
-                id
-            ))),
+            Ok(None) => Err(block_not_found(&storage, id)),
             Err(e) => Err(e.into()),
         };
         let is_err = res.is_err();
@@ -183,15 +206,13 @@ async fn handle_pull_blocks(
     to: HeaderHash,
     handle: ReplyStreamHandle<Block>,
 ) -> Result<(), ReplySendError> {
-    use crate::intercom::Error as IntercomError;
-
     let res = storage
         .find_closest_ancestor(from, to)
         .map_err(Into::into)
-        .and_then(move |maybe_ancestor| {
+        .and_then(|maybe_ancestor| {
             maybe_ancestor
                 .map(|ancestor| (to, ancestor.distance))
-                .ok_or_else(|| IntercomError::not_found("`from` not found"))
+                .ok_or_else(|| checkpoints_not_found(&storage))
         });
     match res {
         Ok((to, depth)) => storage.send_branch(to, Some(depth), handle).await,
@@ -212,14 +233,17 @@ async fn handle_pull_blocks_to_tip(
     let tip_hash = tip.hash();
     let res = storage
         .find_closest_ancestor(checkpoints, tip_hash)
-        .map(move |maybe_ancestor| {
-            let depth = maybe_ancestor.map(|ancestor| ancestor.distance);
-            (tip_hash, depth)
+        .map_err(Into::into)
+        .and_then(|maybe_ancestor| match maybe_ancestor {
+            Some(ancestor) => Ok((tip_hash, Some(ancestor.distance))),
+            // a pruned storage cannot send the chain from the block0
+            None if storage.is_pruned()? => Err(checkpoints_not_found(&storage)),
+            None => Ok((tip_hash, None)),
         });
     match res {
         Ok((to, depth)) => storage.send_branch(to, depth, handle).await,
         Err(e) => {
-            handle.reply_error(e.into());
+            handle.reply_error(e);
             Ok(())
         }
     }
@@ -14,7 +14,8 @@ use self::scalars::{
     PoolId, PublicKey, Slot, Value, VoteOptionRange, VotePlanId, Weight,
 };
 use super::indexing::{
-    BlockProducer, EpochData, ExplorerAddress, ExplorerBlock, ExplorerTransaction, StakePoolData,
+    BlockProducer, EpochData, ExplorerAddress, ExplorerBlock, ExplorerTransaction,
+    ExplorerUpdateProposal, StakePoolData,
 };
 use super::persistent_sequence::PersistentSequence;
 use crate::blockcfg::{self, FragmentId, HeaderHash};
@@ -1250,6 +1251,53 @@ impl VoteProposalStatus {
     }
 }
 
+pub struct UpdateProposal {
+    proposal: Arc<ExplorerUpdateProposal>,
+}
+
+#[juniper::graphql_object(
+    Context = Context
+)]
+impl UpdateProposal {
+    /// the id of the fragment of the proposal
+    pub fn id(&self) -> String {
+        format!("{}", self.proposal.id)
+    }
+
+    pub fn proposer(&self) -> BftLeader {
+        BftLeader {
+            id: self.proposal.proposer_id.clone(),
+        }
+    }
+
+    /// the date of the block including the proposal
+    pub fn date(&self) -> BlockDate {
+        self.proposal.date.into()
+    }
+
+    /// the changes of the blockchain parameters, as a JSON object
+    pub fn changes(&self) -> FieldResult<String> {
+        let changes = self
+            .proposal
+            .changes()
+            .map_err(|err| ErrorKind::InternalError(err.to_string()))?;
+        serde_json::to_string(&changes)
+            .map_err(|err| ErrorKind::InternalError(err.to_string()).into())
+    }
+
+    pub fn votes(&self) -> Vec<BftLeader> {
+        self.proposal
+            .votes
+            .iter()
+            .map(|id| BftLeader { id: id.clone() })
+            .collect()
+    }
+
+    pub fn vote_count(&self) -> i32 {
+        self.proposal.votes.len() as i32
+    }
+}
+
 pub struct Query;
 
 #[juniper::graphql_object(
@@ -1409,6 +1457,16 @@ impl Query {
         VotePlanStatus::vote_plan_from_id(VotePlanId(id), context).await
     }
 
+    /// the update proposals of the BFT leaders included in the main branch
+    pub async fn update_proposals(&self, context: &Context) -> Vec<UpdateProposal> {
+        let mut proposals = context.db.get_update_proposals().await;
+        proposals.sort_unstable_by_key(|proposal| proposal.date);
+        proposals
+            .into_iter()
+            .map(|proposal| UpdateProposal { proposal })
+            .collect()
+    }
+
     pub async fn all_vote_plans(
         &self,
         first: Option<i32>,
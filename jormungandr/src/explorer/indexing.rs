@@ -2,7 +2,9 @@ use super::persistent_sequence::PersistentSequence;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 
-use crate::blockcfg::{Block, BlockDate, ChainLength, Epoch, Fragment, FragmentId, HeaderHash};
+use crate::blockcfg::{
+    Block, BlockDate, ChainLength, ConfigParams, Epoch, Fragment, FragmentId, HeaderHash,
+};
 use cardano_legacy_address::Addr as OldAddress;
 use chain_addr::{Address, Discrimination};
 use chain_core::property::Block as _;
@@ -17,7 +19,11 @@ use chain_impl_mockchain::value::Value;
 use chain_impl_mockchain::vote::{
     Choice, EncryptedVote, Options, PayloadType, ProofOfCorrectVote, Weight,
 };
-use std::{convert::TryInto, sync::Arc};
+use jormungandr_lib::interfaces::{ProposalChanges, ProposalChangesError};
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
 
 pub type Hamt<K, V> = imhamt::Hamt<DefaultHasher, K, Arc<V>>;
 
@@ -33,6 +39,8 @@ pub type StakePool = Hamt<PoolId, StakePoolData>;
 
 pub type VotePlans = Hamt<VotePlanId, ExplorerVotePlan>;
 
+pub type UpdateProposals = Hamt<FragmentId, ExplorerUpdateProposal>;
+
 #[derive(Clone)]
 pub struct StakePoolData {
     pub registration: PoolRegistration,
@@ -136,6 +144,23 @@ pub enum ExplorerVoteTally {
     },
 }
 
+/// an update proposal of a BFT leader, the id of the proposal being the id
+/// of its fragment
+#[derive(Clone)]
+pub struct ExplorerUpdateProposal {
+    pub id: FragmentId,
+    pub proposer_id: BftLeaderId,
+    pub date: BlockDate,
+    pub changes: ConfigParams,
+    pub votes: Vec<BftLeaderId>,
+}
+
+impl ExplorerUpdateProposal {
+    pub fn changes(&self) -> Result<ProposalChanges, ProposalChangesError> {
+        ProposalChanges::try_from(self.changes.clone())
+    }
+}
+
 pub struct ExplorerBlockBuildingContext<'a> {
     pub discrimination: Discrimination,
     pub prev_transactions: &'a Transactions,
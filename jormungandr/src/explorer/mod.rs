@@ -9,8 +9,8 @@ pub use self::graphql::create_schema;
 use self::graphql::Context;
 use self::indexing::{
     Addresses, Blocks, ChainLengths, EpochData, Epochs, ExplorerAddress, ExplorerBlock,
-    ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, StakePool, StakePoolBlocks,
-    StakePoolData, Transactions, VotePlans,
+    ExplorerUpdateProposal, ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, StakePool,
+    StakePoolBlocks, StakePoolData, Transactions, UpdateProposals, VotePlans,
 };
 use self::persistent_sequence::PersistentSequence;
 use tracing::{span, Level};
@@ -26,7 +26,7 @@ use crate::intercom::ExplorerMsg;
 use crate::utils::async_msg::MessageQueue;
 use crate::utils::task::TokioServiceInfo;
 use chain_addr::Discrimination;
-use chain_core::property::Block as _;
+use chain_core::property::{Block as _, Fragment as _};
 use chain_impl_mockchain::certificate::{Certificate, PoolId, VotePlanId};
 use chain_impl_mockchain::fee::LinearFee;
 use futures::prelude::*;
@@ -90,6 +90,7 @@ pub(self) struct State {
     stake_pool_data: StakePool,
     stake_pool_blocks: StakePoolBlocks,
     vote_plans: VotePlans,
+    update_proposals: UpdateProposals,
 }
 
 #[derive(Clone)]
@@ -211,6 +212,7 @@ impl ExplorerDB {
         let (stake_pool_data, stake_pool_blocks) =
             apply_block_to_stake_pools(StakePool::new(), StakePoolBlocks::new(), &block);
         let vote_plans = apply_block_to_vote_plans(VotePlans::new(), &blockchain_tip, &block);
+        let update_proposals = apply_block_to_update_proposals(UpdateProposals::new(), &block0);
 
         let initial_state = State {
             transactions,
@@ -221,6 +223,7 @@ impl ExplorerDB {
             stake_pool_data,
             stake_pool_blocks,
             vote_plans,
+            update_proposals,
         };
 
         let block0_id = block0.id();
@@ -303,6 +306,7 @@ impl ExplorerDB {
             stake_pool_data,
             stake_pool_blocks,
             vote_plans,
+            update_proposals,
         } = previous_state.state().clone();
 
         let explorer_block = ExplorerBlock::resolve_from(
@@ -334,6 +338,7 @@ impl ExplorerDB {
                         &self.blockchain_tip,
                         &explorer_block,
                     ),
+                    update_proposals: apply_block_to_update_proposals(update_proposals, &block),
                 },
             )
             .await;
@@ -468,6 +473,13 @@ impl ExplorerDB {
         None
     }
 
+    /// the update proposals included in the main branch, with the votes
+    /// they received
+    pub async fn get_update_proposals(&self) -> Vec<Arc<ExplorerUpdateProposal>> {
+        let (_, state_ref) = self.get_main_tip().await;
+        state_ref.state().get_update_proposals()
+    }
+
     pub(self) async fn get_main_tip(&self) -> (HeaderHash, multiverse::Ref) {
         let hash = self.longest_chain_tip.get_block_id().await;
         (hash, self.multiverse.get_ref(&hash).await.unwrap())
@@ -777,6 +789,50 @@ fn apply_block_to_vote_plans(
     vote_plans
 }
 
+fn apply_block_to_update_proposals(
+    mut update_proposals: UpdateProposals,
+    block: &Block,
+) -> UpdateProposals {
+    let date = block.date();
+    for fragment in block.contents.iter() {
+        update_proposals = match fragment {
+            Fragment::UpdateProposal(signed) => {
+                let proposal = &signed.proposal;
+                let id = fragment.id();
+                update_proposals
+                    .insert(
+                        id,
+                        Arc::new(ExplorerUpdateProposal {
+                            id,
+                            proposer_id: proposal.proposer_id.clone(),
+                            date,
+                            changes: proposal.proposal.changes.clone(),
+                            votes: Vec::new(),
+                        }),
+                    )
+                    .unwrap()
+            }
+            Fragment::UpdateVote(signed) => {
+                let vote = &signed.vote;
+                // a vote for a proposal that is not indexed cannot be
+                // accepted by the ledger, it is ignored
+                update_proposals
+                    .update(&vote.proposal_id, |proposal| {
+                        let mut proposal = proposal.as_ref().clone();
+                        if !proposal.votes.contains(&vote.voter_id) {
+                            proposal.votes.push(vote.voter_id.clone());
+                        }
+                        Ok::<_, Infallible>(Some(Arc::new(proposal)))
+                    })
+                    .unwrap_or(update_proposals)
+            }
+            _ => update_proposals,
+        }
+    }
+
+    update_proposals
+}
+
 impl BlockchainConfig {
     fn from_config_params(params: &ConfigParams) -> BlockchainConfig {
         let mut discrimination: Option<Discrimination> = None;
@@ -831,6 +887,13 @@ impl State {
             .collect()
     }
 
+    pub fn get_update_proposals(&self) -> Vec<Arc<ExplorerUpdateProposal>> {
+        self.update_proposals
+            .iter()
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
     pub fn get_stake_pools(&self) -> Vec<(PoolId, Arc<StakePoolData>)> {
         self.stake_pool_data
             .iter()
@@ -48,6 +48,13 @@ impl Logs {
             .count()
     }
 
+    pub fn remove_all(&mut self, fragment_ids: impl IntoIterator<Item = FragmentId>) {
+        for fragment_id in fragment_ids {
+            let fragment_id: Hash = fragment_id.into();
+            self.entries.pop(&fragment_id);
+        }
+    }
+
     pub fn modify(&mut self, fragment_id: FragmentId, status: FragmentStatus) {
         let fragment_id: Hash = fragment_id.into();
         match self.entries.get_mut(&fragment_id) {
@@ -80,6 +87,11 @@ impl Logs {
         }
     }
 
+    pub fn get(&self, fragment_id: FragmentId) -> Option<&FragmentLog> {
+        let fragment_id: Hash = fragment_id.into();
+        self.entries.peek(&fragment_id)
+    }
+
     pub fn logs_by_ids(
         &self,
         fragment_ids: impl IntoIterator<Item = FragmentId>,
@@ -97,7 +109,7 @@ impl Logs {
         result
     }
 
-    pub fn logs(&self) -> impl Iterator<Item = &FragmentLog> {
+    pub fn logs(&self) -> impl DoubleEndedIterator<Item = &FragmentLog> {
         self.entries.iter().map(|(_, v)| v)
     }
 }
@@ -1,12 +1,16 @@
 mod entry;
 mod logs;
+mod persistence;
 mod pool;
 mod process;
 pub mod selection;
+mod validation;
 
 pub use self::entry::PoolEntry;
 pub use self::logs::Logs;
-pub use self::pool::Pools;
-pub use self::process::Process;
+pub use self::persistence::{MempoolFile, PersistedMempool};
+pub use self::pool::{is_fragment_valid, Pools, Quotas};
+pub use self::process::{remove_expired_fragments, save_mempool, Process};
+pub use self::validation::TipLedger;
 
 pub use crate::blockcfg::{Fragment, FragmentId};
@@ -0,0 +1,206 @@
+//! Persistence of the mempool across restarts of the node.
+//!
+//! The fragments of the pools and the recent fragment logs are written to a
+//! file of the storage directory, periodically and when the node shuts down.
+//! The file starts with a versioned header holding the hash of the block0 of
+//! the chain and a checksum of its body, so a file of another chain or a
+//! corrupted file is not loaded.
+//!
+//! The fragments read back at startup are not trusted: they are applied
+//! again to the ledger of the tip before they are put back in the pools, and
+//! the ones that no longer apply are marked as rejected in the logs.
+
+use crate::{
+    blockcfg::HeaderHash,
+    blockchain::Ref,
+    fragment::{validation::TipLedger, Fragment, Logs},
+};
+use bincode::Options;
+use chain_core::property::{Deserialize, Fragment as _, Serialize};
+use jormungandr_lib::interfaces::{FragmentLog, FragmentStatus};
+use thiserror::Error;
+
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const MEMPOOL_FILE: &str = "mempool";
+const MEMPOOL_MAGIC: &[u8; 8] = b"JORMPOOL";
+const MEMPOOL_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while accessing the mempool file")]
+    Io(#[from] io::Error),
+    #[error("the file is not a mempool file")]
+    InvalidMagic,
+    #[error("unsupported mempool file version {0}, expected {}", MEMPOOL_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("the mempool file is for the chain of block0 {got}, expected {expected}")]
+    Block0Mismatch {
+        expected: HeaderHash,
+        got: HeaderHash,
+    },
+    #[error("the mempool file is corrupted, its checksum does not match")]
+    ChecksumMismatch,
+    #[error("cannot serialize the mempool")]
+    Serialize(#[source] io::Error),
+    #[error("cannot deserialize the mempool")]
+    Deserialize(#[source] io::Error),
+    #[error("cannot encode the fragment logs")]
+    Logs(#[from] bincode::Error),
+}
+
+/// the contents of the mempool, as written to the file
+pub struct PersistedMempool {
+    /// the fragment logs, the oldest first
+    pub logs: Vec<FragmentLog>,
+    /// the fragments of the pools, the oldest first
+    pub fragments: Vec<Fragment>,
+}
+
+/// access to the mempool file of a storage directory
+#[derive(Clone)]
+pub struct MempoolFile {
+    path: PathBuf,
+    block0: HeaderHash,
+}
+
+impl MempoolFile {
+    pub fn new<P: AsRef<Path>>(storage_dir: P, block0: HeaderHash) -> Self {
+        MempoolFile {
+            path: storage_dir.as_ref().join(MEMPOOL_FILE),
+            block0,
+        }
+    }
+
+    /// write the mempool to the file
+    ///
+    /// The mempool is first written to a temporary file that is then
+    /// renamed, so an interrupted write never leaves a partial file.
+    pub fn write(&self, mempool: &PersistedMempool) -> Result<(), Error> {
+        let mut body = Vec::new();
+        write_bytes(&mut body, &bincode::options().serialize(&mempool.logs)?);
+        body.extend_from_slice(&(mempool.fragments.len() as u64).to_be_bytes());
+        for fragment in &mempool.fragments {
+            write_bytes(
+                &mut body,
+                &fragment.serialize_as_vec().map_err(Error::Serialize)?,
+            );
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+            file.write_all(MEMPOOL_MAGIC)?;
+            file.write_all(&MEMPOOL_VERSION.to_be_bytes())?;
+            file.write_all(self.block0.as_bytes())?;
+            file.write_all(HeaderHash::hash_bytes(&body).as_bytes())?;
+            file.write_all(&body)?;
+            file.flush()?;
+            file.get_ref().sync_all()?;
+        }
+        fs::rename(tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// read the mempool from the file, `None` if there is no file
+    pub fn read(&self) -> Result<Option<PersistedMempool>, Error> {
+        let mut reader = match fs::File::open(&self.path) {
+            Ok(file) => io::BufReader::new(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MEMPOOL_MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version != MEMPOOL_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let got = read_hash(&mut reader)?;
+        if got != self.block0 {
+            return Err(Error::Block0Mismatch {
+                expected: self.block0,
+                got,
+            });
+        }
+        let checksum = read_hash(&mut reader)?;
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body)?;
+        if HeaderHash::hash_bytes(&body) != checksum {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        let mut body = body.as_slice();
+        let logs = bincode::options().deserialize(read_bytes(&mut body)?)?;
+        let mut count = [0; 8];
+        body.read_exact(&mut count)?;
+        let fragments = (0..u64::from_be_bytes(count))
+            .map(|_| Fragment::deserialize(read_bytes(&mut body)?).map_err(Error::Deserialize))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(PersistedMempool { logs, fragments }))
+    }
+}
+
+/// put the logs of the persisted mempool back in `logs` and return the
+/// fragments that still apply to the ledger of the tip, the others are
+/// marked as rejected in the logs.
+pub fn revalidate(tip: &Ref, mempool: PersistedMempool, logs: &mut Logs) -> Vec<Fragment> {
+    logs.insert_all(mempool.logs);
+
+    let mut tip_ledger = TipLedger::new(tip);
+    let mut seen = HashSet::new();
+    let mut fragments = Vec::new();
+
+    for fragment in mempool.fragments {
+        let id = fragment.id();
+        if !seen.insert(id) {
+            continue;
+        }
+        match tip_ledger.apply(&fragment) {
+            Ok(()) => fragments.push(fragment),
+            Err(reason) => {
+                tracing::debug!(%id, %reason, "persisted fragment is rejected");
+                logs.modify(id, FragmentStatus::Rejected { reason });
+            }
+        }
+    }
+
+    fragments
+}
+
+fn write_bytes(body: &mut Vec<u8>, bytes: &[u8]) {
+    body.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+    body.extend_from_slice(bytes);
+}
+
+fn read_bytes<'a>(body: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let mut len = [0; 8];
+    body.read_exact(&mut len)?;
+    let len: usize = u64::from_be_bytes(len)
+        .try_into()
+        .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    if body.len() < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    let (bytes, rest) = body.split_at(len);
+    *body = rest;
+    Ok(bytes)
+}
+
+fn read_hash<R: Read>(reader: &mut R) -> Result<HeaderHash, Error> {
+    let mut bytes = [0; 32];
+    reader.read_exact(&mut bytes)?;
+    HeaderHash::deserialize(&bytes[..]).map_err(Error::Deserialize)
+}
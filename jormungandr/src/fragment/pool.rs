@@ -1,45 +1,90 @@
 use crate::{
-    blockcfg::{BlockDate, Ledger, LedgerParameters},
+    blockcfg::{BlockDate, HeaderHash, Ledger, LedgerParameters},
+    blockchain::Ref,
     fragment::{
-        selection::{FragmentSelectionAlgorithm, FragmentSelectionAlgorithmParams, OldestFirst},
-        Fragment, FragmentId, Logs,
+        selection::{
+            FeeDensity, FragmentSelectionAlgorithm, FragmentSelectionAlgorithmParams,
+            HighestFeeDensityFirst, OldestFirst,
+        },
+        validation::TipLedger,
+        Fragment, FragmentId, Logs, PersistedMempool,
     },
     intercom::{NetworkMsg, PropagateMsg},
+    network::p2p::{Address, PeerEvent},
     utils::async_msg::MessageBox,
 };
 use chain_core::property::Fragment as _;
 use chain_impl_mockchain::{fragment::Contents, transaction::Transaction};
 use futures::channel::mpsc::SendError;
 use futures::sink::SinkExt;
-use jormungandr_lib::interfaces::{FragmentLog, FragmentOrigin, FragmentStatus};
+use jormungandr_lib::interfaces::{FragmentLog, FragmentOrigin, FragmentStatus, PendingFragment};
 use thiserror::Error;
 
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// limits on the number of fragments of a pool, a fragment over one of them
+/// is rejected
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quotas {
+    /// the maximum number of fragments spending from the same account
+    pub account_max_entries: Option<usize>,
+    /// the maximum number of fragments received from the REST API
+    pub rest_max_entries: Option<usize>,
+    /// the maximum number of fragments received from the network
+    pub network_max_entries: Option<usize>,
+}
+
+/// the reason logged for a fragment evicted from a full pool
+const EVICTED_REASON: &str =
+    "evicted from the full mempool by a fragment paying at least as much fee per byte";
+
 pub struct Pools {
     logs: Logs,
     pools: Vec<internal::Pool>,
     network_msg_box: MessageBox<NetworkMsg>,
+    pending_ledger: Option<PendingLedger>,
+    accept_update_proposals: bool,
+}
+
+/// the ledger of a tip with the fragments of the pools applied to it, the
+/// oldest first. The new fragments are applied to it as they are added to
+/// the pools, it is rebuilt when the tip changes or when fragments are
+/// removed from the pools.
+struct PendingLedger {
+    tip: HeaderHash,
+    ledger: TipLedger,
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("cannot propagate a fragment to the network")]
     CannotPropagate(#[source] SendError),
+    #[error("cannot report a peer to the network")]
+    CannotReportPeer(#[source] SendError),
 }
 
 impl Pools {
     pub fn new(
         max_entries: usize,
+        quotas: Quotas,
         n_pools: usize,
         logs: Logs,
         network_msg_box: MessageBox<NetworkMsg>,
+        accept_update_proposals: bool,
     ) -> Self {
         let pools = (0..=n_pools)
-            .map(|_| internal::Pool::new(max_entries))
+            .map(|_| internal::Pool::new(max_entries, quotas))
             .collect();
         Pools {
             logs,
             pools,
             network_msg_box,
+            pending_ledger: None,
+            accept_update_proposals,
         }
     }
 
@@ -48,13 +93,23 @@ impl Pools {
     }
 
     /// Returns number of registered fragments
+    ///
+    /// If `tip` is given, the new fragments are checked against its ledger,
+    /// with the pending fragments of the pools applied first so a fragment
+    /// can depend on a pending one, and the ones that cannot be applied are
+    /// rejected in the logs, they are
+    /// neither added to the pools nor propagated. The peer they were received
+    /// from, if any, is reported to the network task for each of them.
     pub async fn insert_and_propagate_all(
         &mut self,
         origin: FragmentOrigin,
+        peer: Option<Address>,
         mut fragments: Vec<Fragment>,
+        tip: Option<Arc<Ref>>,
     ) -> Result<usize, Error> {
         tracing::debug!(origin = ?origin, "received {} fragments", fragments.len());
-        fragments.retain(is_fragment_valid);
+        let accept_update_proposals = self.accept_update_proposals;
+        fragments.retain(|fragment| is_fragment_valid(fragment, accept_update_proposals));
         if fragments.is_empty() {
             tracing::debug!("none of the received fragments are valid");
             return Ok(0);
@@ -67,28 +122,92 @@ impl Pools {
             .zip(fragments_exist_in_logs)
             .filter(|(_, exists_in_logs)| !exists_in_logs)
             .map(|(fragment, _)| fragment);
+        let new_fragments = match tip {
+            Some(tip) => {
+                let (valid, rejected) = self.reject_invalid(origin, new_fragments, &tip);
+                if let Some(node_address) = peer {
+                    for _ in 0..rejected {
+                        let report_msg = NetworkMsg::ReportPeer {
+                            node_address: node_address.clone(),
+                            event: PeerEvent::RejectedFragment,
+                        };
+                        network_msg_box
+                            .send(report_msg)
+                            .await
+                            .map_err(Error::CannotReportPeer)?;
+                    }
+                }
+                valid
+            }
+            None => new_fragments.collect(),
+        };
 
         let mut max_added = 0;
+        let mut added_ids = HashSet::new();
+        let mut rejected = HashMap::new();
+        let mut evicted = Vec::new();
 
         for (i, pool) in self.pools.iter_mut().enumerate() {
-            let new_fragments = pool.insert_all(new_fragments.clone());
-            let count = new_fragments.len();
+            let mut added = Vec::new();
+            for fragment in &new_fragments {
+                match pool.insert(fragment.clone(), origin) {
+                    Ok((fragment, evicted_id)) => {
+                        added.push(fragment);
+                        evicted.extend(evicted_id);
+                    }
+                    Err(internal::Rejection::AlreadyInPool) => {}
+                    Err(rejection) => {
+                        rejected
+                            .entry(fragment.id())
+                            .or_insert_with(|| rejection.to_string());
+                    }
+                }
+            }
+            let count = added.len();
             tracing::debug!(
                 "{} of the received fragments were added to the pool number {}",
                 count,
                 i
             );
-            let fragment_logs = new_fragments
+            let fragment_logs = added
                 .iter()
                 .map(move |fragment| FragmentLog::new(fragment.id(), origin))
                 .collect::<Vec<_>>();
             self.logs.insert_all(fragment_logs);
+            added_ids.extend(added.iter().map(Fragment::id));
             if count > max_added {
                 max_added = count;
             }
         }
 
-        for fragment in new_fragments.into_iter() {
+        // the fragments that none of the pools accepted, because they are
+        // over the quotas or the pools are full, are rejected and not
+        // propagated, so a client flooding the node is not relayed either
+        rejected.retain(|id, _| !added_ids.contains(id));
+        for (id, reason) in &rejected {
+            tracing::debug!(%id, %reason, "fragment is not accepted in the mempool");
+            self.logs.insert(FragmentLog::new(*id, origin));
+            self.logs.modify(
+                *id,
+                FragmentStatus::Rejected {
+                    reason: reason.clone(),
+                },
+            );
+        }
+        // a fragment evicted by a later one of the same batch is not
+        // propagated either
+        let evicted = self.reject_evicted(evicted);
+        if !rejected.is_empty() || !evicted.is_empty() {
+            // the pending ledger has fragments applied that are not in the
+            // pools
+            self.pending_ledger = None;
+        }
+        let new_fragments = new_fragments.into_iter().filter(|fragment| {
+            let id = fragment.id();
+            !rejected.contains_key(&id) && !evicted.contains(&id)
+        });
+
+        for fragment in new_fragments {
             let fragment_msg = NetworkMsg::Propagate(PropagateMsg::Fragment(fragment));
             network_msg_box
                 .send(fragment_msg)
@@ -99,13 +218,222 @@ impl Pools {
         Ok(max_added)
     }
 
+    /// reject in the logs the evicted fragments that are no longer in any
+    /// of the pools, and return their ids
+    fn reject_evicted(&mut self, evicted: Vec<FragmentId>) -> HashSet<FragmentId> {
+        let evicted = evicted
+            .into_iter()
+            .filter(|id| self.get(id).is_none())
+            .collect::<HashSet<_>>();
+        for id in &evicted {
+            tracing::debug!(%id, "fragment is evicted from the mempool");
+            self.logs.modify(
+                *id,
+                FragmentStatus::Rejected {
+                    reason: EVICTED_REASON.to_owned(),
+                },
+            );
+        }
+        evicted
+    }
+
+    /// returns the fragments that apply to the ledger of the tip and the
+    /// number of the others, which are logged as rejected
+    fn reject_invalid(
+        &mut self,
+        origin: FragmentOrigin,
+        fragments: impl Iterator<Item = Fragment>,
+        tip: &Ref,
+    ) -> (Vec<Fragment>, usize) {
+        self.update_pending_ledger(tip);
+        let Pools {
+            logs,
+            pending_ledger,
+            ..
+        } = self;
+        let tip_ledger = &mut pending_ledger
+            .as_mut()
+            .expect("the pending ledger is up to date")
+            .ledger;
+        let mut rejected = 0;
+        let valid = fragments
+            .filter(|fragment| match tip_ledger.apply(fragment) {
+                Ok(()) => true,
+                Err(reason) => {
+                    let id = fragment.id();
+                    tracing::debug!(%id, %reason, "fragment does not apply to the ledger of the tip");
+                    logs.insert(FragmentLog::new(id, origin));
+                    logs.modify(id, FragmentStatus::Rejected { reason });
+                    rejected += 1;
+                    false
+                }
+            })
+            .collect();
+        (valid, rejected)
+    }
+
+    /// rebuild the pending ledger if it is missing or was built for
+    /// another tip
+    fn update_pending_ledger(&mut self, tip: &Ref) {
+        let tip_hash = tip.hash();
+        if let Some(pending_ledger) = &self.pending_ledger {
+            if pending_ledger.tip == tip_hash {
+                return;
+            }
+        }
+        tracing::debug!(tip = %tip_hash, "rebuilding the ledger of the pending fragments");
+        let mut ledger = TipLedger::new(tip);
+        for fragment in self.fragments() {
+            // a pending fragment that no longer applies, for example one
+            // just added to the tip block, is left to the block selection
+            let _ = ledger.apply(fragment);
+        }
+        self.pending_ledger = Some(PendingLedger {
+            tip: tip_hash,
+            ledger,
+        });
+    }
+
     pub fn remove_added_to_block(&mut self, fragment_ids: Vec<FragmentId>, status: FragmentStatus) {
         for pool in &mut self.pools {
             pool.remove_all(fragment_ids.iter());
         }
+        self.pending_ledger = None;
         self.logs.modify_all(fragment_ids, status);
     }
 
+    /// remove from the pools the fragments received more than `ttl` ago,
+    /// they are rejected in the logs.
+    ///
+    /// Returns the number of removed fragments
+    pub fn remove_expired(&mut self, ttl: Duration) -> usize {
+        let now = SystemTime::now();
+        let Pools {
+            logs,
+            pools,
+            pending_ledger,
+            ..
+        } = self;
+        let mut seen = HashSet::new();
+        let expired = pools
+            .iter()
+            .flat_map(|pool| pool.fragments())
+            .map(Fragment::id)
+            .filter(|id| seen.insert(*id))
+            .filter(|id| {
+                logs.get(*id)
+                    .and_then(|log| now.duration_since(*log.received_at().as_ref()).ok())
+                    .map_or(false, |age| age > ttl)
+            })
+            .collect::<Vec<_>>();
+        for pool in pools.iter_mut() {
+            pool.remove_all(expired.iter());
+        }
+        let count = expired.len();
+        if count > 0 {
+            *pending_ledger = None;
+        }
+        logs.modify_all(
+            expired,
+            FragmentStatus::Rejected {
+                reason: "expired".to_owned(),
+            },
+        );
+        count
+    }
+
+    /// put back in the pools the fragments of blocks that were rolled back
+    /// from the main branch, their logs are reset to pending.
+    ///
+    /// Returns number of registered fragments
+    pub async fn restore_rolled_back(
+        &mut self,
+        fragments: Vec<Fragment>,
+        tip: Option<Arc<Ref>>,
+    ) -> Result<usize, Error> {
+        self.logs.remove_all(fragments.iter().map(Fragment::id));
+        self.insert_and_propagate_all(FragmentOrigin::Network, None, fragments, tip)
+            .await
+    }
+
+    /// put back in the pools the fragments read from the mempool file,
+    /// they are not propagated again
+    pub fn restore_persisted(&mut self, fragments: Vec<Fragment>) {
+        for fragment in &fragments {
+            self.logs
+                .insert(FragmentLog::new(fragment.id(), FragmentOrigin::Network));
+        }
+        let logs = &self.logs;
+        let with_origins = fragments
+            .into_iter()
+            .map(|fragment| {
+                let origin = logs
+                    .get(fragment.id())
+                    .map_or(FragmentOrigin::Network, |log| *log.received_from());
+                (fragment, origin)
+            })
+            .collect::<Vec<_>>();
+        let mut evicted = Vec::new();
+        for pool in &mut self.pools {
+            evicted.extend(pool.insert_all(with_origins.iter().cloned()));
+        }
+        self.reject_evicted(evicted);
+        self.pending_ledger = None;
+    }
+
+    /// the fragments of the pools, each one once, the oldest first
+    pub fn fragments(&self) -> impl Iterator<Item = &Fragment> {
+        let mut seen = HashSet::new();
+        self.pools
+            .iter()
+            .flat_map(|pool| pool.fragments())
+            .filter(move |fragment| seen.insert(fragment.id()))
+    }
+
+    /// the decoded fragments of the pools, the oldest first
+    pub fn pending(&self) -> Vec<PendingFragment> {
+        self.fragments()
+            .filter_map(|fragment| {
+                self.logs
+                    .get(fragment.id())
+                    .map(|log| PendingFragment::new(fragment, log))
+            })
+            .collect()
+    }
+
+    /// the fragment with the given id, if it is in one of the pools
+    pub fn get(&self, fragment_id: &FragmentId) -> Option<&Fragment> {
+        self.pools.iter().find_map(|pool| pool.get(fragment_id))
+    }
+
+    /// the fee density a fragment of `size` bytes has to exceed to be
+    /// selected in a block of `block_content_max_size` bytes when the
+    /// fragments paying the highest fee per byte are selected first, `None`
+    /// if there is room for it next to all the waiting fragments
+    pub fn fee_density_threshold(
+        &self,
+        block_content_max_size: u32,
+        size: u32,
+    ) -> Option<FeeDensity> {
+        let mut fee_densities: Vec<FeeDensity> = self.fragments().map(FeeDensity::of).collect();
+        fee_densities.sort_unstable_by(|a, b| b.cmp(a));
+        let available = u64::from(block_content_max_size.saturating_sub(size));
+        let mut total_size = 0;
+        fee_densities.into_iter().find(|fee_density| {
+            total_size += fee_density.size();
+            total_size > available
+        })
+    }
+
+    /// the contents of the pools and the logs, to be written to the
+    /// mempool file
+    pub fn to_persisted(&self) -> PersistedMempool {
+        PersistedMempool {
+            logs: self.logs.logs().rev().cloned().collect(),
+            fragments: self.fragments().cloned().collect(),
+        }
+    }
+
     pub fn select(
         &mut self,
         pool_idx: usize,
@@ -114,6 +442,8 @@ impl Pools {
         ledger_params: LedgerParameters,
         selection_alg: FragmentSelectionAlgorithmParams,
     ) -> Contents {
+        // the fragments selected or rejected are removed from the pool
+        self.pending_ledger = None;
         let Pools { logs, pools, .. } = self;
         let pool = &mut pools[pool_idx];
         match selection_alg {
@@ -122,11 +452,18 @@ impl Pools {
                 selection_alg.select(&ledger, &ledger_params, block_date, logs, pool);
                 selection_alg.finalize()
             }
+            FragmentSelectionAlgorithmParams::HighestFeeDensityFirst => {
+                let mut selection_alg = HighestFeeDensityFirst::new();
+                selection_alg.select(&ledger, &ledger_params, block_date, logs, pool);
+                selection_alg.finalize()
+            }
         }
     }
 }
 
-fn is_fragment_valid(fragment: &Fragment) -> bool {
+/// whether the fragment is of a kind accepted in the pools, and its
+/// transaction, if any, is well-formed
+pub fn is_fragment_valid(fragment: &Fragment, accept_update_proposals: bool) -> bool {
     match fragment {
         // never valid in the pool, only acceptable in genesis
         Fragment::Initial(_) => false,
@@ -138,9 +475,11 @@ fn is_fragment_valid(fragment: &Fragment) -> bool {
         Fragment::PoolRegistration(ref tx) => is_transaction_valid(tx),
         Fragment::PoolRetirement(ref tx) => is_transaction_valid(tx),
         Fragment::PoolUpdate(ref tx) => is_transaction_valid(tx),
+        // update proposals and votes are not signed, anyone can submit one
+        // in the name of a BFT leader, they are only accepted if enabled
+        Fragment::UpdateProposal(_) => accept_update_proposals,
+        Fragment::UpdateVote(_) => accept_update_proposals,
         // vote stuff
-        Fragment::UpdateProposal(_) => false, // TODO: enable when ready
-        Fragment::UpdateVote(_) => false,     // TODO: enable when ready
         Fragment::VotePlan(ref tx) => is_transaction_valid(tx),
         Fragment::VoteCast(ref tx) => is_transaction_valid(tx),
         Fragment::VoteTally(ref tx) => is_transaction_valid(tx),
@@ -154,49 +493,516 @@ fn is_transaction_valid<E>(tx: &Transaction<E>) -> bool {
 
 pub(super) mod internal {
     use super::*;
+    use chain_impl_mockchain::transaction::{InputEnum, UnspecifiedAccountIdentifier};
+    use jormungandr_lib::interfaces::AccountIdentifier;
     use lru::LruCache;
+    use std::{
+        collections::{BTreeSet, HashMap},
+        fmt,
+    };
+
+    /// the fee density of a fragment and its order of arrival, the oldest
+    /// fragments being lower among the ones paying the same fee
+    type FeeKey = (FeeDensity, u64, FragmentId);
+
+    struct Entry {
+        fragment: Fragment,
+        key: FeeKey,
+        origin: FragmentOrigin,
+        accounts: Vec<UnspecifiedAccountIdentifier>,
+    }
+
+    /// the reasons a fragment is not added to a pool
+    pub enum Rejection {
+        AlreadyInPool,
+        /// the pool is full of fragments paying a higher fee
+        PoolFull,
+        OriginQuota {
+            origin: FragmentOrigin,
+            max: usize,
+        },
+        AccountQuota {
+            account: UnspecifiedAccountIdentifier,
+            max: usize,
+        },
+    }
+
+    impl fmt::Display for Rejection {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Rejection::AlreadyInPool => write!(f, "the fragment is already in the pool"),
+                Rejection::PoolFull => write!(
+                    f,
+                    "the mempool is full of fragments paying a higher fee per byte"
+                ),
+                Rejection::OriginQuota { origin, max } => write!(
+                    f,
+                    "the pool already holds the maximum of {} fragments received from {:?}",
+                    max, origin
+                ),
+                Rejection::AccountQuota { account, max } => write!(
+                    f,
+                    "the pool already holds the maximum of {} fragments spending from account {}",
+                    max,
+                    AccountIdentifier::from(account.clone())
+                ),
+            }
+        }
+    }
 
+    /// The fragments of a pool, indexed by their order of arrival and
+    /// by the fee they pay per byte.
+    ///
+    /// When the pool is full, the fragment paying the lowest fee per byte
+    /// is evicted to make room for a fragment paying more, and a fragment
+    /// paying less is not accepted. A fragment paying as much as the lowest
+    /// one replaces the oldest fragment paying that fee, so a pool full of
+    /// fragments paying the same fee, as on a network without fees, keeps
+    /// accepting new fragments.
+    ///
+    /// The number of fragments received from each origin, and spending from
+    /// each account, is limited by the quotas.
     pub struct Pool {
-        entries: LruCache<FragmentId, Fragment>,
+        max_entries: usize,
+        quotas: Quotas,
+        next_sequence: u64,
+        entries: LruCache<FragmentId, Entry>,
+        by_fee_density: BTreeSet<FeeKey>,
+        by_origin: HashMap<FragmentOrigin, usize>,
+        by_account: HashMap<UnspecifiedAccountIdentifier, usize>,
     }
 
     impl Pool {
-        pub fn new(max_entries: usize) -> Self {
+        pub fn new(max_entries: usize, quotas: Quotas) -> Self {
             Pool {
-                entries: LruCache::new(max_entries),
+                max_entries,
+                quotas,
+                next_sequence: 0,
+                entries: LruCache::unbounded(),
+                by_fee_density: BTreeSet::new(),
+                by_origin: HashMap::new(),
+                by_account: HashMap::new(),
             }
         }
 
-        /// Returns clone of fragment if it was registered
-        pub fn insert(&mut self, fragment: Fragment) -> Option<Fragment> {
+        /// Returns clone of fragment if it was registered, with the id of
+        /// the fragment evicted to make room for it, if any
+        pub fn insert(
+            &mut self,
+            fragment: Fragment,
+            origin: FragmentOrigin,
+        ) -> Result<(Fragment, Option<FragmentId>), Rejection> {
             let fragment_id = fragment.id();
             if self.entries.contains(&fragment_id) {
-                None
-            } else {
-                self.entries.put(fragment_id, fragment.clone());
-                Some(fragment)
+                return Err(Rejection::AlreadyInPool);
+            }
+
+            let accounts = input_accounts(&fragment);
+            self.check_quotas(origin, &accounts)?;
+
+            let fee_density = FeeDensity::of(&fragment);
+            let mut evicted = None;
+            if self.entries.len() >= self.max_entries {
+                // the oldest fragment paying the lowest fee comes first
+                match self.by_fee_density.iter().next() {
+                    Some(&(lowest, _, lowest_id)) if lowest <= fee_density => {
+                        tracing::debug!(
+                            "the pool is full, evicting fragment {} paying the lowest fee",
+                            lowest_id
+                        );
+                        self.remove(&lowest_id);
+                        evicted = Some(lowest_id);
+                    }
+                    _ => return Err(Rejection::PoolFull),
+                }
+            }
+
+            let key = (fee_density, self.next_sequence, fragment_id);
+            self.next_sequence += 1;
+            self.by_fee_density.insert(key);
+            *self.by_origin.entry(origin).or_insert(0) += 1;
+            for account in &accounts {
+                *self.by_account.entry(account.clone()).or_insert(0) += 1;
             }
+            self.entries.put(
+                fragment_id,
+                Entry {
+                    fragment: fragment.clone(),
+                    key,
+                    origin,
+                    accounts,
+                },
+            );
+            Ok((fragment, evicted))
         }
 
-        /// Returns clones of registered fragments
+        /// Returns the ids of the fragments evicted to make room for the
+        /// registered ones
         pub fn insert_all(
             &mut self,
-            fragments: impl IntoIterator<Item = Fragment>,
-        ) -> Vec<Fragment> {
+            fragments: impl IntoIterator<Item = (Fragment, FragmentOrigin)>,
+        ) -> Vec<FragmentId> {
             fragments
                 .into_iter()
-                .filter_map(|fragment| self.insert(fragment))
+                .filter_map(|(fragment, origin)| self.insert(fragment, origin).ok())
+                .filter_map(|(_, evicted)| evicted)
                 .collect()
         }
 
+        fn check_quotas(
+            &self,
+            origin: FragmentOrigin,
+            accounts: &[UnspecifiedAccountIdentifier],
+        ) -> Result<(), Rejection> {
+            let max = match origin {
+                FragmentOrigin::Rest => self.quotas.rest_max_entries,
+                FragmentOrigin::Network => self.quotas.network_max_entries,
+            };
+            if let Some(max) = max {
+                if self.by_origin.get(&origin).copied().unwrap_or(0) >= max {
+                    return Err(Rejection::OriginQuota { origin, max });
+                }
+            }
+            if let Some(max) = self.quotas.account_max_entries {
+                for account in accounts {
+                    if self.by_account.get(account).copied().unwrap_or(0) >= max {
+                        return Err(Rejection::AccountQuota {
+                            account: account.clone(),
+                            max,
+                        });
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        fn remove_entry(&mut self, entry: Entry) -> Fragment {
+            self.by_fee_density.remove(&entry.key);
+            decrement(&mut self.by_origin, &entry.origin);
+            for account in &entry.accounts {
+                decrement(&mut self.by_account, account);
+            }
+            entry.fragment
+        }
+
+        fn remove(&mut self, fragment_id: &FragmentId) -> Option<Fragment> {
+            let entry = self.entries.pop(fragment_id)?;
+            Some(self.remove_entry(entry))
+        }
+
         pub fn remove_all<'a>(&mut self, fragment_ids: impl IntoIterator<Item = &'a FragmentId>) {
             for fragment_id in fragment_ids {
-                self.entries.pop(fragment_id);
+                self.remove(fragment_id);
             }
         }
 
         pub fn remove_oldest(&mut self) -> Option<Fragment> {
-            self.entries.pop_lru().map(|(_, value)| value)
+            let (_, entry) = self.entries.pop_lru()?;
+            Some(self.remove_entry(entry))
+        }
+
+        /// remove the fragment paying the highest fee per byte, the oldest
+        /// one among those paying the same fee
+        pub fn remove_highest_fee_density(&mut self) -> Option<Fragment> {
+            let &(highest, _, _) = self.by_fee_density.iter().next_back()?;
+            let &(_, _, fragment_id) = self
+                .by_fee_density
+                .range((highest, 0, FragmentId::zero_hash())..)
+                .next()?;
+            self.remove(&fragment_id)
+        }
+
+        pub fn get(&self, fragment_id: &FragmentId) -> Option<&Fragment> {
+            self.entries.peek(fragment_id).map(|entry| &entry.fragment)
+        }
+
+        /// the fragments of the pool, the oldest first
+        pub fn fragments(&self) -> impl Iterator<Item = &Fragment> {
+            self.entries.iter().rev().map(|(_, entry)| &entry.fragment)
+        }
+    }
+
+    fn decrement<K: std::hash::Hash + Eq>(counts: &mut HashMap<K, usize>, key: &K) {
+        if let Some(count) = counts.get_mut(key) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(key);
+            }
+        }
+    }
+
+    /// the accounts the fragment spends from
+    fn input_accounts(fragment: &Fragment) -> Vec<UnspecifiedAccountIdentifier> {
+        fn accounts<P>(tx: &Transaction<P>) -> Vec<UnspecifiedAccountIdentifier> {
+            let mut accounts = Vec::new();
+            for input in tx.as_slice().inputs().iter() {
+                if let InputEnum::AccountInput(account, _) = input.to_enum() {
+                    if !accounts.contains(&account) {
+                        accounts.push(account);
+                    }
+                }
+            }
+            accounts
+        }
+
+        match fragment {
+            Fragment::Transaction(tx) => accounts(tx),
+            Fragment::OwnerStakeDelegation(tx) => accounts(tx),
+            Fragment::StakeDelegation(tx) => accounts(tx),
+            Fragment::PoolRegistration(tx) => accounts(tx),
+            Fragment::PoolRetirement(tx) => accounts(tx),
+            Fragment::PoolUpdate(tx) => accounts(tx),
+            Fragment::VotePlan(tx) => accounts(tx),
+            Fragment::VoteCast(tx) => accounts(tx),
+            Fragment::VoteTally(tx) => accounts(tx),
+            Fragment::EncryptedVoteTally(tx) => accounts(tx),
+            Fragment::Initial(_)
+            | Fragment::OldUtxoDeclaration(_)
+            | Fragment::UpdateProposal(_)
+            | Fragment::UpdateVote(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::internal::{Pool, Rejection};
+    use super::*;
+    use crate::{blockcfg::HeaderHash, utils::async_msg};
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{
+        account::SpendingCounter,
+        transaction::{Input, TxBuilder, Witness},
+        value::Value,
+    };
+
+    /// a transaction spending `fee` from the account of `key` with no
+    /// outputs, so it pays all of it as fee
+    fn transaction_from(key: &SecretKey<Ed25519>, fee: u64) -> Fragment {
+        let inputs = [Input::from_account_single(
+            key.to_public().into(),
+            Value(fee),
+        )];
+        let builder = TxBuilder::new().set_nopayload().set_ios(&inputs, &[]);
+        let sign_data = builder.get_auth_data_for_witness().hash();
+        let witness = Witness::new_account(
+            &HeaderHash::zero_hash(),
+            &sign_data,
+            SpendingCounter::zero(),
+            |d| key.sign(d),
+        );
+        Fragment::Transaction(builder.set_witnesses(&[witness]).set_payload_auth(&()))
+    }
+
+    /// a transaction paying `fee` from a new account, the transactions
+    /// built by this function all have the same size
+    fn transaction(fee: u64) -> Fragment {
+        transaction_from(&SecretKey::generate(rand_core::OsRng), fee)
+    }
+
+    fn inserted(pool: &mut Pool, fragment: &Fragment) -> Option<FragmentId> {
+        match pool.insert(fragment.clone(), FragmentOrigin::Network) {
+            Ok((_, evicted)) => evicted,
+            Err(rejection) => panic!("the fragment is rejected: {}", rejection),
         }
     }
+
+    fn pools(max_entries: usize, quotas: Quotas) -> (Pools, async_msg::MessageQueue<NetworkMsg>) {
+        let (network_msg_box, network_queue) = async_msg::channel(32);
+        let pools = Pools::new(
+            max_entries,
+            quotas,
+            0,
+            Logs::new(32),
+            network_msg_box,
+            false,
+        );
+        (pools, network_queue)
+    }
+
+    fn status(pools: &mut Pools, fragment: &Fragment) -> FragmentStatus {
+        pools
+            .logs()
+            .get(fragment.id())
+            .expect("the fragment is in the logs")
+            .status()
+            .clone()
+    }
+
+    #[test]
+    fn full_pool_evicts_the_lowest_fee() {
+        let mut pool = Pool::new(2, Quotas::default());
+        let low = transaction(10);
+        let high = transaction(30);
+        assert_eq!(inserted(&mut pool, &low), None);
+        assert_eq!(inserted(&mut pool, &transaction(20)), None);
+
+        assert_eq!(inserted(&mut pool, &high), Some(low.id()));
+        assert!(pool.get(&low.id()).is_none());
+        assert!(matches!(
+            pool.insert(transaction(5), FragmentOrigin::Network),
+            Err(Rejection::PoolFull)
+        ));
+        assert!(pool.get(&high.id()).is_some());
+    }
+
+    #[test]
+    fn full_pool_evicts_the_oldest_of_equal_fees() {
+        let mut pool = Pool::new(2, Quotas::default());
+        let first = transaction(0);
+        let second = transaction(0);
+        let third = transaction(0);
+        inserted(&mut pool, &first);
+        inserted(&mut pool, &second);
+
+        assert_eq!(inserted(&mut pool, &third), Some(first.id()));
+        assert_eq!(inserted(&mut pool, &transaction(0)), Some(second.id()));
+        assert!(pool.get(&third.id()).is_some());
+    }
+
+    #[test]
+    fn highest_fee_is_removed_first_the_oldest_among_equal_fees() {
+        let mut pool = Pool::new(4, Quotas::default());
+        let low = transaction(10);
+        let first = transaction(20);
+        let second = transaction(20);
+        for fragment in &[&low, &first, &second] {
+            inserted(&mut pool, fragment);
+        }
+
+        let removed = std::iter::from_fn(|| pool.remove_highest_fee_density())
+            .map(|fragment| fragment.id())
+            .collect::<Vec<_>>();
+        assert_eq!(removed, vec![first.id(), second.id(), low.id()]);
+    }
+
+    #[tokio::test]
+    async fn evicted_fragments_are_rejected_in_the_logs() {
+        let (mut pools, _network_queue) = pools(1, Quotas::default());
+        let low = transaction(10);
+        let high = transaction(20);
+        let lower = transaction(5);
+        for fragment in &[&low, &high, &lower] {
+            pools
+                .insert_and_propagate_all(
+                    FragmentOrigin::Rest,
+                    None,
+                    vec![(*fragment).clone()],
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            status(&mut pools, &low),
+            FragmentStatus::Rejected {
+                reason: EVICTED_REASON.to_owned()
+            }
+        );
+        assert_eq!(status(&mut pools, &high), FragmentStatus::Pending);
+        assert!(matches!(
+            status(&mut pools, &lower),
+            FragmentStatus::Rejected { .. }
+        ));
+        assert_eq!(
+            pools.fragments().map(Fragment::id).collect::<Vec<_>>(),
+            vec![high.id()]
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_fragments_are_removed_and_rejected() {
+        let (mut pools, _network_queue) = pools(4, Quotas::default());
+        let fragment = transaction(10);
+        pools
+            .insert_and_propagate_all(FragmentOrigin::Rest, None, vec![fragment.clone()], None)
+            .await
+            .unwrap();
+
+        assert_eq!(pools.remove_expired(Duration::from_secs(3600)), 0);
+        assert_eq!(status(&mut pools, &fragment), FragmentStatus::Pending);
+
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(pools.remove_expired(Duration::from_millis(1)), 1);
+        assert_eq!(
+            status(&mut pools, &fragment),
+            FragmentStatus::Rejected {
+                reason: "expired".to_owned()
+            }
+        );
+        assert!(pools.get(&fragment.id()).is_none());
+    }
+
+    #[test]
+    fn origin_quota_is_enforced() {
+        let quotas = Quotas {
+            rest_max_entries: Some(1),
+            ..Quotas::default()
+        };
+        let mut pool = Pool::new(4, quotas);
+        let rest = transaction(10);
+        assert!(pool.insert(rest.clone(), FragmentOrigin::Rest).is_ok());
+        assert!(matches!(
+            pool.insert(transaction(10), FragmentOrigin::Rest),
+            Err(Rejection::OriginQuota {
+                origin: FragmentOrigin::Rest,
+                max: 1
+            })
+        ));
+        assert!(pool
+            .insert(transaction(10), FragmentOrigin::Network)
+            .is_ok());
+
+        pool.remove_all(std::iter::once(&rest.id()));
+        assert!(pool.insert(transaction(10), FragmentOrigin::Rest).is_ok());
+    }
+
+    #[test]
+    fn account_quota_is_enforced() {
+        let quotas = Quotas {
+            account_max_entries: Some(1),
+            ..Quotas::default()
+        };
+        let mut pool = Pool::new(4, quotas);
+        let key = SecretKey::generate(rand_core::OsRng);
+        let first = transaction_from(&key, 10);
+        inserted(&mut pool, &first);
+        assert!(matches!(
+            pool.insert(transaction_from(&key, 20), FragmentOrigin::Network),
+            Err(Rejection::AccountQuota { max: 1, .. })
+        ));
+        inserted(&mut pool, &transaction(20));
+
+        pool.remove_all(std::iter::once(&first.id()));
+        inserted(&mut pool, &transaction_from(&key, 20));
+    }
+
+    #[tokio::test]
+    async fn fragments_over_the_quotas_are_rejected_in_the_logs() {
+        let quotas = Quotas {
+            account_max_entries: Some(1),
+            ..Quotas::default()
+        };
+        let (mut pools, _network_queue) = pools(4, quotas);
+        let key = SecretKey::generate(rand_core::OsRng);
+        let first = transaction_from(&key, 10);
+        let second = transaction_from(&key, 20);
+        pools
+            .insert_and_propagate_all(
+                FragmentOrigin::Rest,
+                None,
+                vec![first.clone(), second.clone()],
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(status(&mut pools, &first), FragmentStatus::Pending);
+        assert!(matches!(
+            status(&mut pools, &second),
+            FragmentStatus::Rejected { reason } if reason.contains("maximum of 1 fragments spending")
+        ));
+        assert!(pools.get(&second.id()).is_none());
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
-    fragment::{Logs, Pools},
-    intercom::{NetworkMsg, TransactionMsg},
+    blockchain::{Ref, Tip},
+    fragment::{persistence, Fragment, Logs, MempoolFile, Pools, Quotas},
+    intercom::{self, NetworkMsg, ReplySendError, ReplyStreamHandle, TransactionMsg},
     stats_counter::StatsCounter,
     utils::{
         async_msg::{MessageBox, MessageQueue},
@@ -8,8 +9,10 @@ use crate::{
     },
 };
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
+use futures::{channel::mpsc::SendError, SinkExt};
+use jormungandr_lib::interfaces::{FragmentOrigin, FragmentTtl};
 use thiserror::Error;
 use tokio_stream::StreamExt;
 use tracing::{span, Level};
@@ -17,8 +20,13 @@ use tracing_futures::Instrument;
 
 pub struct Process {
     pool_max_entries: usize,
+    quotas: Quotas,
     logs: Logs,
     network_msg_box: MessageBox<NetworkMsg>,
+    mempool_file: Option<MempoolFile>,
+    ledger_validation: bool,
+    accept_update_proposals: bool,
+    fragment_ttl: Option<FragmentTtl>,
 }
 
 #[derive(Debug, Error)]
@@ -30,14 +38,24 @@ pub enum Error {
 impl Process {
     pub fn new(
         pool_max_entries: usize,
+        quotas: Quotas,
         logs_max_entries: usize,
         network_msg_box: MessageBox<NetworkMsg>,
+        mempool_file: Option<MempoolFile>,
+        ledger_validation: bool,
+        accept_update_proposals: bool,
+        fragment_ttl: Option<FragmentTtl>,
     ) -> Self {
         let logs = Logs::new(logs_max_entries);
         Process {
             pool_max_entries,
+            quotas,
             logs,
             network_msg_box,
+            mempool_file,
+            ledger_validation,
+            accept_update_proposals,
+            fragment_ttl,
         }
     }
 
@@ -46,36 +64,59 @@ impl Process {
         n_pools: usize,
         service_info: TokioServiceInfo,
         stats_counter: StatsCounter,
+        blockchain_tip: Tip,
         mut input: MessageQueue<TransactionMsg>,
     ) -> Result<(), Error> {
         let mut pool = Pools::new(
             self.pool_max_entries,
+            self.quotas,
             n_pools,
             self.logs,
             self.network_msg_box,
+            self.accept_update_proposals,
         );
+        let mempool_file = self.mempool_file;
+        let ledger_validation = self.ledger_validation;
+        let fragment_ttl = self.fragment_ttl;
+        let service_info = &service_info;
 
         async move {
+            if let Some(mempool_file) = &mempool_file {
+                restore_mempool(mempool_file, &mut pool, &blockchain_tip).await;
+            }
+
             while let Some(input_result) = input.next().await {
                 match input_result {
                     TransactionMsg::SendTransaction(origin, txs) => {
-                        // Note that we cannot use apply_block here, since we don't have a valid context to which to apply
-                        // those blocks. one valid tx in a given context, could be invalid in another. for example
-                        // fee calculations, existence utxo / account solvency.
-
-                        // FIXME/TODO check that the txs are valid within themselves with basic requirements (e.g. inputs >= outputs).
-                        // we also want to keep a basic capability to filter away repetitive queries or definitely discarded txid.
+                        // The fragments are checked against the ledger of the current tip, unless disabled in the
+                        // settings. This does not guarantee they will be valid in the block they end up in: one valid
+                        // tx in a given context could be invalid in another, for example after a branch switch.
+                        // This is why the fragments are applied again to the ledger when they are selected.
 
                         // This interface only makes sense for messages coming from arbitrary users (like transaction, certificates),
                         // for other message we don't want to receive them through this interface, and possibly
                         // put them in another pool.
 
                         let stats_counter = stats_counter.clone();
+                        let tip = validation_tip(ledger_validation, &blockchain_tip).await;
 
-                        pool.insert_and_propagate_all(origin, txs)
+                        pool.insert_and_propagate_all(origin, None, txs, tip)
                             .await
                             .map(move |count| stats_counter.add_tx_recv_cnt(count))?;
                     }
+                    TransactionMsg::SendPeerTransactions(node_address, txs) => {
+                        let stats_counter = stats_counter.clone();
+                        let tip = validation_tip(ledger_validation, &blockchain_tip).await;
+
+                        pool.insert_and_propagate_all(
+                            FragmentOrigin::Network,
+                            Some(node_address),
+                            txs,
+                            tip,
+                        )
+                        .await
+                        .map(move |count| stats_counter.add_tx_recv_cnt(count))?;
+                    }
                     TransactionMsg::RemoveTransactions(fragment_ids, status) => {
                         tracing::debug!(
                             "removing fragments added to block {:?}: {:?}",
@@ -84,6 +125,14 @@ impl Process {
                         );
                         pool.remove_added_to_block(fragment_ids, status);
                     }
+                    TransactionMsg::RestoreTransactions(fragments) => {
+                        tracing::debug!(
+                            "restoring {} fragments of rolled back blocks",
+                            fragments.len()
+                        );
+                        let tip = validation_tip(ledger_validation, &blockchain_tip).await;
+                        pool.restore_rolled_back(fragments, tip).await?;
+                    }
                     TransactionMsg::GetLogs(reply_handle) => {
                         let logs = pool.logs().logs().cloned().collect();
                         reply_handle.reply_ok(logs);
@@ -97,6 +146,30 @@ impl Process {
                         );
                         reply_handle.reply_ok(statuses);
                     }
+                    TransactionMsg::GetPendingFragments(reply_handle) => {
+                        reply_handle.reply_ok(pool.pending());
+                    }
+                    TransactionMsg::GetPendingFragment(fragment_id, reply_handle) => {
+                        reply_handle.reply_ok(pool.get(&fragment_id).cloned());
+                    }
+                    TransactionMsg::GetFeeDensityThreshold {
+                        block_content_max_size,
+                        size,
+                        reply_handle,
+                    } => {
+                        reply_handle
+                            .reply_ok(pool.fee_density_threshold(block_content_max_size, size));
+                    }
+                    TransactionMsg::GetFragments(fragment_ids, handle) => {
+                        let fragments = fragment_ids
+                            .iter()
+                            .filter_map(|fragment_id| pool.get(fragment_id).cloned())
+                            .collect();
+                        // the fragments are streamed from another task so
+                        // a slow peer does not hold the fragment process
+                        service_info
+                            .spawn_fallible("get fragments", send_fragments(fragments, handle));
+                    }
                     TransactionMsg::SelectTransactions {
                         pool_idx,
                         ledger,
@@ -109,6 +182,37 @@ impl Process {
                             pool.select(pool_idx, ledger, block_date, ledger_params, selection_alg);
                         reply_handle.reply_ok(contents);
                     }
+                    TransactionMsg::SaveMempool(reply_handle) => {
+                        let result = match &mempool_file {
+                            Some(mempool_file) => {
+                                let mempool_file = mempool_file.clone();
+                                let persisted = pool.to_persisted();
+                                tokio::task::spawn_blocking(move || mempool_file.write(&persisted))
+                                    .await
+                                    .map_err(intercom::Error::failed)
+                                    .and_then(|result| result.map_err(intercom::Error::failed))
+                            }
+                            None => Ok(()),
+                        };
+                        reply_handle.reply(result);
+                    }
+                    TransactionMsg::RemoveExpiredTransactions => {
+                        if let Some(fragment_ttl) = fragment_ttl {
+                            let ttl = match fragment_ttl {
+                                FragmentTtl::Duration(duration) => duration.into(),
+                                FragmentTtl::Slots(slots) => {
+                                    let tip = blockchain_tip.get_ref().await;
+                                    Duration::from_secs(
+                                        u64::from(slots) * tip.time_frame().slot_duration(),
+                                    )
+                                }
+                            };
+                            let count = pool.remove_expired(ttl);
+                            if count > 0 {
+                                tracing::debug!("removed {} expired fragments", count);
+                            }
+                        }
+                    }
                 }
             }
             Ok(())
@@ -117,3 +221,63 @@ impl Process {
         .await
     }
 }
+
+/// the tip whose ledger the incoming fragments are checked against, if
+/// enabled
+async fn validation_tip(ledger_validation: bool, blockchain_tip: &Tip) -> Option<Arc<Ref>> {
+    if ledger_validation {
+        Some(blockchain_tip.get_ref().await)
+    } else {
+        None
+    }
+}
+
+async fn restore_mempool(mempool_file: &MempoolFile, pool: &mut Pools, blockchain_tip: &Tip) {
+    let persisted = match mempool_file.read() {
+        Ok(Some(persisted)) => persisted,
+        Ok(None) => return,
+        Err(err) => {
+            tracing::warn!(reason = %err, "cannot read the mempool file, starting with an empty mempool");
+            return;
+        }
+    };
+    let count = persisted.fragments.len();
+    let tip = blockchain_tip.get_ref().await;
+    let fragments = persistence::revalidate(&tip, persisted, pool.logs());
+    tracing::info!(
+        "restored {} of the {} fragments of the mempool file",
+        fragments.len(),
+        count
+    );
+    pool.restore_persisted(fragments);
+}
+
+async fn send_fragments(
+    fragments: Vec<Fragment>,
+    handle: ReplyStreamHandle<Fragment>,
+) -> Result<(), ReplySendError> {
+    let mut sink = handle.start_sending();
+    for fragment in fragments {
+        sink.send(Ok(fragment)).await?;
+    }
+    sink.close().await
+}
+
+/// ask the fragment process to remove the expired fragments
+pub async fn remove_expired_fragments(
+    mut msg_box: MessageBox<TransactionMsg>,
+) -> Result<(), SendError> {
+    msg_box
+        .send(TransactionMsg::RemoveExpiredTransactions)
+        .await
+}
+
+/// ask the fragment process to write the mempool to the mempool file
+pub async fn save_mempool(mut msg_box: MessageBox<TransactionMsg>) -> Result<(), intercom::Error> {
+    let (reply_handle, reply_future) = intercom::unary_reply();
+    msg_box
+        .send(TransactionMsg::SaveMempool(reply_handle))
+        .await
+        .map_err(intercom::Error::failed)?;
+    reply_future.await
+}
@@ -2,13 +2,17 @@ use super::logs::Logs;
 use super::pool::internal::Pool;
 use crate::{
     blockcfg::{BlockDate, Contents, ContentsBuilder, Ledger, LedgerParameters},
-    fragment::FragmentId,
+    fragment::{Fragment, FragmentId},
 };
 use chain_core::property::Fragment as _;
-use jormungandr_lib::interfaces::FragmentStatus;
+use chain_impl_mockchain::transaction::Transaction;
+use jormungandr_lib::interfaces::{FragmentOrigin, FragmentStatus};
+use serde::{Deserialize, Serialize};
 
 use tracing::{span, Level};
 
+use std::cmp::Ordering;
+use std::convert::TryInto;
 use std::error::Error;
 use std::iter;
 
@@ -32,11 +36,98 @@ pub trait FragmentSelectionAlgorithm {
     fn finalize(self) -> Contents;
 }
 
-#[derive(Debug)]
+/// the fragment selection algorithm used to build the blocks, set with the
+/// `leadership.fragment_selection` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FragmentSelectionAlgorithmParams {
     OldestFirst,
+    HighestFeeDensityFirst,
 }
 
+impl Default for FragmentSelectionAlgorithmParams {
+    fn default() -> Self {
+        FragmentSelectionAlgorithmParams::OldestFirst
+    }
+}
+
+/// the fee paid by a fragment per byte of its serialized size
+///
+/// The fee paid by a transaction is its balance: what the inputs bring on
+/// top of the outputs, that is the fee required by the `LinearFee` of the
+/// ledger plus any excess. The fragments without a transaction pay no fee.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeDensity {
+    fee: u64,
+    size: u64,
+}
+
+impl FeeDensity {
+    pub fn of(fragment: &Fragment) -> Self {
+        fn fee<P>(tx: &Transaction<P>) -> u64 {
+            match (tx.total_input(), tx.total_output()) {
+                (Ok(input), Ok(output)) => (input - output).map_or(0, |fee| fee.0),
+                _ => 0,
+            }
+        }
+
+        let fee = match fragment {
+            Fragment::Transaction(tx) => fee(tx),
+            Fragment::OwnerStakeDelegation(tx) => fee(tx),
+            Fragment::StakeDelegation(tx) => fee(tx),
+            Fragment::PoolRegistration(tx) => fee(tx),
+            Fragment::PoolRetirement(tx) => fee(tx),
+            Fragment::PoolUpdate(tx) => fee(tx),
+            Fragment::VotePlan(tx) => fee(tx),
+            Fragment::VoteCast(tx) => fee(tx),
+            Fragment::VoteTally(tx) => fee(tx),
+            Fragment::EncryptedVoteTally(tx) => fee(tx),
+            Fragment::Initial(_)
+            | Fragment::OldUtxoDeclaration(_)
+            | Fragment::UpdateProposal(_)
+            | Fragment::UpdateVote(_) => 0,
+        };
+        FeeDensity {
+            fee,
+            size: fragment.to_raw().size_bytes_plus_size() as u64,
+        }
+    }
+
+    /// the size of the fragment, in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// the lowest fee a fragment of `size` bytes has to pay to have a
+    /// higher fee density than this one
+    pub fn lowest_fee_above(&self, size: u64) -> u64 {
+        let fee = u128::from(self.fee) * u128::from(size) / u128::from(self.size);
+        fee.try_into().unwrap_or(u64::MAX).saturating_add(1)
+    }
+}
+
+impl Ord for FeeDensity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // compare the fractions without rounding, the sizes are never zero
+        (u128::from(self.fee) * u128::from(other.size))
+            .cmp(&(u128::from(other.fee) * u128::from(self.size)))
+    }
+}
+
+impl PartialOrd for FeeDensity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for FeeDensity {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FeeDensity {}
+
 pub struct OldestFirst {
     builder: ContentsBuilder,
     current_total_size: u32,
@@ -70,60 +161,142 @@ impl FragmentSelectionAlgorithm for OldestFirst {
         logs: &mut Logs,
         pool: &mut Pool,
     ) {
-        let mut ledger_simulation = ledger.clone();
-
-        let mut return_to_pool = Vec::new();
-
-        while let Some(fragment) = pool.remove_oldest() {
-            let id = fragment.id();
-            let fragment_raw = fragment.to_raw(); // TODO: replace everything to FragmentRaw in the node
-            let fragment_size = fragment_raw.size_bytes_plus_size() as u32;
-
-            let span = span!(Level::TRACE, "fragment_selection_algorithm", kind="older_first", hash=%id.to_string());
-            let _enter = span.enter();
-            if fragment_size > ledger_params.block_content_max_size {
-                let reason = format!(
-                    "fragment size {} exceeds maximum block content size {}",
-                    fragment_size, ledger_params.block_content_max_size
-                );
-                tracing::debug!("{}", reason);
-                logs.modify(id, FragmentStatus::Rejected { reason });
-                continue;
-            }
+        select_in_order(
+            &mut self.builder,
+            &mut self.current_total_size,
+            "older_first",
+            ledger,
+            ledger_params,
+            block_date,
+            logs,
+            pool,
+            Pool::remove_oldest,
+        )
+    }
+}
 
-            let total_size = self.current_total_size + fragment_size;
+/// select the fragments paying the highest fee per byte first, so the
+/// fragments paying more are included first when the blocks are full
+pub struct HighestFeeDensityFirst {
+    builder: ContentsBuilder,
+    current_total_size: u32,
+}
 
-            if total_size <= ledger_params.block_content_max_size {
-                tracing::debug!("applying fragment in simulation");
-                match ledger_simulation.apply_fragment(ledger_params, &fragment, block_date) {
-                    Ok(ledger_new) => {
-                        self.builder.push(fragment);
-                        ledger_simulation = ledger_new;
-                        tracing::debug!("successfully applied and committed the fragment");
-                    }
-                    Err(error) => {
-                        let mut msg = error.to_string();
-                        for e in iter::successors(error.source(), |&e| e.source()) {
-                            msg.push_str(": ");
-                            msg.push_str(&e.to_string());
-                        }
-                        tracing::debug!(?error, "fragment is rejected");
-                        logs.modify(id, FragmentStatus::Rejected { reason: msg })
+impl HighestFeeDensityFirst {
+    pub fn new() -> Self {
+        HighestFeeDensityFirst {
+            builder: ContentsBuilder::new(),
+            current_total_size: 0,
+        }
+    }
+}
+
+impl Default for HighestFeeDensityFirst {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FragmentSelectionAlgorithm for HighestFeeDensityFirst {
+    fn finalize(self) -> Contents {
+        self.builder.into()
+    }
+
+    fn select(
+        &mut self,
+        ledger: &Ledger,
+        ledger_params: &LedgerParameters,
+        block_date: BlockDate,
+        logs: &mut Logs,
+        pool: &mut Pool,
+    ) {
+        select_in_order(
+            &mut self.builder,
+            &mut self.current_total_size,
+            "highest_fee_density_first",
+            ledger,
+            ledger_params,
+            block_date,
+            logs,
+            pool,
+            Pool::remove_highest_fee_density,
+        )
+    }
+}
+
+/// take the fragments out of the pool in the order given by `next` and
+/// apply them to a simulation of the ledger, the ones that apply are
+/// added to the block contents and the ones that fail are rejected
+#[allow(clippy::too_many_arguments)]
+fn select_in_order(
+    builder: &mut ContentsBuilder,
+    current_total_size: &mut u32,
+    kind: &'static str,
+    ledger: &Ledger,
+    ledger_params: &LedgerParameters,
+    block_date: BlockDate,
+    logs: &mut Logs,
+    pool: &mut Pool,
+    mut next: impl FnMut(&mut Pool) -> Option<Fragment>,
+) {
+    let mut ledger_simulation = ledger.clone();
+
+    let mut return_to_pool = Vec::new();
+
+    while let Some(fragment) = next(pool) {
+        let id = fragment.id();
+        let fragment_raw = fragment.to_raw(); // TODO: replace everything to FragmentRaw in the node
+        let fragment_size = fragment_raw.size_bytes_plus_size() as u32;
+
+        let span = span!(Level::TRACE, "fragment_selection_algorithm", kind, hash=%id.to_string());
+        let _enter = span.enter();
+        if fragment_size > ledger_params.block_content_max_size {
+            let reason = format!(
+                "fragment size {} exceeds maximum block content size {}",
+                fragment_size, ledger_params.block_content_max_size
+            );
+            tracing::debug!("{}", reason);
+            logs.modify(id, FragmentStatus::Rejected { reason });
+            continue;
+        }
+
+        let total_size = *current_total_size + fragment_size;
+
+        if total_size <= ledger_params.block_content_max_size {
+            tracing::debug!("applying fragment in simulation");
+            match ledger_simulation.apply_fragment(ledger_params, &fragment, block_date) {
+                Ok(ledger_new) => {
+                    builder.push(fragment);
+                    ledger_simulation = ledger_new;
+                    tracing::debug!("successfully applied and committed the fragment");
+                }
+                Err(error) => {
+                    let mut msg = error.to_string();
+                    for e in iter::successors(error.source(), |&e| e.source()) {
+                        msg.push_str(": ");
+                        msg.push_str(&e.to_string());
                     }
+                    tracing::debug!(?error, "fragment is rejected");
+                    logs.modify(id, FragmentStatus::Rejected { reason: msg })
                 }
+            }
 
-                self.current_total_size = total_size;
+            *current_total_size = total_size;
 
-                if total_size == ledger_params.block_content_max_size {
-                    break;
-                }
-            } else {
-                // return a fragment to the pool later if does not fit the contents size limit
-                return_to_pool.push(fragment);
+            if total_size == ledger_params.block_content_max_size {
+                break;
             }
-            drop(_enter);
+        } else {
+            // return a fragment to the pool later if does not fit the contents size limit
+            return_to_pool.push(fragment);
         }
-
-        pool.insert_all(return_to_pool);
+        drop(_enter);
     }
+
+    pool.insert_all(return_to_pool.into_iter().map(|fragment| {
+        let origin = logs
+            .get(fragment.id())
+            .map_or(FragmentOrigin::Network, |log| *log.received_from());
+        (fragment, origin)
+    }));
 }
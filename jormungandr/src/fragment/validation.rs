@@ -0,0 +1,66 @@
+//! Checks of the fragments against the ledger state of the tip, done when
+//! they enter the mempool and when the persisted mempool is reloaded.
+
+use crate::{
+    blockcfg::{BlockDate, Ledger, LedgerParameters},
+    blockchain::Ref,
+    fragment::Fragment,
+};
+use chain_time::era::EpochPosition;
+
+use std::{error::Error, iter, sync::Arc, time::SystemTime};
+
+/// a simulation of the ledger of the tip, the fragments that are accepted
+/// are applied to it one after the other as they would be in a block, so
+/// two fragments spending the same funds are not both accepted.
+pub struct TipLedger {
+    ledger: Ledger,
+    ledger_params: Arc<LedgerParameters>,
+    block_date: BlockDate,
+}
+
+impl TipLedger {
+    pub fn new(tip: &Ref) -> Self {
+        TipLedger {
+            ledger: tip.ledger().as_ref().clone(),
+            ledger_params: Arc::clone(tip.epoch_ledger_parameters()),
+            block_date: current_date(tip),
+        }
+    }
+
+    /// apply the fragment to the simulated ledger, returns the reason of
+    /// the rejection of the fragment if it cannot be applied
+    pub fn apply(&mut self, fragment: &Fragment) -> Result<(), String> {
+        match self
+            .ledger
+            .apply_fragment(&self.ledger_params, fragment, self.block_date)
+        {
+            Ok(ledger) => {
+                self.ledger = ledger;
+                Ok(())
+            }
+            Err(error) => {
+                let mut reason = error.to_string();
+                for e in iter::successors(error.source(), |&e| e.source()) {
+                    reason.push_str(": ");
+                    reason.push_str(&e.to_string());
+                }
+                Err(reason)
+            }
+        }
+    }
+}
+
+/// the date of the current slot, or the date of the tip if the current
+/// slot cannot be computed
+fn current_date(tip: &Ref) -> BlockDate {
+    let era = tip.epoch_leadership_schedule().era();
+    tip.time_frame()
+        .slot_at(&SystemTime::now())
+        .and_then(|slot| era.from_slot_to_era(slot))
+        .map(|EpochPosition { epoch, slot }| BlockDate {
+            epoch: epoch.0,
+            slot_id: slot.0,
+        })
+        .unwrap_or_else(|| tip.block_date())
+}
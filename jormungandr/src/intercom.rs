@@ -2,12 +2,12 @@ use crate::blockcfg::{
     Block, BlockDate, Fragment, FragmentId, Header, HeaderHash, Ledger, LedgerParameters,
 };
 use crate::blockchain::{Checkpoints, StorageError};
-use crate::fragment::selection::FragmentSelectionAlgorithmParams;
-use crate::network::p2p::{comm::PeerInfo, Address};
+use crate::fragment::selection::{FeeDensity, FragmentSelectionAlgorithmParams};
+use crate::network::p2p::{comm::PeerInfo, Address, PeerEvent};
 use crate::utils::async_msg::{self, MessageBox, MessageQueue};
 use chain_impl_mockchain::fragment::Contents as FragmentContents;
 use chain_network::error as net_error;
-use jormungandr_lib::interfaces::{FragmentLog, FragmentOrigin, FragmentStatus};
+use jormungandr_lib::interfaces::{FragmentLog, FragmentOrigin, FragmentStatus, PendingFragment};
 
 use futures::channel::{mpsc, oneshot};
 use futures::prelude::*;
@@ -90,6 +90,16 @@ impl Error {
         }
     }
 
+    pub fn out_of_range<T>(cause: T) -> Self
+    where
+        T: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        Error {
+            code: net_error::Code::OutOfRange,
+            cause: cause.into(),
+        }
+    }
+
     pub fn unimplemented<S: Into<String>>(message: S) -> Self {
         Error {
             code: net_error::Code::Unimplemented,
@@ -499,12 +509,35 @@ pub fn stream_request<T, R>(
 #[derive(Debug)]
 pub enum TransactionMsg {
     SendTransaction(FragmentOrigin, Vec<Fragment>),
+    /// Fragments received from the peer with the given address, the peer
+    /// is reported to the network task for the fragments rejected by the
+    /// ledger
+    SendPeerTransactions(Address, Vec<Fragment>),
     RemoveTransactions(Vec<FragmentId>, FragmentStatus),
+    /// The fragments of blocks that were rolled back from the main branch,
+    /// to be put back in the pool
+    RestoreTransactions(Vec<Fragment>),
     GetLogs(ReplyHandle<Vec<FragmentLog>>),
     GetStatuses(
         Vec<FragmentId>,
         ReplyHandle<HashMap<FragmentId, FragmentStatus>>,
     ),
+    /// The decoded fragments of the pools
+    GetPendingFragments(ReplyHandle<Vec<PendingFragment>>),
+    /// The fragment with the given id, if it is in one of the pools
+    GetPendingFragment(FragmentId, ReplyHandle<Option<Fragment>>),
+    /// Stream the fragments with the given ids that are in the pools,
+    /// the ids of the fragments not found are skipped
+    GetFragments(Vec<FragmentId>, ReplyStreamHandle<Fragment>),
+    /// The fee density a fragment of `size` bytes has to exceed to be
+    /// selected in the next block when the fragments paying the highest fee
+    /// per byte are selected first, `None` if it fits with all the
+    /// fragments of the pools
+    GetFeeDensityThreshold {
+        block_content_max_size: u32,
+        size: u32,
+        reply_handle: ReplyHandle<Option<FeeDensity>>,
+    },
     SelectTransactions {
         pool_idx: usize,
         ledger: Ledger,
@@ -513,6 +546,12 @@ pub enum TransactionMsg {
         selection_alg: FragmentSelectionAlgorithmParams,
         reply_handle: ReplyHandle<FragmentContents>,
     },
+    /// Write the pools and the fragment logs to the mempool file
+    /// of the storage, if any
+    SaveMempool(ReplyHandle<()>),
+    /// Remove the fragments that have been in the pools for longer than
+    /// the `mempool.fragment_ttl` setting
+    RemoveExpiredTransactions,
 }
 
 /// Client messages, mainly requests from connected peers to our node.
@@ -571,12 +610,16 @@ pub enum BlockMsg {
     LeadershipBlock(Block),
     /// A untrusted block Header has been received from the network task
     AnnouncedBlock(Header, Address),
-    /// A stream of untrusted blocks has been received from the network task.
-    NetworkBlocks(RequestStreamHandle<Block, ()>),
+    /// A stream of untrusted blocks has been received from the network task,
+    /// from the peer with the given address when it is known.
+    NetworkBlocks(Option<Address>, RequestStreamHandle<Block, ()>),
     /// The stream of headers for missing chain blocks has been received
     /// from the network in response to a PullHeaders request or a Missing
     /// solicitation event.
     ChainHeaders(RequestStreamHandle<Header, ()>),
+    /// Roll the tip back to the given ancestor, removing at most the given
+    /// number of blocks from the main branch. Replies with the new tip.
+    RollbackTip(HeaderHash, u32, ReplyHandle<HeaderHash>),
 }
 
 /// Propagation requests for the network task.
@@ -598,6 +641,11 @@ pub enum NetworkMsg {
         to: HeaderHash,
     },
     PeerInfo(ReplyHandle<Vec<PeerInfo>>),
+    /// Update the score of the peer with its behaviour
+    ReportPeer {
+        node_address: Address,
+        event: PeerEvent,
+    },
 }
 
 /// Messages to the explorer task
@@ -5,6 +5,7 @@ use crate::{
         Ledger, LedgerParameters,
     },
     blockchain::{new_epoch_leadership_from, Ref, Tip},
+    fragment::selection::FragmentSelectionAlgorithmParams,
     intercom::{unary_reply, BlockMsg, Error as IntercomError, TransactionMsg},
     leadership::{
         enclave::{Enclave, EnclaveError, LeaderEvent, Schedule},
@@ -69,6 +70,7 @@ pub struct Module {
     pool: MessageBox<TransactionMsg>,
     enclave: Enclave,
     block_message: MessageBox<BlockMsg>,
+    selection_alg: FragmentSelectionAlgorithmParams,
 }
 
 impl Module {
@@ -79,6 +81,7 @@ impl Module {
         pool: MessageBox<TransactionMsg>,
         enclave: Enclave,
         block_message: MessageBox<BlockMsg>,
+        selection_alg: FragmentSelectionAlgorithmParams,
     ) -> Result<Self, LeadershipError> {
         let tip_ref = tip.get_ref().await;
 
@@ -91,6 +94,7 @@ impl Module {
             pool,
             enclave,
             block_message,
+            selection_alg,
         })
     }
 
@@ -451,7 +455,15 @@ impl Module {
             return Ok(());
         };
 
-        let contents = prepare_block(pool, event.id, event.date, ledger, ledger_parameters).await?;
+        let contents = prepare_block(
+            pool,
+            event.id,
+            event.date,
+            ledger,
+            ledger_parameters,
+            self.selection_alg,
+        )
+        .await?;
 
         let event_logs_error = event_logs.clone();
         let signing = {
@@ -647,9 +659,8 @@ async fn prepare_block(
     block_date: BlockDate,
     ledger: Arc<Ledger>,
     epoch_parameters: Arc<LedgerParameters>,
+    selection_alg: FragmentSelectionAlgorithmParams,
 ) -> Result<Contents, LeadershipError> {
-    use crate::fragment::selection::FragmentSelectionAlgorithmParams;
-
     let (reply_handle, reply_future) = unary_reply();
 
     let pool_idx: u32 = leader_id.into();
@@ -659,7 +670,7 @@ async fn prepare_block(
         ledger: ledger.as_ref().clone(),
         block_date,
         ledger_params: epoch_parameters.as_ref().clone(),
-        selection_alg: FragmentSelectionAlgorithmParams::OldestFirst,
+        selection_alg,
         reply_handle,
     };
 
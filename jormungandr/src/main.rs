@@ -20,7 +20,7 @@ use crate::{
 use futures::executor::block_on;
 use futures::prelude::*;
 use jormungandr_lib::interfaces::NodeState;
-use settings::{start::RawSettings, CommandLine};
+use settings::{start::RawSettings, Command, CommandLine};
 use tokio::signal;
 use tokio_util::sync::CancellationToken;
 use tracing::{span, Level, Span};
@@ -65,6 +65,7 @@ pub struct BootstrappedNode {
     blockchain: Blockchain,
     blockchain_tip: blockchain::Tip,
     block0_hash: HeaderHash,
+    known_peers: Vec<network::p2p::KnownPeer>,
     explorer_db: Option<explorer::ExplorerDB>,
     rest_context: Option<rest::ContextLock>,
     services: Services,
@@ -77,6 +78,10 @@ const NETWORK_TASK_QUEUE_LEN: usize = 32;
 const EXPLORER_TASK_QUEUE_LEN: usize = 32;
 const CLIENT_TASK_QUEUE_LEN: usize = 32;
 const BOOTSTRAP_RETRY_WAIT: Duration = Duration::from_secs(5);
+const MEMPOOL_SAVE_INTERVAL: Duration = Duration::from_secs(600);
+const MEMPOOL_SAVE_TIMEOUT: Duration = Duration::from_secs(30);
+const KNOWN_PEERS_SAVE_TIMEOUT: Duration = Duration::from_secs(30);
+const FRAGMENT_EXPIRY_INTERVAL: Duration = Duration::from_secs(10);
 
 fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::Error> {
     if let Some(context) = bootstrapped_node.rest_context.as_ref() {
@@ -159,9 +164,16 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
     }
 
     // FIXME: reduce state sharing across services
+    let peers_file = bootstrapped_node
+        .settings
+        .storage
+        .as_ref()
+        .map(|dir| network::p2p::PeersFile::new(dir, bootstrapped_node.block0_hash));
     let network_state = Arc::new(network::GlobalState::new(
         bootstrapped_node.block0_hash,
         bootstrapped_node.settings.network.clone(),
+        peers_file,
+        bootstrapped_node.known_peers,
         stats_counter.clone(),
         span!(Level::TRACE, "task", kind = "network"),
     ));
@@ -204,10 +216,11 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
 
     {
         let leadership_logs = leadership_logs.clone();
-        let block_msgbox = block_msgbox;
+        let block_msgbox = block_msgbox.clone();
         let blockchain_tip = blockchain_tip.clone();
         let enclave = leadership::Enclave::new(enclave.clone());
         let fragment_msgbox = fragment_msgbox.clone();
+        let selection_alg = bootstrapped_node.settings.leadership.fragment_selection;
 
         services.spawn_try_future("leadership", move |info| {
             leadership::Module::new(
@@ -217,6 +230,7 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
                 fragment_msgbox,
                 enclave,
                 block_msgbox,
+                selection_alg,
             )
             .and_then(|module| module.run())
         });
@@ -224,14 +238,47 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
 
     {
         let stats_counter = stats_counter.clone();
+        let blockchain_tip = blockchain_tip.clone();
+        let fragment_msgbox = fragment_msgbox.clone();
+        let mempool_file = bootstrapped_node
+            .settings
+            .storage
+            .as_ref()
+            .map(|dir| fragment::MempoolFile::new(dir, bootstrapped_node.block0_hash));
+        let persist_mempool = mempool_file.is_some();
+        let mempool = &bootstrapped_node.settings.mempool;
+        let fragment_ttl = mempool.fragment_ttl;
+        let quotas = fragment::Quotas {
+            account_max_entries: mempool.account_max_entries,
+            rest_max_entries: mempool.rest_max_entries,
+            network_max_entries: mempool.network_max_entries,
+        };
         let process = fragment::Process::new(
-            bootstrapped_node.settings.mempool.pool_max_entries.into(),
-            bootstrapped_node.settings.mempool.log_max_entries.into(),
+            mempool.pool_max_entries.into(),
+            quotas,
+            mempool.log_max_entries.into(),
             network_msgbox.clone(),
+            mempool_file,
+            mempool.ledger_validation,
+            mempool.accept_update_proposals,
+            fragment_ttl,
         );
 
         services.spawn_try_future("fragment", move |info| {
-            process.start(n_pools, info, stats_counter, fragment_queue)
+            if persist_mempool {
+                let fragment_msgbox = fragment_msgbox.clone();
+                info.run_periodic_fallible("save mempool", MEMPOOL_SAVE_INTERVAL, move || {
+                    fragment::save_mempool(fragment_msgbox.clone())
+                });
+            }
+            if fragment_ttl.is_some() {
+                info.run_periodic_fallible(
+                    "remove expired fragments",
+                    FRAGMENT_EXPIRY_INTERVAL,
+                    move || fragment::remove_expired_fragments(fragment_msgbox.clone()),
+                );
+            }
+            process.start(n_pools, info, stats_counter, blockchain_tip, fragment_queue)
         });
     };
 
@@ -239,10 +286,13 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         let full_context = rest::FullContext {
             stats_counter,
             network_task: network_msgbox,
-            transaction_task: fragment_msgbox,
+            transaction_task: fragment_msgbox.clone(),
+            block_task: block_msgbox,
             leadership_logs,
             enclave,
-            network_state,
+            fragment_selection: bootstrapped_node.settings.leadership.fragment_selection,
+            accept_update_proposals: bootstrapped_node.settings.mempool.accept_update_proposals,
+            network_state: network_state.clone(),
             explorer: explorer.as_ref().map(|(_msg_box, context)| context.clone()),
         };
         block_on(async {
@@ -266,7 +316,34 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         });
     }
 
-    match services.wait_any_finished() {
+    let result = services.wait_any_finished();
+
+    if bootstrapped_node.settings.storage.is_some() {
+        let save_mempool = services.block_on_task("save_mempool", |_info| {
+            tokio::time::timeout(
+                MEMPOOL_SAVE_TIMEOUT,
+                fragment::save_mempool(fragment_msgbox),
+            )
+        });
+        match save_mempool {
+            Ok(Ok(())) => tracing::info!("mempool saved"),
+            Ok(Err(err)) => tracing::warn!(reason = %err, "cannot save the mempool"),
+            Err(_) => tracing::warn!("saving the mempool timed out"),
+        }
+
+        let save_known_peers = services.block_on_task("save_known_peers", |_info| {
+            tokio::time::timeout(KNOWN_PEERS_SAVE_TIMEOUT, async move {
+                network_state.save_known_peers().await
+            })
+        });
+        match save_known_peers {
+            Ok(Ok(())) => tracing::info!("known peers saved"),
+            Ok(Err(err)) => tracing::warn!(reason = %err, "cannot save the known peers"),
+            Err(_) => tracing::warn!("saving the known peers timed out"),
+        }
+    }
+
+    match result {
         Ok(()) => {
             tracing::info!("Shutting down node");
             Ok(())
@@ -306,6 +383,7 @@ fn bootstrap(initialized_node: InitializedNode) -> Result<BootstrappedNode, star
         blockchain,
         blockchain_tip,
         block0_hash,
+        known_peers,
         explorer_db,
         rest_context,
         settings,
@@ -323,6 +401,7 @@ fn bootstrap(initialized_node: InitializedNode) -> Result<BootstrappedNode, star
     Ok(BootstrappedNode {
         settings,
         block0_hash,
+        known_peers,
         blockchain,
         blockchain_tip,
         explorer_db,
@@ -336,6 +415,7 @@ struct BootstrapData {
     blockchain: Blockchain,
     blockchain_tip: blockchain::Tip,
     block0_hash: HeaderHash,
+    known_peers: Vec<network::p2p::KnownPeer>,
     explorer_db: Option<explorer::ExplorerDB>,
     rest_context: Option<rest::ContextLock>,
     settings: Settings,
@@ -366,9 +446,20 @@ async fn bootstrap_internal(
 
     let cache_capacity = 102_400;
 
-    let (blockchain, blockchain_tip) =
-        start_up::load_blockchain(block0, storage, cache_capacity, settings.rewards_report_all)
-            .await?;
+    let snapshots = start_up::prepare_ledger_snapshots(&settings)?;
+
+    let (blockchain, blockchain_tip) = start_up::load_blockchain(
+        block0,
+        storage,
+        snapshots,
+        settings
+            .storage_pruning
+            .as_ref()
+            .map(|pruning| pruning.keep_epochs),
+        cache_capacity,
+        settings.rewards_report_all,
+    )
+    .await?;
 
     if let Some(context) = &rest_context {
         let mut context = context.write().await;
@@ -377,6 +468,22 @@ async fn bootstrap_internal(
         context.set_bootstrap_stopper(cancellation_token.clone());
     };
 
+    if let Some(path) = &settings.import_blocks {
+        tracing::info!("importing the blocks from '{:?}'", path);
+        let count =
+            blockchain::import_blocks(blockchain.clone(), blockchain_tip.clone(), path).await?;
+        tracing::info!("{} blocks imported", count);
+    }
+
+    let known_peers = settings
+        .storage
+        .as_ref()
+        .map(|dir| {
+            network::p2p::PeersFile::new(dir, block0_hash)
+                .load(settings.network.known_peers_max_age)
+        })
+        .unwrap_or_default();
+
     let mut bootstrap_attempt: usize = 0;
 
     loop {
@@ -394,6 +501,7 @@ async fn bootstrap_internal(
         // Will return true if we successfully bootstrap or there are no trusted peers defined.
         if network::bootstrap(
             &settings.network,
+            &known_peers,
             blockchain.clone(),
             blockchain_tip.clone(),
             cancellation_token.clone(),
@@ -434,6 +542,7 @@ async fn bootstrap_internal(
 
     Ok(BootstrapData {
         block0_hash,
+        known_peers,
         blockchain,
         blockchain_tip,
         explorer_db,
@@ -504,8 +613,10 @@ fn init_os_signal_watchers(services: &mut Services, token: CancellationToken) {
 }
 
 fn initialize_node() -> Result<InitializedNode, start_up::Error> {
-    let command_line = CommandLine::load();
-    let exit_after_storage_setup = command_line.storage_check;
+    let mut command_line = CommandLine::load();
+    let storage_repair = command_line.storage_repair;
+    let exit_after_storage_setup = command_line.storage_check || storage_repair;
+    let command = command_line.command.take();
 
     if command_line.full_version {
         println!("{}", env!("FULL_VERSION"));
@@ -530,11 +641,21 @@ fn initialize_node() -> Result<InitializedNode, start_up::Error> {
 
     let settings = raw_settings.try_into_settings()?;
 
-    let storage = start_up::prepare_storage(&settings)?;
+    // the storage is checked as it is, before it is rewritten by a pruning
+    let storage = start_up::prepare_storage(&settings, !exit_after_storage_setup)?;
     if exit_after_storage_setup {
+        start_up::check_storage(&settings, storage, storage_repair)?;
         tracing::info!("Exiting after successful storage setup");
         std::mem::drop(_enter);
         std::mem::drop(init_span);
+        std::process::exit(0);
+    }
+
+    if let Some(Command::ExportBlocks(arguments)) = command {
+        block_on(start_up::export_blocks(&settings, &storage, &arguments))?;
+        tracing::info!("Exiting after successful export of the blocks");
+        std::mem::drop(_enter);
+        std::mem::drop(init_span);
         std::mem::drop(storage);
         std::process::exit(0);
     }
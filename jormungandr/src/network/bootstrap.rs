@@ -1,13 +1,20 @@
 use super::grpc;
-use crate::blockcfg::{Block, HeaderDesc, HeaderHash};
-use crate::blockchain::{self, Blockchain, Error as BlockchainError, PreCheckedHeader, Ref, Tip};
-use crate::settings::start::network::Peer;
+use crate::blockcfg::{Block, Header, HeaderDesc, HeaderHash};
+use crate::blockchain::{
+    self, Blockchain, CheckHeaderProof, Error as BlockchainError, HeaderProofPipeline,
+    PreCheckedHeader, Ref, Tip, DEFAULT_VERIFICATION_WINDOW,
+};
+use crate::settings::start::{
+    config::TrustedCheckpoint,
+    network::{Peer, Tls},
+};
 use chain_core::property::{Deserialize, HasHeader};
 use chain_network::data as net_data;
-use chain_network::error::Error as NetworkError;
+use chain_network::error::{self as net_error, Error as NetworkError};
 use futures::{prelude::*, stream, task::Poll};
 use tokio_util::sync::CancellationToken;
 
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -48,14 +55,39 @@ pub enum Error {
     Interrupted,
     #[error("Trusted peers cannot be empty. To avoid bootstrap use `skip_bootstrap: true`")]
     EmptyTrustedPeers,
+    #[error("block {got} has the chain length {chain_length} of the trusted checkpoint, expected block {expected}")]
+    CheckpointMismatch {
+        chain_length: u32,
+        expected: HeaderHash,
+        got: HeaderHash,
+    },
+    #[error("the peer did not send the blocks up to the trusted checkpoint {0}")]
+    CheckpointNotReached(HeaderHash),
+    #[error("the peer sent headers beyond the trusted checkpoint {0}")]
+    BeyondCheckpoint(HeaderHash),
+    #[error("received block {0} is not on the chain leading to the trusted checkpoint")]
+    BlockNotOnCheckpointChain(HeaderHash),
+}
+
+impl Error {
+    /// the peer does not have the blocks to bootstrap from as they have been
+    /// removed from its storage by the pruning
+    pub fn is_peer_pruned(&self) -> bool {
+        match self {
+            Error::PullRequestFailed(e) | Error::PullStreamFailed(e) => {
+                matches!(e.code(), net_error::Code::OutOfRange)
+            }
+            _ => false,
+        }
+    }
 }
 
 const MAX_BOOTSTRAP_PEERS: u32 = 32;
 
-pub async fn peers_from_trusted_peer(peer: &Peer) -> Result<Vec<Peer>, Error> {
+pub async fn peers_from_trusted_peer(peer: &Peer, tls: Option<&Tls>) -> Result<Vec<Peer>, Error> {
     tracing::info!("getting peers from bootstrap peer {}", peer.connection);
 
-    let mut client = grpc::connect(&peer).await.map_err(Error::Connect)?;
+    let mut client = grpc::connect(&peer, tls).await.map_err(Error::Connect)?;
     let peers = client
         .peers(MAX_BOOTSTRAP_PEERS)
         .await
@@ -66,10 +98,47 @@ pub async fn peers_from_trusted_peer(peer: &Peer) -> Result<Vec<Peer>, Error> {
     Ok(peers)
 }
 
+/// check that the local chain contains the trusted checkpoint if it is long
+/// enough to reach it. The check is skipped if the checkpoint was pruned from
+/// the storage.
+pub async fn check_trusted_checkpoint(
+    blockchain: &Blockchain,
+    tip: &Tip,
+    checkpoint: &TrustedCheckpoint,
+) -> Result<(), Error> {
+    let tip_ref = tip.get_ref().await;
+    let tip_chain_length = u32::from(tip_ref.chain_length());
+    if tip_chain_length < checkpoint.chain_length {
+        return Ok(());
+    }
+
+    let expected = checkpoint.hash.into_hash();
+    match blockchain
+        .storage()
+        .get_nth_ancestor(tip_ref.hash(), tip_chain_length - checkpoint.chain_length)
+    {
+        Ok(got) if got == expected => Ok(()),
+        Ok(got) => Err(Error::CheckpointMismatch {
+            chain_length: checkpoint.chain_length,
+            expected,
+            got,
+        }),
+        Err(err) => {
+            tracing::warn!(
+                reason = %err,
+                "cannot check that the local chain contains the trusted checkpoint"
+            );
+            Ok(())
+        }
+    }
+}
+
 pub async fn bootstrap_from_peer(
     peer: &Peer,
+    tls: Option<&Tls>,
     blockchain: Blockchain,
     tip: Tip,
+    trusted_checkpoint: Option<&TrustedCheckpoint>,
     cancellation_token: CancellationToken,
 ) -> Result<(), Error> {
     use crate::network::convert::Decode;
@@ -90,9 +159,10 @@ pub async fn bootstrap_from_peer(
 
     tracing::debug!("connecting to bootstrap peer {}", peer.connection);
 
-    let mut client = with_cancellation_token(grpc::connect(&peer).boxed(), &cancellation_token)
-        .await?
-        .map_err(Error::Connect)?;
+    let mut client =
+        with_cancellation_token(grpc::connect(&peer, tls).boxed(), &cancellation_token)
+            .await?
+            .map_err(Error::Connect)?;
 
     loop {
         let remote_tip = with_cancellation_token(client.tip().boxed(), &cancellation_token)
@@ -101,23 +171,62 @@ pub async fn bootstrap_from_peer(
             .map_err(Error::TipFailed)?
             .id();
 
-        if remote_tip == tip.get_ref().await.hash() {
+        let tip_ref = tip.get_ref().await;
+        if remote_tip == tip_ref.hash() {
             break Ok(());
         }
 
+        // while the local chain is below the trusted checkpoint, the blocks
+        // are pulled up to the checkpoint only, so the chain received can be
+        // checked to lead to it
+        let pull_to_checkpoint = trusted_checkpoint
+            .filter(|checkpoint| u32::from(tip_ref.chain_length()) < checkpoint.chain_length)
+            .map(|checkpoint| checkpoint.hash.into_hash());
+        let to = pull_to_checkpoint.unwrap_or(remote_tip);
+
         let checkpoints = blockchain.get_checkpoints(tip.branch()).await;
         let checkpoints = net_data::block::try_ids_from_iter(checkpoints).unwrap();
 
-        let remote_tip = BlockId::try_from(remote_tip.as_ref()).unwrap();
+        let to = BlockId::try_from(to.as_ref()).unwrap();
 
         tracing::info!(
-            "pulling blocks starting from checkpoints: {:?}; to tip {:?}",
+            "pulling blocks starting from checkpoints: {:?}; to {} {:?}",
             checkpoints,
-            remote_tip,
+            if pull_to_checkpoint.is_some() {
+                "trusted checkpoint"
+            } else {
+                "tip"
+            },
+            to,
         );
 
+        // the headers up to the checkpoint are pulled first, so only the
+        // blocks of a chain that does lead to the checkpoint are stored
+        let checkpoint_chain = match trusted_checkpoint.filter(|_| pull_to_checkpoint.is_some()) {
+            Some(checkpoint) => {
+                let headers = with_cancellation_token(
+                    client.pull_headers(checkpoints.clone(), to).boxed(),
+                    &cancellation_token,
+                )
+                .await?
+                .map_err(Error::PullRequestFailed)?
+                .map(|header| {
+                    header
+                        .and_then(|header| header.decode())
+                        .map_err(Error::PullStreamFailed)
+                });
+                let chain = with_cancellation_token(
+                    checkpoint_chain(headers, checkpoint).boxed(),
+                    &cancellation_token,
+                )
+                .await??;
+                Some(chain)
+            }
+            None => None,
+        };
+
         let stream = with_cancellation_token(
-            client.pull_blocks(checkpoints, remote_tip).boxed(),
+            client.pull_blocks(checkpoints, to).boxed(),
             &cancellation_token,
         )
         .await?
@@ -127,6 +236,8 @@ pub async fn bootstrap_from_peer(
             blockchain.clone(),
             tip.clone(),
             stream,
+            trusted_checkpoint,
+            checkpoint_chain.as_ref(),
             cancellation_token.clone(),
         )
         .await?;
@@ -197,10 +308,56 @@ impl BootstrapInfo {
     }
 }
 
+/// read the headers of the stream, pulled up to the trusted checkpoint, and
+/// return the hashes of the chain of headers leading to the checkpoint.
+///
+/// The chain of hashes authenticates the blocks below the checkpoint, so the
+/// blocks pulled afterwards are stored only if they are on this chain.
+async fn checkpoint_chain<S>(
+    mut headers: S,
+    checkpoint: &TrustedCheckpoint,
+) -> Result<HashSet<HeaderHash>, Error>
+where
+    S: Stream<Item = Result<Header, Error>> + Unpin,
+{
+    let expected = checkpoint.hash.into_hash();
+    let mut parents = HashMap::new();
+    while let Some(header) = headers.next().await {
+        let header = header?;
+        check_header(&header, Some(checkpoint))?;
+        // there are no more headers up to the checkpoint than its chain
+        // length, counting the block0
+        if parents.len() > checkpoint.chain_length as usize {
+            return Err(Error::BeyondCheckpoint(expected));
+        }
+        parents.insert(header.hash(), header.parent_id());
+    }
+
+    let mut chain = HashSet::new();
+    let mut current = expected;
+    while let Some(parent) = parents.remove(&current) {
+        chain.insert(current);
+        current = parent;
+    }
+    if chain.is_empty() {
+        return Err(Error::CheckpointNotReached(expected));
+    }
+    Ok(chain)
+}
+
+/// apply the blocks of the stream and update the tip with the last one.
+///
+/// With `checkpoint_chain`, the stream is expected to end with the trusted
+/// checkpoint: only the blocks of the chain of hashes leading to the
+/// checkpoint are accepted, their proofs are not checked as the chain of
+/// hashes authenticates them, and the tip is only updated if the checkpoint
+/// is reached.
 async fn bootstrap_from_stream<S>(
     mut blockchain: Blockchain,
     branch: Tip,
     stream: S,
+    trusted_checkpoint: Option<&TrustedCheckpoint>,
+    checkpoint_chain: Option<&HashSet<HeaderHash>>,
     cancellation_token: CancellationToken,
 ) -> Result<(), Error>
 where
@@ -208,6 +365,7 @@ where
 {
     const PROCESS_LOGGING_DISTANCE: u64 = 2500;
     let block0 = *blockchain.block0();
+    let to_trusted_checkpoint = checkpoint_chain.is_some();
 
     let mut bootstrap_info = BootstrapInfo::new();
     let mut maybe_parent_tip = None;
@@ -230,51 +388,113 @@ where
         }
     });
 
-    while let Some(block_result) = stream.next().await {
-        let result = match block_result {
-            Ok(block) => {
-                let block =
-                    Block::deserialize(block.as_bytes()).map_err(Error::BlockDecodingFailed)?;
-
-                if block.header.hash() == block0 {
-                    continue;
+    // without any applied block the pipeline does not verify the proofs
+    // ahead, they are then skipped when applying the blocks
+    let mut pipeline = HeaderProofPipeline::new(DEFAULT_VERIFICATION_WINDOW);
+    if !to_trusted_checkpoint {
+        pipeline.applied(&branch.get_ref().await);
+    }
+    let mut stream_ended = false;
+    let mut stream_error = None;
+
+    // The proofs of the queued blocks are verified ahead while the blocks
+    // are applied one after the other. If the stream fails, the blocks
+    // received so far are still applied before the error is returned.
+    let result = loop {
+        while !stream_ended && !pipeline.is_full() {
+            let block = match stream.next().await {
+                Some(Ok(block)) => {
+                    Block::deserialize(block.as_bytes()).map_err(Error::BlockDecodingFailed)
+                }
+                Some(Err(err)) => Err(err),
+                None => {
+                    stream_ended = true;
+                    break;
+                }
+            };
+            let block = match block {
+                Ok(block) if block.header.hash() == block0 => continue,
+                Ok(block) => check_block(block, trusted_checkpoint, checkpoint_chain),
+                Err(err) => Err(err),
+            };
+            let block = match block {
+                Ok(block) => block,
+                Err(err) => {
+                    stream_ended = true;
+                    stream_error = Some(err);
+                    break;
                 }
+            };
 
-                bootstrap_info.append_block(&block);
+            bootstrap_info.append_block(&block);
 
-                if bootstrap_info.block_received % PROCESS_LOGGING_DISTANCE == 0 {
-                    bootstrap_info.report();
-                }
+            if bootstrap_info.block_received % PROCESS_LOGGING_DISTANCE == 0 {
+                bootstrap_info.report();
+            }
+
+            pipeline.push(block);
+        }
 
-                handle_block(&blockchain, block).await
+        let (block, check_header_proof) = match pipeline.next().await {
+            Some(Ok(_)) if to_trusted_checkpoint && stream_error.is_some() => {
+                // the checkpoint cannot be reached anymore
+                break stream_error.map_or(Ok(()), Err);
+            }
+            Some(Ok((block, _))) if to_trusted_checkpoint => {
+                (block, CheckHeaderProof::SkipBelowTrustedCheckpoint)
             }
-            Err(err) => Err(err),
+            Some(Ok(next)) => next,
+            Some(Err(err)) => break Err(Error::HeaderCheckFailed(err)),
+            None => break stream_error.map_or(Ok(()), Err),
         };
 
-        match result {
+        match handle_block(&blockchain, block, check_header_proof).await {
             Ok(parent_tip) => {
+                if !to_trusted_checkpoint {
+                    pipeline.applied(&parent_tip);
+                }
                 maybe_parent_tip = Some(parent_tip);
             }
-            Err(err) => {
-                if let Some(parent_tip) = maybe_parent_tip {
-                    if let Err(err) = blockchain::process_new_ref(
-                        &mut blockchain,
-                        branch.clone(),
-                        parent_tip.clone(),
-                        None,
-                    )
-                    .await
-                    {
-                        tracing::warn!(error = ?err, "couldn't gracefully exit from failed netboot");
-                    }
-                }
-                return Err(err);
+            Err(err) => break Err(err),
+        }
+    };
+
+    let result = match (result, trusted_checkpoint) {
+        (Ok(()), Some(checkpoint)) if to_trusted_checkpoint => {
+            let checkpoint = checkpoint.hash.into_hash();
+            if maybe_parent_tip.as_ref().map(|tip| tip.hash()) == Some(checkpoint) {
+                Ok(())
+            } else {
+                Err(Error::CheckpointNotReached(checkpoint))
+            }
+        }
+        (result, _) => result,
+    };
+
+    if let Err(err) = result {
+        // the blocks pulled up to the trusted checkpoint were not checked,
+        // the tip is not moved to them unless the checkpoint was reached
+        if to_trusted_checkpoint {
+            return Err(err);
+        }
+        if let Some(parent_tip) = maybe_parent_tip {
+            if let Err(err) = blockchain::process_new_ref(
+                &mut blockchain,
+                branch.clone(),
+                parent_tip.clone(),
+                None,
+                None,
+            )
+            .await
+            {
+                tracing::warn!(error = ?err, "couldn't gracefully exit from failed netboot");
             }
         }
+        return Err(err);
     }
 
     if let Some(parent_tip) = maybe_parent_tip {
-        blockchain::process_new_ref(&mut blockchain, branch, parent_tip, None)
+        blockchain::process_new_ref(&mut blockchain, branch, parent_tip, None, None)
             .await
             .map_err(Error::ChainSelectionFailed)
     } else {
@@ -283,7 +503,51 @@ where
     }
 }
 
-async fn handle_block(blockchain: &Blockchain, block: Block) -> Result<Arc<Ref>, Error> {
+/// reject the block if it is not on the chain leading to the trusted
+/// checkpoint, when pulling the blocks up to it, or if it has the chain length
+/// of the trusted checkpoint but is another block
+fn check_block(
+    block: Block,
+    trusted_checkpoint: Option<&TrustedCheckpoint>,
+    checkpoint_chain: Option<&HashSet<HeaderHash>>,
+) -> Result<Block, Error> {
+    let hash = block.header.hash();
+    if checkpoint_chain.map_or(false, |chain| !chain.contains(&hash)) {
+        return Err(Error::BlockNotOnCheckpointChain(hash));
+    }
+    check_header(&block.header, trusted_checkpoint)?;
+    Ok(block)
+}
+
+/// reject the header if it has the chain length of the trusted checkpoint but
+/// is another header
+fn check_header(
+    header: &Header,
+    trusted_checkpoint: Option<&TrustedCheckpoint>,
+) -> Result<(), Error> {
+    match trusted_checkpoint {
+        Some(checkpoint) if u32::from(header.chain_length()) == checkpoint.chain_length => {
+            let expected = checkpoint.hash.into_hash();
+            let got = header.hash();
+            if got == expected {
+                Ok(())
+            } else {
+                Err(Error::CheckpointMismatch {
+                    chain_length: checkpoint.chain_length,
+                    expected,
+                    got,
+                })
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn handle_block(
+    blockchain: &Blockchain,
+    block: Block,
+    check_header_proof: CheckHeaderProof,
+) -> Result<Arc<Ref>, Error> {
     let header = block.header();
     let pre_checked = blockchain
         .pre_check_header(header, true)
@@ -303,7 +567,7 @@ async fn handle_block(blockchain: &Blockchain, block: Block) -> Result<Arc<Ref>,
         }
         PreCheckedHeader::HeaderWithCache { header, parent_ref } => {
             let post_checked = blockchain
-                .post_check_header(header, parent_ref, blockchain::CheckHeaderProof::Enabled)
+                .post_check_header(header, parent_ref, check_header_proof)
                 .await
                 .map_err(Error::HeaderCheckFailed)?;
 
@@ -320,3 +584,119 @@ async fn handle_block(blockchain: &Blockchain, block: Block) -> Result<Arc<Ref>,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{BlockDate, BlockVersion, Contents, ContentsBuilder, HeaderBuilderNew};
+    use chain_core::property::ChainLength as _;
+
+    /// a genesis header followed by `length` headers, the `fork` number is
+    /// used to give different dates, hence different hashes, to competing
+    /// chains
+    fn chain(length: u32, fork: u32) -> Vec<Header> {
+        let contents: Contents = ContentsBuilder::new().into();
+        let genesis = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+            .set_genesis()
+            .set_date(BlockDate::first())
+            .into_unsigned_header()
+            .unwrap()
+            .generalize();
+        let mut headers = vec![genesis];
+        for slot_id in 1..=length {
+            let parent = headers.last().unwrap();
+            let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+                .set_parent(&parent.hash(), parent.chain_length().next())
+                .set_date(BlockDate {
+                    epoch: 0,
+                    slot_id: slot_id + fork * 1_000,
+                })
+                .into_unsigned_header()
+                .unwrap()
+                .generalize();
+            headers.push(header);
+        }
+        headers
+    }
+
+    fn checkpoint(header: &Header) -> TrustedCheckpoint {
+        TrustedCheckpoint {
+            chain_length: u32::from(header.chain_length()),
+            hash: header.hash().into(),
+        }
+    }
+
+    fn stream_of(headers: &[Header]) -> impl Stream<Item = Result<Header, Error>> + Unpin {
+        stream::iter(headers.to_vec().into_iter().map(Ok))
+    }
+
+    #[tokio::test]
+    async fn chain_leading_to_the_checkpoint_is_accepted() {
+        let main = chain(5, 0);
+        let checkpoint = checkpoint(&main[5]);
+
+        let chain = checkpoint_chain(stream_of(&main[1..]), &checkpoint)
+            .await
+            .unwrap();
+
+        assert_eq!(chain.len(), 5);
+        assert!(main[1..]
+            .iter()
+            .all(|header| chain.contains(&header.hash())));
+    }
+
+    #[tokio::test]
+    async fn forged_chain_is_rejected() {
+        let main = chain(5, 0);
+        let forged = chain(5, 1);
+        let checkpoint = checkpoint(&main[5]);
+
+        let result = checkpoint_chain(stream_of(&forged[1..]), &checkpoint).await;
+
+        assert!(matches!(result, Err(Error::CheckpointMismatch { .. })));
+    }
+
+    #[tokio::test]
+    async fn forged_chain_stopping_below_the_checkpoint_is_rejected() {
+        let main = chain(5, 0);
+        let forged = chain(3, 1);
+        let checkpoint = checkpoint(&main[5]);
+
+        let result = checkpoint_chain(stream_of(&forged[1..]), &checkpoint).await;
+
+        assert!(matches!(result, Err(Error::CheckpointNotReached(_))));
+    }
+
+    #[tokio::test]
+    async fn aborted_stream_is_rejected() {
+        let main = chain(5, 0);
+        let checkpoint = checkpoint(&main[5]);
+
+        let ended_early = checkpoint_chain(stream_of(&main[1..4]), &checkpoint).await;
+        assert!(matches!(ended_early, Err(Error::CheckpointNotReached(_))));
+
+        let failing = stream_of(&main[1..4]).chain(stream::iter(vec![Err(Error::Interrupted)]));
+        let failed = checkpoint_chain(failing, &checkpoint).await;
+        assert!(matches!(failed, Err(Error::Interrupted)));
+    }
+
+    #[tokio::test]
+    async fn blocks_off_the_checkpoint_chain_are_rejected() {
+        let main = chain(5, 0);
+        let forged = chain(5, 1);
+        let checkpoint = checkpoint(&main[5]);
+        let chain = checkpoint_chain(stream_of(&main[1..]), &checkpoint)
+            .await
+            .unwrap();
+
+        let block = |header: &Header| Block {
+            header: header.clone(),
+            contents: ContentsBuilder::new().into(),
+        };
+        assert!(check_block(block(&main[2]), Some(&checkpoint), Some(&chain)).is_ok());
+        assert!(matches!(
+            check_block(block(&forged[2]), Some(&checkpoint), Some(&chain)),
+            Err(Error::BlockNotOnCheckpointChain(_))
+        ));
+    }
+}
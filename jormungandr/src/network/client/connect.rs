@@ -34,6 +34,7 @@ pub fn connect(state: ConnectionState, channels: Channels) -> (ConnectHandle, Co
     let peer = state.peer();
     let keypair = state.global.keypair.clone();
     let legacy_node_id = state.global.config.legacy_node_id;
+    let tls = state.global.config.tls.clone();
     let span = state.span().clone();
     let async_span = span.clone();
     let _enter = span.enter();
@@ -44,10 +45,10 @@ pub fn connect(state: ConnectionState, channels: Channels) -> (ConnectHandle, Co
                 "connecting with legacy node id {}",
                 hex::encode(node_id.as_bytes())
             );
-            grpc::connect_legacy(&peer, node_id).await
+            grpc::connect_legacy(&peer, tls.as_ref(), node_id).await
         } else {
             tracing::debug!("connecting");
-            grpc::connect(&peer).await
+            grpc::connect(&peer, tls.as_ref()).await
         }
         .map_err(ConnectError::Transport)?;
 
@@ -68,6 +69,10 @@ pub fn connect(state: ConnectionState, channels: Channels) -> (ConnectHandle, Co
 
         tracing::debug!(node_id = ?peer_id, "authenticated server peer node");
 
+        if state.global.is_private_network() && !state.global.is_node_allowed(&peer_id) {
+            return Err(ConnectError::NodeNotAllowed(peer_id));
+        }
+
         // Send client authentication
         let auth = keypair.sign(&hr.nonce);
         grpc_client
@@ -180,6 +185,8 @@ pub enum ConnectError {
     InvalidNodeSignature(#[source] chain_crypto::SignatureError),
     #[error("signature verification failed for peer node ID")]
     PeerSignatureVerificationFailed(#[source] net_error::Error),
+    #[error("the peer node {0:?} is not allowed in the private network")]
+    NodeNotAllowed(NodeId),
     #[error("client authentication failed")]
     ClientAuth(#[source] net_error::Error),
     #[error("subscription request failed")]
@@ -442,12 +442,15 @@ impl Client {
             kind = "GetBlocks"
         );
         let (handle, sink, _) = intercom::stream_request(buffer_sizes::inbound::BLOCKS);
+        let node_address = self.inbound.peer_address.clone();
         // TODO: make sure that back pressure on the number of requests
         // in flight prevents unlimited spawning of these tasks.
         // https://github.com/input-output-hk/jormungandr/issues/1034
         self.global_state.spawn(
             async move {
-                let res = block_box.send(BlockMsg::NetworkBlocks(handle)).await;
+                let res = block_box
+                    .send(BlockMsg::NetworkBlocks(Some(node_address), handle))
+                    .await;
                 if let Err(e) = res {
                     tracing::error!(
                         reason = %e,
@@ -2,7 +2,7 @@ use crate::{
     blockcfg::{Block, HeaderHash},
     network::convert::Decode,
     network::{concurrency_limits, keepalive_durations},
-    settings::start::network::{Peer, Protocol},
+    settings::start::network::{Peer, Protocol, Tls},
 };
 use chain_network::data as net_data;
 use chain_network::error as net_error;
@@ -34,36 +34,53 @@ pub type ConnectError = transport::Error;
 
 pub type Client = chain_network::grpc::Client<tonic::transport::Channel>;
 
-pub async fn connect(peer: &Peer) -> Result<Client, ConnectError> {
-    connect_internal(peer, Builder::new()).await
+/// Connects to the peer, over TLS if `tls` is given.
+pub async fn connect(peer: &Peer, tls: Option<&Tls>) -> Result<Client, ConnectError> {
+    connect_internal(peer, tls, Builder::new()).await
 }
 
-pub async fn connect_legacy(peer: &Peer, node_id: legacy::NodeId) -> Result<Client, ConnectError> {
+pub async fn connect_legacy(
+    peer: &Peer,
+    tls: Option<&Tls>,
+    node_id: legacy::NodeId,
+) -> Result<Client, ConnectError> {
     let mut builder = Builder::new();
     builder.legacy_node_id(node_id);
-    connect_internal(peer, builder).await
+    connect_internal(peer, tls, builder).await
 }
 
-async fn connect_internal(peer: &Peer, builder: Builder) -> Result<Client, ConnectError> {
+async fn connect_internal(
+    peer: &Peer,
+    tls: Option<&Tls>,
+    builder: Builder,
+) -> Result<Client, ConnectError> {
     assert!(peer.protocol == Protocol::Grpc);
-    let endpoint = destination_endpoint(peer.connection)
+    let mut endpoint = destination_endpoint(peer.connection, tls.is_some())
         .concurrency_limit(concurrency_limits::CLIENT_REQUESTS)
         .tcp_keepalive(Some(keepalive_durations::TCP))
         .http2_keep_alive_interval(keepalive_durations::HTTP2)
         .timeout(peer.timeout);
+    if let Some(tls) = tls {
+        endpoint = endpoint.tls_config(tls.client_config())?;
+    }
     builder.connect(endpoint).await
 }
 
-fn destination_endpoint(addr: SocketAddr) -> transport::Endpoint {
-    let uri = format!("http://{}", addr);
+fn destination_endpoint(addr: SocketAddr, tls: bool) -> transport::Endpoint {
+    let scheme = if tls { "https" } else { "http" };
+    let uri = format!("{}://{}", scheme, addr);
     transport::Endpoint::try_from(uri).unwrap()
 }
 
 // Fetches a block from a network peer.
 // This function is used during node bootstrap to fetch the genesis block.
-pub async fn fetch_block(peer: &Peer, hash: HeaderHash) -> Result<Block, FetchBlockError> {
+pub async fn fetch_block(
+    peer: &Peer,
+    tls: Option<&Tls>,
+    hash: HeaderHash,
+) -> Result<Block, FetchBlockError> {
     tracing::info!("fetching block {}", hash);
-    let mut client = connect(peer)
+    let mut client = connect(peer, tls)
         .await
         .map_err(|err| FetchBlockError::Connect { source: err })?;
     let block_id = net_data::BlockId::try_from(hash.as_bytes()).unwrap();
@@ -26,9 +26,17 @@ pub async fn run_listen_socket(
             let node_id: grpc::legacy::NodeId = node_id.as_ref().try_into().unwrap();
             builder.legacy_node_id(node_id);
         }
+        let tls = state.config.tls.as_ref().map(|tls| tls.server_config());
         let service = builder.build(NodeService::new(channels, state));
 
-        Server::builder()
+        let mut server = Server::builder();
+        if let Some(tls) = tls {
+            tracing::info!("accepting TLS connections only");
+            server = server
+                .tls_config(tls)
+                .map_err(|cause| ListenError { cause, sockaddr })?;
+        }
+        server
             .concurrency_limit_per_connection(concurrency_limits::SERVER_REQUESTS)
             .tcp_keepalive(Some(keepalive_durations::TCP))
             .add_service(service)
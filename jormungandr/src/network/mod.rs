@@ -48,6 +48,10 @@ mod buffer_sizes {
         // (GetBlocks response or an UploadBlocks request)
         // before the client request task producing them gets preempted.
         pub const BLOCKS: usize = 8;
+
+        // The maximum number of fragments to buffer for an outbound
+        // GetFragments response.
+        pub const FRAGMENTS: usize = 32;
     }
 }
 
@@ -78,14 +82,17 @@ use self::p2p::{comm::Peers, P2pTopology};
 use crate::blockcfg::{Block, HeaderHash};
 use crate::blockchain::{Blockchain as NewBlockchain, Tip};
 use crate::intercom::{BlockMsg, ClientMsg, NetworkMsg, PropagateMsg, TransactionMsg};
-use crate::settings::start::network::{Configuration, Peer, Protocol};
+use crate::settings::start::{
+    config::TrustedCheckpoint,
+    network::{Configuration, Peer, Protocol, Tls},
+};
 use crate::utils::{
     async_msg::{MessageBox, MessageQueue},
     task::TokioServiceInfo,
 };
+use chain_crypto::{Ed25519, PublicKey, SecretKey};
 use chain_network::data::gossip::Gossip;
-use chain_network::data::NodeKeyPair;
-use poldercast::StrikeReason;
+use chain_network::data::{NodeId, NodeKeyPair};
 use rand::seq::SliceRandom;
 use tonic::transport;
 use tracing::{span, Level, Span};
@@ -101,6 +108,9 @@ use std::time::Duration;
 pub use self::bootstrap::Error as BootstrapError;
 use crate::stats_counter::StatsCounter;
 
+// How often the peers known to the node are saved to the storage directory
+const KNOWN_PEERS_SAVE_INTERVAL: Duration = Duration::from_secs(600);
+
 #[derive(Debug)]
 pub struct ListenError {
     cause: transport::Error,
@@ -150,6 +160,9 @@ pub struct GlobalState {
     topology: P2pTopology,
     peers: Peers,
     keypair: NodeKeyPair,
+    node_id: PublicKey<Ed25519>,
+    peers_file: Option<p2p::PeersFile>,
+    known_peers: Vec<p2p::KnownPeer>,
     span: Span,
 }
 
@@ -157,14 +170,22 @@ pub type GlobalStateR = Arc<GlobalState>;
 
 impl GlobalState {
     /// the network global state
+    ///
+    /// The peers known to the node are saved to `peers_file` if given, and
+    /// the `known_peers` loaded from it at startup are the initial gossips
+    /// of the node along with the trusted peers.
     pub fn new(
         block0_hash: HeaderHash,
         config: Configuration,
+        peers_file: Option<p2p::PeersFile>,
+        known_peers: Vec<p2p::KnownPeer>,
         stats_counter: StatsCounter,
         span: Span,
     ) -> Self {
+        let reputation = config.policy.reputation();
         let peers = Peers::new(
             config.max_connections,
+            reputation.clone(),
             span!(parent: &span, Level::TRACE, "peers"),
         );
 
@@ -172,10 +193,18 @@ impl GlobalState {
         rand::thread_rng().fill(&mut rng_seed);
         let mut prng = ChaChaRng::from_seed(rng_seed);
 
-        let keypair = NodeKeyPair::generate(&mut prng);
+        // the key given in the configuration keeps the identity of the node
+        // across restarts
+        let node_key = config
+            .node_key
+            .clone()
+            .unwrap_or_else(|| SecretKey::generate(&mut prng));
+        let node_id = node_key.to_public();
+        let keypair = NodeKeyPair::from(node_key);
 
         let topology = P2pTopology::new(
             &config,
+            reputation,
             span!(parent: &span, Level::TRACE, "sub_task", kind = "poldercast"),
             prng,
         );
@@ -187,10 +216,46 @@ impl GlobalState {
             topology,
             peers,
             keypair,
+            node_id,
+            peers_file,
+            known_peers,
             span,
         }
     }
 
+    /// write the peers known to the node to the peers file, along with the
+    /// peers loaded at startup that have not expired yet
+    pub async fn save_known_peers(&self) -> Result<(), p2p::PersistenceError> {
+        let peers_file = match &self.peers_file {
+            Some(peers_file) => peers_file,
+            None => return Ok(()),
+        };
+        let peers = p2p::merge_known_peers(
+            self.topology.known_peers().await,
+            &self.known_peers,
+            self.config.known_peers_max_age,
+        );
+        peers_file.write(&peers)?;
+        tracing::debug!("{} known peers saved", peers.len());
+        Ok(())
+    }
+
+    /// the public key authenticating the node to its peers
+    pub fn node_id(&self) -> &PublicKey<Ed25519> {
+        &self.node_id
+    }
+
+    /// whether the node with the given id is allowed to authenticate to
+    /// this node
+    fn is_node_allowed(&self, id: &NodeId) -> bool {
+        let allowed = &self.config.allowed_node_ids;
+        allowed.is_empty() || allowed.iter().any(|key| key.as_ref() == id.as_bytes())
+    }
+
+    fn is_private_network(&self) -> bool {
+        self.config.private_network
+    }
+
     pub fn span(&self) -> &Span {
         &self.span
     }
@@ -318,6 +383,18 @@ pub async fn start(service_info: TokioServiceInfo, params: TaskParams) {
         });
     }
 
+    if global_state.peers_file.is_some() {
+        let save_state = global_state.clone();
+        service_info.run_periodic_fallible(
+            "save known peers",
+            KNOWN_PEERS_SAVE_INTERVAL,
+            move || {
+                let state = save_state.clone();
+                async move { state.save_known_peers().await }
+            },
+        );
+    }
+
     let gossip = async {
         let mut gossip_interval = time::interval(global_state.config.gossip_interval);
         loop {
@@ -360,6 +437,12 @@ async fn handle_network_input(
             NetworkMsg::PeerInfo(reply) => {
                 state.peers.infos().map(|infos| reply.reply_ok(infos)).await;
             }
+            NetworkMsg::ReportPeer {
+                node_address,
+                event,
+            } => {
+                state.topology.report_node(node_address, event).await;
+            }
         }
     }
 }
@@ -439,6 +522,20 @@ async fn start_gossiping(state: GlobalStateR, channels: Channels) {
                 })
                 .await;
         }
+        // the peers known before the restart are gossiped about as well,
+        // except the ones that were quarantined
+        for peer in state.known_peers.iter() {
+            if peer.status == p2p::KnownPeerStatus::Quarantined {
+                continue;
+            }
+            topology
+                .accept_gossips(peer.address.clone(), {
+                    let mut builder = poldercast::NodeProfileBuilder::new();
+                    builder.address(peer.address.clone());
+                    p2p::Gossips::from(vec![p2p::Gossip::from(builder.build())])
+                })
+                .await;
+        }
         let view = topology.view(poldercast::Selection::Any).await;
         let peers: Vec<p2p::Address> = view.peers;
         tracing::debug!("sending gossip to {} peers", peers.len());
@@ -536,6 +633,10 @@ fn connect_and_propagate(
                         tracing::info!(reason = %e, "protocol handshake with peer failed");
                         false
                     }
+                    ConnectError::NodeNotAllowed(_) => {
+                        tracing::info!(reason = %e, "peer is outside the private network");
+                        false
+                    }
                     ConnectError::Canceled => {
                         tracing::debug!("connection to peer has been canceled");
                         true
@@ -549,7 +650,7 @@ fn connect_and_propagate(
                     future::join(
                         state
                             .topology
-                            .report_node(node.clone(), StrikeReason::CannotConnect),
+                            .report_node(node.clone(), p2p::PeerEvent::CannotConnect),
                         state.peers.remove_peer(node.clone()),
                     )
                     .await;
@@ -619,8 +720,21 @@ impl BootstrapPeers {
     }
 }
 
+/// the known peers with the given quarantine status, as bootstrap peers
+fn known_bootstrap_peers(known_peers: &[p2p::KnownPeer], quarantined: bool) -> Vec<Peer> {
+    known_peers
+        .iter()
+        .filter(|peer| (peer.status == p2p::KnownPeerStatus::Quarantined) == quarantined)
+        .filter_map(|peer| peer.address.to_socket_addr().map(Peer::new))
+        .collect()
+}
+
 /// Try to get sufficient peers to do a netboot from
-async fn netboot_peers(config: &Configuration, parent_span: &Span) -> BootstrapPeers {
+async fn netboot_peers(
+    config: &Configuration,
+    known_peers: &[p2p::KnownPeer],
+    parent_span: &Span,
+) -> BootstrapPeers {
     let mut peers = BootstrapPeers::new();
 
     // extract the trusted peers from the config
@@ -629,7 +743,8 @@ async fn netboot_peers(config: &Configuration, parent_span: &Span) -> BootstrapP
         .iter()
         .filter_map(|tp| tp.address.to_socket_addr().map(Peer::new))
         .collect::<Vec<_>>();
-    if config.bootstrap_from_trusted_peers {
+    // the peers of a private network do not share their peers
+    if config.bootstrap_from_trusted_peers || config.private_network {
         let _: usize = peers.add_peers(&trusted_peers);
     } else {
         let mut rng = rand::rngs::OsRng;
@@ -643,15 +758,16 @@ async fn netboot_peers(config: &Configuration, parent_span: &Span) -> BootstrapP
                 peer_addr = %tpeer.address().to_string()
             );
             peers = async move {
-                let received_peers = bootstrap::peers_from_trusted_peer(&tpeer)
-                    .await
-                    .unwrap_or_else(|e| {
-                        tracing::warn!(
-                            reason = %e,
-                            "failed to retrieve the list of bootstrap peers from trusted peer"
-                        );
-                        vec![tpeer]
-                    });
+                let received_peers =
+                    bootstrap::peers_from_trusted_peer(&tpeer, config.tls.as_ref())
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!(
+                                reason = %e,
+                                "failed to retrieve the list of bootstrap peers from trusted peer"
+                            );
+                            vec![tpeer]
+                        });
                 let added = peers.add_peers(&received_peers);
                 tracing::info!("adding {} peers from peer", added);
                 peers
@@ -664,11 +780,18 @@ async fn netboot_peers(config: &Configuration, parent_span: &Span) -> BootstrapP
             }
         }
     }
+    // the peers known before the restart keep the node from being isolated
+    // when the trusted peers are down
+    if !config.bootstrap_from_trusted_peers {
+        let added = peers.add_peers(&known_bootstrap_peers(known_peers, false));
+        tracing::debug!("adding {} known peers", added);
+    }
     peers
 }
 
 pub async fn bootstrap(
     config: &Configuration,
+    known_peers: &[p2p::KnownPeer],
     blockchain: NewBlockchain,
     branch: Tip,
     cancellation_token: CancellationToken,
@@ -688,10 +811,15 @@ pub async fn bootstrap(
         return Err(bootstrap::Error::EmptyTrustedPeers);
     }
 
+    let trusted_checkpoint = config.trusted_checkpoint.as_ref();
+    if let Some(checkpoint) = trusted_checkpoint {
+        bootstrap::check_trusted_checkpoint(&blockchain, &branch, checkpoint).await?;
+    }
+
     let mut bootstrapped = false;
 
     let (netboot_peers, _) = match select(
-        netboot_peers(config, span).boxed(),
+        netboot_peers(config, known_peers, span).boxed(),
         cancellation_token.cancelled().boxed(),
     )
     .await
@@ -700,47 +828,65 @@ pub async fn bootstrap(
         Either::Right(((), _)) => return Err(bootstrap::Error::Interrupted),
     };
 
+    let mut pruned_peers = Vec::new();
+
     for peer in netboot_peers.randomly() {
-        let span =
-            span!(parent: span, Level::TRACE, "bootstrap", peer_addr = %peer.address().to_string());
-        let res = bootstrap::bootstrap_from_peer(
+        let attempt = bootstrap_attempt(
             peer,
-            blockchain.clone(),
-            branch.clone(),
-            cancellation_token.clone(),
+            config.tls.as_ref(),
+            &blockchain,
+            &branch,
+            trusted_checkpoint,
+            &cancellation_token,
+            span,
         )
-        .await;
-
-        match res {
-            Err(bootstrap::Error::Connect(e)) => {
-                async move {
-                    tracing::warn!(reason = %e, "unable to reach peer for initial bootstrap");
-                }
-                .instrument(span)
-                .await;
-            }
-            Err(bootstrap::Error::Interrupted) => {
-                async move {
-                    tracing::warn!("the bootstrap process was interrupted");
-                }
-                .instrument(span)
-                .await;
-                return Err(bootstrap::Error::Interrupted);
+        .await?;
+        match attempt {
+            BootstrapAttempt::Completed => {
+                bootstrapped = true;
+                break;
             }
-            Err(e) => {
-                async move {
-                    tracing::warn!(error = ?e, "initial bootstrap failed");
-                }
-                .instrument(span)
-                .await;
+            BootstrapAttempt::PeerPruned => pruned_peers.push(peer),
+            BootstrapAttempt::Failed => {}
+        }
+    }
+
+    // the peers that pruned the blocks we asked for are tried last: the
+    // partial bootstraps from the other peers may have brought our chain
+    // within the range of blocks they still have
+    if !bootstrapped {
+        for peer in pruned_peers {
+            let attempt = bootstrap_attempt(
+                peer,
+                config.tls.as_ref(),
+                &blockchain,
+                &branch,
+                trusted_checkpoint,
+                &cancellation_token,
+                span,
+            )
+            .await?;
+            if let BootstrapAttempt::Completed = attempt {
+                bootstrapped = true;
+                break;
             }
-            Ok(()) => {
-                async move {
-                    tracing::info!("initial bootstrap completed");
-                }
-                .instrument(span)
-                .await;
+        }
+    }
 
+    // the peers that were quarantined before the restart are the last resort
+    if !bootstrapped && !config.bootstrap_from_trusted_peers {
+        for peer in known_bootstrap_peers(known_peers, true) {
+            let attempt = bootstrap_attempt(
+                &peer,
+                config.tls.as_ref(),
+                &blockchain,
+                &branch,
+                trusted_checkpoint,
+                &cancellation_token,
+                span,
+            )
+            .await?;
+            if let BootstrapAttempt::Completed = attempt {
                 bootstrapped = true;
                 break;
             }
@@ -755,6 +901,80 @@ pub async fn bootstrap(
     Ok(bootstrapped)
 }
 
+enum BootstrapAttempt {
+    Completed,
+    PeerPruned,
+    Failed,
+}
+
+async fn bootstrap_attempt(
+    peer: &Peer,
+    tls: Option<&Tls>,
+    blockchain: &NewBlockchain,
+    branch: &Tip,
+    trusted_checkpoint: Option<&TrustedCheckpoint>,
+    cancellation_token: &CancellationToken,
+    span: &Span,
+) -> Result<BootstrapAttempt, bootstrap::Error> {
+    let span =
+        span!(parent: span, Level::TRACE, "bootstrap", peer_addr = %peer.address().to_string());
+    let res = bootstrap::bootstrap_from_peer(
+        peer,
+        tls,
+        blockchain.clone(),
+        branch.clone(),
+        trusted_checkpoint,
+        cancellation_token.clone(),
+    )
+    .await;
+
+    match res {
+        Err(bootstrap::Error::Connect(e)) => {
+            async move {
+                tracing::warn!(reason = %e, "unable to reach peer for initial bootstrap");
+            }
+            .instrument(span)
+            .await;
+            Ok(BootstrapAttempt::Failed)
+        }
+        Err(bootstrap::Error::Interrupted) => {
+            async move {
+                tracing::warn!("the bootstrap process was interrupted");
+            }
+            .instrument(span)
+            .await;
+            Err(bootstrap::Error::Interrupted)
+        }
+        Err(e) if e.is_peer_pruned() => {
+            async move {
+                tracing::info!(
+                    reason = %e,
+                    "the peer has pruned the blocks needed for the initial bootstrap"
+                );
+            }
+            .instrument(span)
+            .await;
+            Ok(BootstrapAttempt::PeerPruned)
+        }
+        Err(e) => {
+            async move {
+                tracing::warn!(error = ?e, "initial bootstrap failed");
+            }
+            .instrument(span)
+            .await;
+            Ok(BootstrapAttempt::Failed)
+        }
+        Ok(()) => {
+            async move {
+                tracing::info!("initial bootstrap completed");
+            }
+            .instrument(span)
+            .await;
+            Ok(BootstrapAttempt::Completed)
+        }
+    }
+}
+
 /// Queries the trusted peers for a block identified with the hash.
 /// The calling thread is blocked until the block is retrieved.
 /// This function is called during blockchain initialization
@@ -778,7 +998,7 @@ pub async fn fetch_block(
         for address in trusted_peers_shuffled(&config) {
             let peer_span = span!(Level::TRACE, "peer_address", address = %address.to_string());
             let peer = Peer::new(address);
-            match grpc::fetch_block(&peer, hash)
+            match grpc::fetch_block(&peer, config.tls.as_ref(), hash)
                 .instrument(peer_span.clone())
                 .await
             {
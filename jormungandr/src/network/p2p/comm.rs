@@ -2,7 +2,11 @@ mod peer_map;
 
 use peer_map::{CommStatus, PeerMap};
 
-use crate::network::{client::ConnectHandle, p2p::Address, security_params::NONCE_LEN};
+use crate::network::{
+    client::ConnectHandle,
+    p2p::{Address, Reputation},
+    security_params::NONCE_LEN,
+};
 use chain_network::data::block::{BlockEvent, ChainPullRequest};
 use chain_network::data::{BlockId, BlockIds, Fragment, Gossip, Header, NodeId};
 use futures::channel::mpsc;
@@ -492,9 +496,9 @@ pub struct Peers {
 }
 
 impl Peers {
-    pub fn new(capacity: usize, span: Span) -> Self {
+    pub fn new(capacity: usize, reputation: Reputation, span: Span) -> Self {
         Peers {
-            mutex: Mutex::new(PeerMap::new(capacity)),
+            mutex: Mutex::new(PeerMap::new(capacity, reputation)),
             span,
         }
     }
@@ -564,6 +568,16 @@ impl Peers {
         .await
     }
 
+    /// the node id the client peer has authenticated with, if any
+    pub async fn get_node_id(&self, peer: Address) -> Option<NodeId> {
+        async move {
+            let mut map = self.inner().await;
+            map.peer_comms(&peer).and_then(|comms| comms.node_id())
+        }
+        .instrument(self.span.clone())
+        .await
+    }
+
     pub async fn set_node_id(&self, peer: Address, id: NodeId) {
         async move {
             tracing::debug!(
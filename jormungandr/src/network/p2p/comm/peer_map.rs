@@ -2,7 +2,7 @@ use crate::network::{
     client::ConnectHandle,
     p2p::{
         comm::{PeerComms, PeerInfo, PeerStats},
-        Address,
+        Address, Reputation,
     },
 };
 use chain_network::data::NodeId;
@@ -11,6 +11,7 @@ use linked_hash_map::LinkedHashMap;
 pub struct PeerMap {
     map: LinkedHashMap<Address, PeerData>,
     capacity: usize,
+    reputation: Reputation,
 }
 
 #[derive(Default)]
@@ -72,10 +73,11 @@ impl<'a> CommStatus<'a> {
 }
 
 impl PeerMap {
-    pub fn new(capacity: usize) -> Self {
+    pub fn new(capacity: usize, reputation: Reputation) -> Self {
         PeerMap {
             map: LinkedHashMap::new(),
             capacity,
+            reputation,
         }
     }
 
@@ -150,20 +152,36 @@ impl PeerMap {
             .collect()
     }
 
+    /// evict the `num` clients with the lowest scores, the least recently
+    /// refreshed first among the clients with the same score
     pub fn evict_clients(&mut self, num: usize) {
-        for entry in self
+        let reputation = &self.reputation;
+        let mut clients: Vec<(f64, Address)> = self
             .map
-            .entries()
-            .filter(|entry| entry.get().comms.has_client_subscriptions())
-            .take(num)
-        {
-            entry.remove();
+            .iter()
+            .filter(|(_, data)| data.comms.has_client_subscriptions())
+            .map(|(id, _)| (reputation.score(id), id.clone()))
+            .collect();
+        clients.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+        for (_, id) in clients.into_iter().take(num) {
+            self.map.remove(&id);
         }
     }
 
+    /// evict the peer with the lowest score if the map is full, the least
+    /// recently refreshed first among the peers with the same score
     fn evict_if_full(&mut self) {
         if self.map.len() >= self.capacity {
-            self.map.pop_front();
+            let reputation = &self.reputation;
+            let worst = self
+                .map
+                .keys()
+                .map(|id| (reputation.score(id), id))
+                .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+                .map(|(_, id)| id.clone());
+            if let Some(id) = worst {
+                self.map.remove(&id);
+            }
         }
     }
 }
@@ -1,11 +1,17 @@
 pub mod comm;
 mod gossip;
 pub mod layers;
+mod persistence;
 mod policy;
+mod reputation;
 mod topology;
 
 pub use self::gossip::{Gossip, Gossips, Peer, Peers};
+pub use self::persistence::{
+    merge_known_peers, Error as PersistenceError, KnownPeer, KnownPeerStatus, PeersFile,
+};
 pub use self::policy::{Policy, PolicyConfig};
+pub use self::reputation::{PeerEvent, Reputation};
 pub use self::topology::P2pTopology;
 
 pub use poldercast::Address;
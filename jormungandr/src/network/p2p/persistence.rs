@@ -0,0 +1,249 @@
+//! Persistence of the known peers across restarts of the node.
+//!
+//! The peers of the view and the available and quarantined nodes of the
+//! topology are written to a file of the storage directory, periodically and
+//! when the node shuts down. The file starts with a versioned header holding
+//! the hash of the block0 of the chain and a checksum of its body, so a file
+//! of another chain or a corrupted file is not loaded.
+//!
+//! The peers read back at startup are additional bootstrap candidates and
+//! initial gossips, so a restarted node can join the network again even if
+//! its trusted peers are down. The peers that were not seen for longer than
+//! `p2p.known_peers_max_age` are dropped.
+
+use crate::{blockcfg::HeaderHash, network::p2p::Address};
+use bincode::Options;
+use chain_core::property::Deserialize as _;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+const PEERS_FILE: &str = "peers";
+const PEERS_MAGIC: &[u8; 8] = b"JORMPEER";
+const PEERS_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while accessing the peers file")]
+    Io(#[from] io::Error),
+    #[error("the file is not a peers file")]
+    InvalidMagic,
+    #[error("unsupported peers file version {0}, expected {}", PEERS_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("the peers file is for the chain of block0 {got}, expected {expected}")]
+    Block0Mismatch {
+        expected: HeaderHash,
+        got: HeaderHash,
+    },
+    #[error("the peers file is corrupted, its checksum does not match")]
+    ChecksumMismatch,
+    #[error("invalid block0 hash in the peers file")]
+    InvalidBlock0(#[source] io::Error),
+    #[error("cannot encode the known peers")]
+    Encoding(#[from] bincode::Error),
+}
+
+/// where a known peer was in the topology when it was saved. The peers
+/// that were in the view are the most useful ones, the quarantined ones
+/// the least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum KnownPeerStatus {
+    View,
+    Available,
+    Quarantined,
+}
+
+/// a peer known to the node, as written to the file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownPeer {
+    pub address: Address,
+    pub status: KnownPeerStatus,
+    /// the last time the node heard of the peer
+    pub last_seen: SystemTime,
+}
+
+impl KnownPeer {
+    fn is_expired(&self, now: SystemTime, max_age: Duration) -> bool {
+        now.duration_since(self.last_seen)
+            .map_or(false, |age| age > max_age)
+    }
+}
+
+/// access to the peers file of a storage directory
+#[derive(Clone)]
+pub struct PeersFile {
+    path: PathBuf,
+    block0: HeaderHash,
+}
+
+impl PeersFile {
+    pub fn new<P: AsRef<Path>>(storage_dir: P, block0: HeaderHash) -> Self {
+        PeersFile {
+            path: storage_dir.as_ref().join(PEERS_FILE),
+            block0,
+        }
+    }
+
+    /// write the known peers to the file
+    ///
+    /// The peers are first written to a temporary file that is then
+    /// renamed, so an interrupted write never leaves a partial file.
+    pub fn write(&self, peers: &[KnownPeer]) -> Result<(), Error> {
+        let body = bincode::options().serialize(peers)?;
+
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+            file.write_all(PEERS_MAGIC)?;
+            file.write_all(&PEERS_VERSION.to_be_bytes())?;
+            file.write_all(self.block0.as_bytes())?;
+            file.write_all(HeaderHash::hash_bytes(&body).as_bytes())?;
+            file.write_all(&body)?;
+            file.flush()?;
+            file.get_ref().sync_all()?;
+        }
+        fs::rename(tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// read the known peers from the file, `None` if there is no file
+    pub fn read(&self) -> Result<Option<Vec<KnownPeer>>, Error> {
+        let mut reader = match fs::File::open(&self.path) {
+            Ok(file) => io::BufReader::new(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != PEERS_MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version != PEERS_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let got = read_hash(&mut reader)?;
+        if got != self.block0 {
+            return Err(Error::Block0Mismatch {
+                expected: self.block0,
+                got,
+            });
+        }
+        let checksum = read_hash(&mut reader)?;
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body)?;
+        if HeaderHash::hash_bytes(&body) != checksum {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        Ok(Some(bincode::options().deserialize(&body)?))
+    }
+
+    /// read the known peers from the file, without the ones not seen for
+    /// longer than `max_age`. A file that cannot be read is ignored.
+    pub fn load(&self, max_age: Duration) -> Vec<KnownPeer> {
+        match self.read() {
+            Ok(Some(peers)) => {
+                let peers = retain_known_peers(peers, max_age, SystemTime::now());
+                tracing::info!("{} known peers loaded", peers.len());
+                peers
+            }
+            Ok(None) => Vec::new(),
+            Err(err) => {
+                tracing::warn!(reason = %err, "cannot load the known peers");
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// the peers of `current` followed by the peers of `previous` that are no
+/// longer in `current`, without the ones not seen for longer than `max_age`
+pub fn merge_known_peers(
+    current: Vec<KnownPeer>,
+    previous: &[KnownPeer],
+    max_age: Duration,
+) -> Vec<KnownPeer> {
+    let in_current: HashSet<_> = current.iter().map(|peer| peer.address.clone()).collect();
+    let previous = previous
+        .iter()
+        .filter(|peer| !in_current.contains(&peer.address))
+        .cloned();
+    let peers = current.into_iter().chain(previous).collect();
+    retain_known_peers(peers, max_age, SystemTime::now())
+}
+
+/// drop the expired peers and keep one entry per address, the peers with the
+/// best status and the most recently seen first
+fn retain_known_peers(
+    mut peers: Vec<KnownPeer>,
+    max_age: Duration,
+    now: SystemTime,
+) -> Vec<KnownPeer> {
+    peers.retain(|peer| !peer.is_expired(now, max_age));
+    peers.sort_by_key(|peer| (peer.status, Reverse(peer.last_seen)));
+    let mut seen = HashSet::new();
+    peers.retain(|peer| seen.insert(peer.address.clone()));
+    peers
+}
+
+fn read_hash<R: Read>(reader: &mut R) -> Result<HeaderHash, Error> {
+    let mut bytes = [0; 32];
+    reader.read_exact(&mut bytes)?;
+    HeaderHash::deserialize(&bytes[..]).map_err(Error::InvalidBlock0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    fn peer(port: u16, status: KnownPeerStatus, age: u64, now: SystemTime) -> KnownPeer {
+        KnownPeer {
+            address: Address::tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, port))),
+            status,
+            last_seen: now - Duration::from_secs(age),
+        }
+    }
+
+    #[test]
+    fn expired_peers_are_dropped() {
+        let now = SystemTime::now();
+        let peers = vec![
+            peer(1, KnownPeerStatus::View, 10, now),
+            peer(2, KnownPeerStatus::Available, 1000, now),
+        ];
+        let peers = retain_known_peers(peers, Duration::from_secs(100), now);
+        assert_eq!(peers, vec![peer(1, KnownPeerStatus::View, 10, now)]);
+    }
+
+    #[test]
+    fn best_status_is_kept() {
+        let now = SystemTime::now();
+        let peers = vec![
+            peer(1, KnownPeerStatus::Quarantined, 10, now),
+            peer(2, KnownPeerStatus::Available, 20, now),
+            peer(1, KnownPeerStatus::View, 50, now),
+            peer(2, KnownPeerStatus::Available, 10, now),
+        ];
+        let peers = retain_known_peers(peers, Duration::from_secs(100), now);
+        assert_eq!(
+            peers,
+            vec![
+                peer(1, KnownPeerStatus::View, 50, now),
+                peer(2, KnownPeerStatus::Available, 10, now),
+            ]
+        );
+    }
+}
@@ -1,5 +1,5 @@
+use crate::network::p2p::Reputation;
 use jormungandr_lib::time::Duration;
-use lru::LruCache;
 use poldercast::{Address, Node, PolicyReport};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -12,27 +12,28 @@ const DEFAULT_QUARANTINE_DURATION: StdDuration = StdDuration::from_secs(10 * 60)
 /// default max quarantine is 2 days
 const DEFAULT_MAX_QUARANTINE_DURATION: StdDuration = StdDuration::from_secs(2 * 24 * 3600);
 
-/// default number of records is 24_000
+/// default number of peer scores kept is 24_000
 const DEFAULT_MAX_NUM_QUARANTINE_RECORDS: usize = 24_000;
 
-/// This is the P2P policy. Right now it is very similar to the default policy
-/// defined in `poldercast` crate.
+/// default half-life of the peer scores is 30min
+const DEFAULT_SCORE_HALF_LIFE: StdDuration = StdDuration::from_secs(30 * 60);
+
+/// default score at or below which a peer is quarantined
+const DEFAULT_QUARANTINE_THRESHOLD: f64 = -30.0;
+
+/// This is the P2P policy. The nodes are quarantined based on their score
+/// in the `Reputation` of the peers.
 ///
 #[derive(Debug)]
 pub struct Policy {
     quarantine_duration: StdDuration,
     max_quarantine: StdDuration,
-    records: LruCache<Address, Records>,
+    quarantine_threshold: f64,
+    reputation: Reputation,
     quarantine_whitelist: HashSet<Address>,
     span: Span,
 }
 
-pub struct Records {
-    /// record the number of time the given node has been quarantined
-    /// in known time.
-    quarantine: u32,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct PolicyConfig {
@@ -43,43 +44,43 @@ pub struct PolicyConfig {
     max_num_quarantine_records: Option<usize>,
     #[serde(default)]
     quarantine_whitelist: HashSet<Address>,
+    #[serde(default)]
+    score_half_life: Option<Duration>,
+    #[serde(default)]
+    quarantine_threshold: Option<f64>,
 }
 
 impl Policy {
-    pub fn new(pc: PolicyConfig, span: Span) -> Self {
+    pub fn new(pc: PolicyConfig, reputation: Reputation, span: Span) -> Self {
         Self {
             quarantine_duration: pc.quarantine_duration.into(),
             max_quarantine: pc
                 .max_quarantine
                 .unwrap_or_else(|| DEFAULT_MAX_QUARANTINE_DURATION.into())
                 .into(),
-            records: LruCache::new(
-                pc.max_num_quarantine_records
-                    .unwrap_or(DEFAULT_MAX_NUM_QUARANTINE_RECORDS),
-            ),
+            quarantine_threshold: pc
+                .quarantine_threshold
+                .unwrap_or(DEFAULT_QUARANTINE_THRESHOLD),
+            reputation,
             quarantine_whitelist: pc.quarantine_whitelist,
             span,
         }
     }
 
-    fn quarantine_duration_for(&mut self, id: Address) -> StdDuration {
-        if let Some(r) = self.records.get_mut(&id) {
-            r.quarantine_for(self.quarantine_duration, self.max_quarantine)
-        } else {
-            let r = Records::new();
-            let t = r.quarantine_for(self.quarantine_duration, self.max_quarantine);
-            self.records.put(id, r);
-            t
-        }
+    fn is_below_threshold(&self, address: &Address) -> bool {
+        self.reputation.score(address) <= self.quarantine_threshold
     }
+}
 
-    fn update(&mut self, id: Address) {
-        if let Some(r) = self.records.get_mut(&id) {
-            r.update();
-        } else {
-            let r = Records::new();
-            self.records.put(id, r);
-        }
+impl PolicyConfig {
+    /// create the store of the peer scores configured by this policy
+    pub fn reputation(&self) -> Reputation {
+        Reputation::new(
+            self.score_half_life
+                .map_or(DEFAULT_SCORE_HALF_LIFE, StdDuration::from),
+            self.max_num_quarantine_records
+                .unwrap_or(DEFAULT_MAX_NUM_QUARANTINE_RECORDS),
+        )
     }
 }
 
@@ -90,33 +91,12 @@ impl Default for PolicyConfig {
             max_quarantine: Some(Duration::from(DEFAULT_MAX_QUARANTINE_DURATION)),
             max_num_quarantine_records: Some(DEFAULT_MAX_NUM_QUARANTINE_RECORDS),
             quarantine_whitelist: HashSet::new(),
+            score_half_life: Some(Duration::from(DEFAULT_SCORE_HALF_LIFE)),
+            quarantine_threshold: Some(DEFAULT_QUARANTINE_THRESHOLD),
         }
     }
 }
 
-impl Records {
-    fn new() -> Records {
-        Self { quarantine: 0 }
-    }
-
-    fn update(&mut self) {
-        self.quarantine += 1;
-    }
-
-    fn quarantine_for(
-        &self,
-        quarantine_instant: StdDuration,
-        max_quarantine: StdDuration,
-    ) -> StdDuration {
-        std::cmp::max(
-            quarantine_instant
-                .checked_mul(self.quarantine)
-                .unwrap_or(max_quarantine),
-            max_quarantine,
-        )
-    }
-}
-
 impl poldercast::Policy for Policy {
     fn check(&mut self, node: &mut Node) -> PolicyReport {
         let id = node.address().to_string();
@@ -126,10 +106,12 @@ impl poldercast::Policy for Policy {
         // if the node is already quarantined
         if let Some(since) = node.logs().quarantined() {
             let duration = since.elapsed().unwrap();
-            let quarantine_duration = self.quarantine_duration_for(node.address().clone());
 
-            if duration < quarantine_duration {
-                // the node still need to do some quarantine time
+            if duration < self.quarantine_duration
+                || (duration < self.max_quarantine && self.is_below_threshold(node_address))
+            {
+                // the node still need to do some quarantine time, until its
+                // score has decayed above the threshold
                 PolicyReport::None
             } else if node.logs().last_update().elapsed().unwrap() < self.quarantine_duration {
                 // the node has been quarantined long enough, check if it has been updated
@@ -147,8 +129,8 @@ impl poldercast::Policy for Policy {
                 tracing::debug!("forgetting about the node");
                 PolicyReport::Forget
             }
-        } else if node.record().is_clear() {
-            // if the record is clear, do nothing, leave the Node in the available nodes
+        } else if !self.is_below_threshold(node_address) {
+            // if the score is good enough, do nothing, leave the Node in the available nodes
             PolicyReport::None
         } else if self.quarantine_whitelist.contains(node_address) {
             // if the node is whitelisted
@@ -158,9 +140,8 @@ impl poldercast::Policy for Policy {
             );
             PolicyReport::None
         } else {
-            // if the record is not `clear` then we quarantine the block for some time
+            // if the score is too low then we quarantine the node for some time
             tracing::debug!("move node to quarantine");
-            self.update(node.address().clone());
             PolicyReport::Quarantine
         }
     }
@@ -0,0 +1,197 @@
+//! reputation of the peers
+//!
+//! Every peer has a score, raised by the useful work it does for the node
+//! and lowered by its misbehaviour. The scores decay towards zero over
+//! time so the peers are judged on their recent behaviour. They drive the
+//! selection of the peers to contact, the eviction of connections and the
+//! quarantine (see the `Policy`).
+
+use lru::LruCache;
+use poldercast::Address;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// the score of the worst behaving peers
+const MIN_SCORE: f64 = -100.0;
+
+/// the score of the best behaving peers. It is kept low so a serious
+/// misbehaviour cannot be compensated by past good behaviour.
+const MAX_SCORE: f64 = 20.0;
+
+/// a behaviour of a peer affecting its score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// the peer sent a block failing the validation
+    InvalidBlock,
+    /// the peer sent a header failing the validation
+    InvalidHeader,
+    /// the peer sent a fragment rejected by the ledger
+    RejectedFragment,
+    /// the peer did not answer a request in time
+    Timeout,
+    /// the node could not connect to the peer
+    CannotConnect,
+    /// the peer was the first to announce a block that became the tip
+    NewBlock,
+}
+
+impl PeerEvent {
+    fn weight(self) -> f64 {
+        match self {
+            PeerEvent::InvalidBlock => -50.0,
+            PeerEvent::InvalidHeader => -30.0,
+            PeerEvent::RejectedFragment => -2.0,
+            PeerEvent::Timeout => -5.0,
+            PeerEvent::CannotConnect => -50.0,
+            PeerEvent::NewBlock => 2.0,
+        }
+    }
+}
+
+/// the scores of the peers, shared between the network tasks
+#[derive(Debug, Clone)]
+pub struct Reputation {
+    inner: Arc<Mutex<Scores>>,
+}
+
+#[derive(Debug)]
+struct Scores {
+    scores: LruCache<Address, Score>,
+    half_life: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Score {
+    value: f64,
+    updated: Instant,
+}
+
+impl Score {
+    /// the value of the score at `now`, halved every `half_life`
+    fn decayed(&self, half_life: Duration, now: Instant) -> f64 {
+        if half_life.as_nanos() == 0 {
+            return 0.0;
+        }
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.value * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+    }
+}
+
+impl Reputation {
+    /// the scores decay by half every `half_life`, and the scores of up to
+    /// `capacity` peers are kept, the least recently updated being forgotten
+    pub fn new(half_life: Duration, capacity: usize) -> Self {
+        Reputation {
+            inner: Arc::new(Mutex::new(Scores {
+                scores: LruCache::new(capacity),
+                half_life,
+            })),
+        }
+    }
+
+    fn inner(&self) -> MutexGuard<'_, Scores> {
+        // the lock is never held across a panic
+        self.inner.lock().unwrap()
+    }
+
+    /// update the score of the peer with the given behaviour, returns the
+    /// new score
+    pub fn record(&self, address: Address, event: PeerEvent) -> f64 {
+        let now = Instant::now();
+        let mut inner = self.inner();
+        let half_life = inner.half_life;
+        let value = inner
+            .scores
+            .get(&address)
+            .map_or(0.0, |score| score.decayed(half_life, now));
+        let value = (value + event.weight()).max(MIN_SCORE).min(MAX_SCORE);
+        inner.scores.put(
+            address,
+            Score {
+                value,
+                updated: now,
+            },
+        );
+        value
+    }
+
+    /// the current score of the peer, 0 for the peers the node knows nothing
+    /// about
+    pub fn score(&self, address: &Address) -> f64 {
+        let inner = self.inner();
+        inner
+            .scores
+            .peek(address)
+            .map_or(0.0, |score| score.decayed(inner.half_life, Instant::now()))
+    }
+
+    /// the current scores of the peers, the best first
+    pub fn scores(&self) -> Vec<(Address, f64)> {
+        let now = Instant::now();
+        let inner = self.inner();
+        let mut scores: Vec<_> = inner
+            .scores
+            .iter()
+            .map(|(address, score)| (address.clone(), score.decayed(inner.half_life, now)))
+            .collect();
+        scores.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+        scores
+    }
+
+    /// sort the addresses by the scores of the peers, the best first. The
+    /// order of the peers with the same score is kept.
+    pub fn sort_by_score(&self, addresses: &mut [Address]) {
+        let now = Instant::now();
+        let inner = self.inner();
+        let score = |address: &Address| {
+            inner
+                .scores
+                .peek(address)
+                .map_or(0.0, |score| score.decayed(inner.half_life, now))
+        };
+        addresses.sort_by(|a, b| score(b).partial_cmp(&score(a)).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    fn address(port: u16) -> Address {
+        Address::tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+    }
+
+    #[test]
+    fn scores_are_bounded() {
+        let reputation = Reputation::new(Duration::from_secs(3600), 16);
+        for _ in 0..100 {
+            reputation.record(address(1), PeerEvent::NewBlock);
+            reputation.record(address(2), PeerEvent::InvalidBlock);
+        }
+        assert!(reputation.score(&address(1)) <= MAX_SCORE);
+        assert!(reputation.score(&address(2)) >= MIN_SCORE);
+    }
+
+    #[test]
+    fn scores_decay() {
+        let reputation = Reputation::new(Duration::from_millis(10), 16);
+        let score = reputation.record(address(1), PeerEvent::InvalidBlock);
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(reputation.score(&address(1)) > score / 2.0);
+    }
+
+    #[test]
+    fn best_peers_first() {
+        let reputation = Reputation::new(Duration::from_secs(3600), 16);
+        reputation.record(address(1), PeerEvent::Timeout);
+        reputation.record(address(3), PeerEvent::NewBlock);
+        let mut addresses = vec![address(1), address(2), address(3)];
+        reputation.sort_by_score(&mut addresses);
+        assert_eq!(addresses, vec![address(3), address(2), address(1)]);
+        let scores: Vec<_> = reputation.scores().into_iter().map(|(a, _)| a).collect();
+        assert_eq!(scores, vec![address(3), address(1)]);
+    }
+}
@@ -2,15 +2,19 @@
 //!
 
 use crate::{
-    network::p2p::{layers::PreferredListLayer, Address, Gossips, Policy, PolicyConfig},
+    network::p2p::{
+        layers::PreferredListLayer, Address, Gossips, KnownPeer, KnownPeerStatus, PeerEvent,
+        Policy, PolicyConfig, Reputation,
+    },
     settings::start::network::Configuration,
 };
 use poldercast::{
     custom_layers,
     poldercast::{Cyclon, Rings, Vicinity},
-    NodeProfile, PolicyReport, StrikeReason, Topology,
+    NodeProfile, PolicyReport, Topology,
 };
 use rand_chacha::ChaChaRng;
+use std::time::SystemTime;
 use tokio::sync::RwLock;
 use tracing::{span, Level, Span};
 
@@ -22,19 +26,22 @@ pub struct View {
 /// object holding the P2pTopology of the Node
 pub struct P2pTopology {
     lock: RwLock<Topology>,
+    reputation: Reputation,
 }
 
 /// Builder object used to initialize the `P2pTopology`
 struct Builder {
     topology: Topology,
+    reputation: Reputation,
     span: Span,
 }
 
 impl Builder {
     /// Create a new topology for the given node profile
-    fn new(node: poldercast::NodeProfile, span: Span) -> Self {
+    fn new(node: poldercast::NodeProfile, reputation: Reputation, span: Span) -> Self {
         Builder {
             topology: Topology::new(node),
+            reputation,
             span,
         }
     }
@@ -42,26 +49,32 @@ impl Builder {
     fn set_policy(mut self, policy: PolicyConfig) -> Self {
         self.topology.set_policy(Policy::new(
             policy,
+            self.reputation.clone(),
             span!(parent: &self.span, Level::TRACE, "sub_task", kind = "policy"),
         ));
         self
     }
 
     /// set all the default poldercast modules (Rings, Vicinity and Cyclon)
-    fn set_poldercast_modules(mut self) -> Self {
+    ///
+    /// The random Cyclon layer is not used in a private network.
+    fn set_poldercast_modules(mut self, config: &Configuration) -> Self {
         self.topology.add_layer(Rings::default());
         self.topology.add_layer(Vicinity::default());
-        self.topology.add_layer(Cyclon::default());
+        if !config.private_network {
+            self.topology.add_layer(Cyclon::default());
+        }
         self
     }
 
     fn set_custom_modules(mut self, config: &Configuration, rng: ChaChaRng) -> Self {
-        if let Some(size) = config.max_unreachable_nodes_to_connect_per_event {
-            self.topology
-                .add_layer(custom_layers::RandomDirectConnections::with_max_view_length(size));
-        } else {
-            self.topology
-                .add_layer(custom_layers::RandomDirectConnections::default());
+        // no random connections in a private network
+        if !config.private_network {
+            let random_connections = match config.max_unreachable_nodes_to_connect_per_event {
+                Some(size) => custom_layers::RandomDirectConnections::with_max_view_length(size),
+                None => custom_layers::RandomDirectConnections::default(),
+            };
+            self.topology.add_layer(random_connections);
         }
 
         self.topology.add_layer(PreferredListLayer::new(
@@ -75,24 +88,27 @@ impl Builder {
     fn build(self) -> P2pTopology {
         P2pTopology {
             lock: RwLock::new(self.topology),
+            reputation: self.reputation,
         }
     }
 }
 
 impl P2pTopology {
-    pub fn new(config: &Configuration, span: Span, rng: ChaChaRng) -> Self {
-        Builder::new(config.profile.clone(), span)
-            .set_poldercast_modules()
+    pub fn new(config: &Configuration, reputation: Reputation, span: Span, rng: ChaChaRng) -> Self {
+        Builder::new(config.profile.clone(), reputation, span)
+            .set_poldercast_modules(config)
             .set_custom_modules(&config, rng)
             .set_policy(config.policy.clone())
             .build()
     }
 
     /// Returns a list of neighbors selected in this turn
-    /// to contact for event dissemination.
+    /// to contact for event dissemination, the peers with the best
+    /// scores first.
     pub async fn view(&self, selection: poldercast::Selection) -> View {
         let mut topology = self.lock.write().await;
-        let peers = topology.view(None, selection).into_iter().collect();
+        let mut peers: Vec<Address> = topology.view(None, selection).into_iter().collect();
+        self.reputation.sort_by_score(&mut peers);
         View {
             self_node: topology.profile().clone(),
             peers,
@@ -159,14 +175,58 @@ impl P2pTopology {
         topology.nodes().node_count()
     }
 
-    /// register a strike against the given node id
+    /// the peers of the view and the available and quarantined nodes, to
+    /// be saved across restarts
+    pub async fn known_peers(&self) -> Vec<KnownPeer> {
+        let now = SystemTime::now();
+        let mut topology = self.lock.write().await;
+        let mut peers: Vec<KnownPeer> = topology
+            .view(None, poldercast::Selection::Any)
+            .into_iter()
+            .map(|address| KnownPeer {
+                address,
+                status: KnownPeerStatus::View,
+                last_seen: now,
+            })
+            .collect();
+        let nodes = topology.nodes();
+        peers.extend(known_nodes(
+            nodes.all_available_nodes(),
+            KnownPeerStatus::Available,
+        ));
+        peers.extend(known_nodes(
+            nodes.all_quarantined_nodes(),
+            KnownPeerStatus::Quarantined,
+        ));
+        peers
+    }
+
+    /// the current scores of the peers, the best first
+    pub fn list_scores(&self) -> Vec<(Address, f64)> {
+        self.reputation.scores()
+    }
+
+    /// update the score of the given node with its behaviour, and let
+    /// the policy check it against its new score
     ///
     /// the function returns `None` if the node was not even in the
     /// the topology (not even quarantined).
-    pub async fn report_node(&self, address: Address, issue: StrikeReason) -> Option<PolicyReport> {
+    pub async fn report_node(&self, address: Address, event: PeerEvent) -> Option<PolicyReport> {
+        let score = self.reputation.record(address.clone(), event);
+        tracing::debug!(peer = %address, ?event, score, "peer score updated");
         let mut topology = self.lock.write().await;
-        topology.update_node(address, |node| {
-            node.record_mut().strike(issue);
-        })
+        topology.update_node(address, |_| {})
     }
 }
+
+fn known_nodes<'a, I>(nodes: I, status: KnownPeerStatus) -> impl Iterator<Item = KnownPeer> + 'a
+where
+    I: IntoIterator<Item = &'a poldercast::Node>,
+    I::IntoIter: 'a,
+{
+    nodes.into_iter().map(move |node| KnownPeer {
+        address: node.address().clone(),
+        status,
+        last_seen: node.logs().last_update(),
+    })
+}
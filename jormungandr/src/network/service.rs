@@ -6,7 +6,7 @@ use super::{
     subscription, Channels, GlobalStateR,
 };
 use crate::blockcfg as app_data;
-use crate::intercom::{self, BlockMsg, ClientMsg};
+use crate::intercom::{self, BlockMsg, ClientMsg, TransactionMsg};
 use crate::utils::async_msg::MessageBox;
 use chain_network::core::server::{BlockService, FragmentService, GossipService, Node, PushStream};
 use chain_network::data::p2p::{AuthenticatedNodeId, Peer, Peers};
@@ -49,6 +49,29 @@ impl NodeService {
     fn subscription_span(&self, subscriber: Peer, stream_name: &'static str) -> Span {
         span!(parent: self.span(), Level::TRACE, "NodeService", peer = %subscriber.to_string(), stream = %stream_name)
     }
+
+    /// In a private network, only the allowed nodes that have authenticated
+    /// can subscribe.
+    async fn check_subscriber(&self, subscriber: &Address) -> Result<(), Error> {
+        if !self.global_state.is_private_network() {
+            return Ok(());
+        }
+        match self
+            .global_state
+            .peers
+            .get_node_id(subscriber.clone())
+            .await
+        {
+            Some(id) if self.global_state.is_node_allowed(&id) => Ok(()),
+            _ => {
+                tracing::info!(peer = %subscriber, "refusing subscription of a node outside the private network");
+                Err(Error::new(
+                    ErrorCode::PermissionDenied,
+                    "the node is not allowed to subscribe, perform ClientAuth with an allowed node id",
+                ))
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -82,7 +105,15 @@ impl Node for NodeService {
             )
         })?;
         auth.verify(&nonce[..])?;
-        self.global_state.peers.set_node_id(addr, auth.into()).await;
+        let id = auth.into();
+        if !self.global_state.is_node_allowed(&id) {
+            tracing::info!(peer = %addr, node_id = ?id, "refusing authentication of a node that is not allowed");
+            return Err(Error::new(
+                ErrorCode::PermissionDenied,
+                "the node id is not allowed",
+            ));
+        }
+        self.global_state.peers.set_node_id(addr, id).await;
         Ok(())
     }
 
@@ -235,7 +266,7 @@ impl BlockService for NodeService {
         let (handle, sink, reply) = intercom::stream_request(buffer_sizes::inbound::BLOCKS);
         let reply = reply.instrument(span.clone());
         let block_box = self.channels.block_box.clone();
-        send_message(block_box, BlockMsg::NetworkBlocks(handle))
+        send_message(block_box, BlockMsg::NetworkBlocks(None, handle))
             .instrument(span)
             .await?;
         try_join!(
@@ -255,6 +286,9 @@ impl BlockService for NodeService {
         let addr = subscriber.addr();
         let span = self.subscription_span(subscriber, "block_events");
         let subscriber = Address::tcp(addr);
+        self.check_subscriber(&subscriber)
+            .instrument(span.clone())
+            .await?;
 
         self.global_state.spawn(
             subscription::process_block_announcements(
@@ -281,8 +315,17 @@ impl FragmentService for NodeService {
     type GetFragmentsStream = ResponseStream<app_data::Fragment>;
     type SubscriptionStream = SubscriptionStream<FragmentSubscription>;
 
-    async fn get_fragments(&self, _ids: FragmentIds) -> Result<Self::GetFragmentsStream, Error> {
-        Err(Error::unimplemented())
+    async fn get_fragments(&self, ids: FragmentIds) -> Result<Self::GetFragmentsStream, Error> {
+        let ids = ids.decode()?;
+        let span = span!(Level::TRACE, "request", kind = "GetFragments");
+        let (handle, future) = intercom::stream_reply(buffer_sizes::outbound::FRAGMENTS);
+        let future = future.instrument(span.clone());
+        let transaction_box = self.channels.transaction_box.clone();
+        send_message(transaction_box, TransactionMsg::GetFragments(ids, handle))
+            .instrument(span)
+            .await?;
+        let stream = future.await?;
+        Ok(convert::response_stream(stream))
     }
 
     async fn fragment_subscription(
@@ -293,6 +336,9 @@ impl FragmentService for NodeService {
         let addr = subscriber.addr();
         let parent_span = self.subscription_span(subscriber, "fragments");
         let subscriber = Address::tcp(addr);
+        self.check_subscriber(&subscriber)
+            .instrument(parent_span.clone())
+            .await?;
         let span = span!(
             parent: parent_span,
             Level::TRACE,
@@ -331,6 +377,9 @@ impl GossipService for NodeService {
         let addr = subscriber.addr();
         let parent_span = self.subscription_span(subscriber, "gossip");
         let subscriber = Address::tcp(addr);
+        self.check_subscriber(&subscriber)
+            .instrument(parent_span.clone())
+            .await?;
         let span = span!(
             parent: parent_span,
             Level::TRACE,
@@ -357,6 +406,14 @@ impl GossipService for NodeService {
     }
 
     async fn peers(&self, limit: u32) -> Result<Peers, Error> {
+        // the requester is not authenticated, the peers of a private network
+        // are not shared with it
+        if self.global_state.is_private_network() {
+            return Err(Error::new(
+                ErrorCode::PermissionDenied,
+                "the peers of a private network are not shared",
+            ));
+        }
         let topology = &self.global_state.topology;
         let view = topology.view(poldercast::Selection::Any).await;
         let mut peers = Vec::new();
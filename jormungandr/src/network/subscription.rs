@@ -12,7 +12,6 @@ use crate::{
 };
 use chain_network::data as net_data;
 use chain_network::error::{Code, Error};
-use jormungandr_lib::interfaces::FragmentOrigin;
 
 use futures::future::BoxFuture;
 use futures::prelude::*;
@@ -340,8 +339,8 @@ impl FragmentProcessor {
             Vec::with_capacity(buffer_sizes::inbound::FRAGMENTS),
         );
         self.mbox
-            .start_send(TransactionMsg::SendTransaction(
-                FragmentOrigin::Network,
+            .start_send(TransactionMsg::SendPeerTransactions(
+                self.node_id.clone(),
                 fragments,
             ))
             .map_err(|e| {
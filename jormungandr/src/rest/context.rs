@@ -3,7 +3,8 @@ use std::sync::Arc;
 use crate::{
     blockchain::{Blockchain, Tip},
     diagnostic::Diagnostic,
-    intercom::{NetworkMsg, TransactionMsg},
+    fragment::selection::FragmentSelectionAlgorithmParams,
+    intercom::{BlockMsg, NetworkMsg, TransactionMsg},
     leadership::Logs as LeadershipLogs,
     network::GlobalStateR as NetworkStateR,
     rest::ServerStopper,
@@ -141,8 +142,11 @@ pub struct FullContext {
     pub stats_counter: StatsCounter,
     pub network_task: MessageBox<NetworkMsg>,
     pub transaction_task: MessageBox<TransactionMsg>,
+    pub block_task: MessageBox<BlockMsg>,
     pub leadership_logs: LeadershipLogs,
     pub enclave: Enclave,
+    pub fragment_selection: FragmentSelectionAlgorithmParams,
+    pub accept_update_proposals: bool,
     pub network_state: NetworkStateR,
     pub explorer: Option<crate::explorer::Explorer>,
 }
@@ -30,8 +30,8 @@ pub async fn start_rest_server(config: Rest, explorer_enabled: bool, context: Co
         .await
         .set_server_stopper(ServerStopper(stopper_tx));
 
-    let api =
-        warp::path!("api" / ..).and(v0::filter(context.clone()).or(v1::filter(context.clone())));
+    let api = warp::path!("api" / ..)
+        .and(v0::filter(context.clone(), config.admin.clone()).or(v1::filter(context.clone())));
     if explorer_enabled {
         let explorer = explorer::filter(context);
         setup_cors(api.or(explorer), config, stopper_rx).await;
@@ -2,6 +2,7 @@ use crate::{
     rest::{v0::logic, ContextLock},
     secure::NodeSecret,
 };
+use jormungandr_lib::interfaces::RestAdmin;
 use warp::{reject::Reject, Rejection, Reply};
 
 impl Reject for logic::Error {}
@@ -41,6 +42,29 @@ pub async fn get_tip(context: ContextLock) -> Result<impl Reply, Rejection> {
     logic::get_tip(&context).await.map_err(warp::reject::custom)
 }
 
+pub async fn rollback_tip(
+    block_id_hex: String,
+    authorization: Option<String>,
+    admin: Option<RestAdmin>,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    // the administration endpoints do not exist unless they are configured
+    let admin = admin.ok_or_else(warp::reject::not_found)?;
+    logic::check_api_token(&admin, authorization.as_deref()).map_err(warp::reject::custom)?;
+    let context = context.read().await;
+    logic::rollback_tip(&context, &block_id_hex, admin.max_rollback_depth)
+        .await
+        .map_err(warp::reject::custom)
+}
+
+pub async fn get_branches(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_branches(&context)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 pub async fn get_stats_counter(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_stats_counter(&context)
@@ -246,6 +270,14 @@ pub async fn get_network_p2p_available(context: ContextLock) -> Result<impl Repl
         .map_err(warp::reject::custom)
 }
 
+pub async fn get_network_p2p_scores(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_network_p2p_scores(&context)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 pub async fn get_network_p2p_view(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_network_p2p_view(&context)
@@ -273,6 +305,14 @@ pub async fn get_committees(context: ContextLock) -> Result<impl Reply, Rejectio
         .map_err(warp::reject::custom)
 }
 
+pub async fn get_update_proposals(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_update_proposals(&context)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 pub async fn get_active_vote_plans(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_active_vote_plans(&context)
@@ -7,9 +7,9 @@
 //   they are 400 or 500.
 
 use crate::{
-    blockchain::StorageError,
+    blockchain::{Ref, StorageError},
     diagnostic::Diagnostic,
-    intercom::{self, NetworkMsg, TransactionMsg},
+    intercom::{self, BlockMsg, NetworkMsg, TransactionMsg},
     rest::Context,
     secure::NodeSecret,
 };
@@ -31,15 +31,17 @@ use chain_impl_mockchain::{
 };
 use jormungandr_lib::{
     interfaces::{
-        AccountState, EnclaveLeaderId, EpochRewardsInfo, FragmentLog, FragmentOrigin,
-        LeadershipLog, NodeStats, NodeStatsDto, PeerStats, Rewards as StakePoolRewards,
-        SettingsDto, StakeDistribution, StakeDistributionDto, StakePoolStats, TaxTypeSerde,
-        TransactionOutput, VotePlanStatus,
+        AccountState, BranchInfo, ConsensusLeaderId, EnclaveLeaderId, EpochRewardsInfo, ForkPoint,
+        FragmentLog, FragmentOrigin, LeadershipLog, NodeStats, NodeStatsDto, PeerScore, PeerStats,
+        ProposalChanges, ProposalChangesError, RestAdmin, Rewards as StakePoolRewards, SettingsDto,
+        StakeDistribution, StakeDistributionDto, StakePoolStats, TaxTypeSerde, TransactionOutput,
+        UpdateProposalStatus, VotePlanStatus,
     },
     time::SystemTime,
 };
+use subtle::ConstantTimeEq;
 
-use std::sync::Arc;
+use std::{convert::TryFrom, sync::Arc};
 
 use futures::{channel::mpsc::SendError, channel::mpsc::TrySendError, prelude::*};
 use tracing::{span, Level};
@@ -76,6 +78,12 @@ pub enum Error {
     InvalidTopic,
     #[error(transparent)]
     Hex(#[from] hex::FromHexError),
+    #[error("Missing or invalid API token")]
+    Unauthorized,
+    #[error("Rollback of the tip failed")]
+    Rollback(#[source] intercom::Error),
+    #[error("Cannot decode the changes of an update proposal")]
+    UpdateProposal(#[from] ProposalChangesError),
 }
 
 fn parse_account_id(id_hex: &str) -> Result<Identifier, Error> {
@@ -141,6 +149,40 @@ pub async fn get_tip(context: &Context) -> Result<String, Error> {
     Ok(context.blockchain_tip()?.get_ref().await.hash().to_string())
 }
 
+/// check the bearer token given in the `Authorization` header of a request
+/// to the administration endpoints
+pub fn check_api_token(admin: &RestAdmin, authorization: Option<&str>) -> Result<(), Error> {
+    // the comparison takes the same time wherever the tokens differ, so the
+    // token cannot be guessed from the response times
+    match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(token) if bool::from(token.as_bytes().ct_eq(admin.api_token.as_bytes())) => Ok(()),
+        _ => Err(Error::Unauthorized),
+    }
+}
+
+pub async fn rollback_tip(
+    context: &Context,
+    block_id_hex: &str,
+    max_depth: u32,
+) -> Result<String, Error> {
+    let target = parse_block_hash(block_id_hex)?;
+    let span = span!(parent: context.span()?, Level::TRACE, "rollback_tip");
+    async move {
+        let (reply_handle, reply_future) = intercom::unary_reply();
+        let mut mbox = context.try_full()?.block_task.clone();
+        mbox.send(BlockMsg::RollbackTip(target, max_depth, reply_handle))
+            .await
+            .map_err(|e| {
+                tracing::debug!(reason = %e, "error sending the tip rollback request");
+                Error::MsgSendError(e)
+            })?;
+        let new_tip = reply_future.await.map_err(Error::Rollback)?;
+        Ok(new_tip.to_string())
+    }
+    .instrument(span)
+    .await
+}
+
 pub async fn get_stats_counter(context: &Context) -> Result<NodeStatsDto, Error> {
     let stats = create_stats(&context).await?;
     Ok(NodeStatsDto {
@@ -219,6 +261,7 @@ async fn create_stats(context: &Context) -> Result<Option<NodeStats>, Error> {
     let stats = &full_context.stats_counter;
     let node_stats = NodeStats {
         block_recv_cnt: stats.block_recv_cnt(),
+        deep_fork_rejected_cnt: stats.deep_fork_rejected_cnt(),
         last_block_content_size: tip_header.block_content_size(),
         last_block_date: tip_header.block_date().to_string().into(),
         last_block_fees: block_fee_sum.0,
@@ -228,17 +271,83 @@ async fn create_stats(context: &Context) -> Result<Option<NodeStats>, Error> {
         last_block_time: SystemTime::from(tip.time()).into(),
         last_block_tx: block_tx_count,
         last_received_block_time: stats.slot_start_time().map(SystemTime::from),
+        node_id: full_context.network_state.node_id().to_bech32_str(),
         peer_available_cnt: nodes_count.available_count,
         peer_connected_cnt: stats.peer_connected_cnt(),
         peer_quarantined_cnt: nodes_count.quarantined_count,
         peer_total_cnt: nodes_count.all_count,
         peer_unreachable_cnt: nodes_count.not_reachable_count,
+        reorg_cnt: stats.reorg_cnt(),
+        reorg_depths: stats.reorg_depths(),
         tx_recv_cnt: stats.tx_recv_cnt(),
         uptime: stats.uptime_sec().into(),
     };
     Ok(Some(node_stats))
 }
 
+pub async fn get_branches(context: &Context) -> Result<Vec<BranchInfo>, Error> {
+    let blockchain = context.blockchain()?;
+    let tip = context.blockchain_tip()?;
+    let tip_ref = tip.get_ref().await;
+    let tip_chain_length = u32::from(tip_ref.chain_length());
+
+    let main_fork_point = ForkPoint {
+        block: tip_ref.hash().into(),
+        chain_length: tip_chain_length,
+        depth: 0,
+    };
+    let mut branches = vec![branch_info(
+        &tip_ref,
+        true,
+        Some(main_fork_point),
+        tip.branch().last_updated().await,
+    )];
+
+    for (branch_ref, last_updated) in blockchain.branches().branches_last_updated().await {
+        if branch_ref.hash() == tip_ref.hash() {
+            continue;
+        }
+        let fork_point = blockchain
+            .storage()
+            .find_common_ancestor(
+                (tip_ref.hash(), tip_chain_length),
+                (branch_ref.hash(), u32::from(branch_ref.chain_length())),
+            )
+            .map(|(block, chain_length)| ForkPoint {
+                block: block.into(),
+                chain_length,
+                depth: tip_chain_length - chain_length,
+            })
+            .map_err(|e| {
+                tracing::debug!(
+                    reason = %e,
+                    "cannot find the fork point of branch {}",
+                    branch_ref.hash()
+                )
+            })
+            .ok();
+        branches.push(branch_info(&branch_ref, false, fork_point, last_updated));
+    }
+
+    Ok(branches)
+}
+
+fn branch_info(
+    branch_ref: &Ref,
+    main: bool,
+    fork_point: Option<ForkPoint>,
+    last_updated: std::time::SystemTime,
+) -> BranchInfo {
+    BranchInfo {
+        tip: branch_ref.hash().into(),
+        chain_length: u32::from(branch_ref.chain_length()),
+        block_date: branch_ref.block_date().into(),
+        main,
+        fork_point,
+        last_updated: last_updated.into(),
+    }
+}
+
 pub async fn get_block_id(context: &Context, block_id_hex: &str) -> Result<Option<Vec<u8>>, Error> {
     context
         .blockchain()?
@@ -599,6 +708,20 @@ pub async fn get_network_p2p_available(context: &Context) -> Result<Vec<polderca
         .await)
 }
 
+pub async fn get_network_p2p_scores(context: &Context) -> Result<Vec<PeerScore>, Error> {
+    Ok(context
+        .try_full()?
+        .network_state
+        .topology()
+        .list_scores()
+        .into_iter()
+        .map(|(address, score)| PeerScore {
+            address: address.to_string(),
+            score,
+        })
+        .collect())
+}
+
 pub async fn get_network_p2p_view(context: &Context) -> Result<Vec<poldercast::Address>, Error> {
     Ok(context
         .try_full()?
@@ -658,3 +781,28 @@ pub async fn get_active_vote_plans(context: &Context) -> Result<Vec<VotePlanStat
         .collect();
     Ok(vp)
 }
+
+pub async fn get_update_proposals(context: &Context) -> Result<Vec<UpdateProposalStatus>, Error> {
+    let span =
+        span!(parent: context.span()?, Level::TRACE, "request", request = "update_proposals");
+    async move {
+        let ledger = context.blockchain_tip()?.get_ref().await.ledger();
+        let mut proposals = ledger
+            .updates()
+            .proposals
+            .iter()
+            .map(|(id, state)| {
+                Ok(UpdateProposalStatus {
+                    proposal_id: (*id).into(),
+                    proposal_date: state.proposal_date.into(),
+                    changes: ProposalChanges::try_from(state.proposal.changes.clone())?,
+                    votes: state.votes.iter().cloned().map(ConsensusLeaderId).collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        proposals.sort_by_key(|proposal| proposal.proposal_date);
+        Ok(proposals)
+    }
+    .instrument(span)
+    .await
+}
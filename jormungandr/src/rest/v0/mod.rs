@@ -2,13 +2,17 @@ mod handlers;
 pub mod logic;
 
 use crate::rest::{display_internal_server_error, ContextLock};
+use chain_network::error::Code;
+use jormungandr_lib::interfaces::RestAdmin;
 
 use warp::{http::StatusCode, Filter, Rejection, Reply};
 
 pub fn filter(
     context: ContextLock,
+    admin: Option<RestAdmin>,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let with_context = warp::any().map(move || context.clone());
+    let with_admin = warp::any().map(move || admin.clone());
     let root = warp::path!("v0" / ..);
 
     let shutdown = warp::path!("shutdown")
@@ -42,6 +46,12 @@ pub fn filter(
         root.and(get.or(get_next)).boxed()
     };
 
+    let branches = warp::path!("branches")
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(handlers::get_branches)
+        .boxed();
+
     let fragment = {
         let root = warp::path!("fragment" / ..).boxed();
 
@@ -106,6 +116,12 @@ pub fn filter(
             .and_then(handlers::get_network_p2p_available)
             .boxed();
 
+        let scores = warp::path!("scores")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_network_p2p_scores)
+            .boxed();
+
         let view = {
             let root = warp::path!("view" / ..);
 
@@ -124,7 +140,7 @@ pub fn filter(
             root.and(view.or(view_topic)).boxed()
         };
 
-        root.and(quarantined.or(non_public).or(available).or(view))
+        root.and(quarantined.or(non_public).or(available).or(scores).or(view))
             .boxed()
     };
 
@@ -189,11 +205,25 @@ pub fn filter(
         .and_then(handlers::get_stats_counter)
         .boxed();
 
-    let tip = warp::path!("tip")
-        .and(warp::get())
-        .and(with_context.clone())
-        .and_then(handlers::get_tip)
-        .boxed();
+    let tip = {
+        let root = warp::path!("tip" / ..);
+
+        let get = warp::path::end()
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_tip)
+            .boxed();
+
+        let rollback = warp::path!("rollback" / String)
+            .and(warp::post())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(with_admin)
+            .and(with_context.clone())
+            .and_then(handlers::rollback_tip)
+            .boxed();
+
+        root.and(get.or(rollback)).boxed()
+    };
 
     let rewards = {
         let root = warp::path!("rewards" / ..);
@@ -235,15 +265,22 @@ pub fn filter(
 
         let vote_plans = warp::path!("plans")
             .and(warp::get())
-            .and(with_context)
+            .and(with_context.clone())
             .and_then(handlers::get_active_vote_plans)
             .boxed();
         root.and(committees.or(vote_plans)).boxed()
     };
 
+    let update_proposals = warp::path!("update" / "proposals")
+        .and(warp::get())
+        .and(with_context)
+        .and_then(handlers::get_update_proposals)
+        .boxed();
+
     let routes = shutdown
         .or(account)
         .or(block)
+        .or(branches)
         .or(fragment)
         .or(leaders)
         .or(network)
@@ -258,6 +295,7 @@ pub fn filter(
         .or(utxo)
         .or(diagnostic)
         .or(votes)
+        .or(update_proposals)
         .boxed();
 
     root.and(routes).recover(handle_rejection).boxed()
@@ -270,6 +308,15 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
             logic::Error::PublicKey(_) | logic::Error::Hash(_) | logic::Error::Hex(_) => {
                 (err.to_string(), StatusCode::BAD_REQUEST)
             }
+            logic::Error::Unauthorized => (err.to_string(), StatusCode::UNAUTHORIZED),
+            logic::Error::Rollback(e)
+                if matches!(
+                    e.code(),
+                    Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition
+                ) =>
+            {
+                (e.to_string(), StatusCode::BAD_REQUEST)
+            }
             err => (
                 display_internal_server_error(err),
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -1,6 +1,13 @@
 use crate::rest::{v1::logic, ContextLock};
+use futures::prelude::*;
+use jormungandr_lib::{
+    interfaces::{FeeEstimateRequest, FragmentType},
+    time::Duration,
+};
 use warp::{reject::Reject, Rejection, Reply};
 
+use std::convert::Infallible;
+
 impl Reject for logic::Error {}
 
 pub async fn post_fragments(
@@ -14,6 +21,28 @@ pub async fn post_fragments(
         .map_err(warp::reject::custom)
 }
 
+pub async fn validate_fragments(
+    fragments: Vec<String>,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::validate_fragments(&context, fragments)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
+pub async fn estimate_fee(
+    request: FeeEstimateRequest,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::estimate_fee(&context, request)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 #[derive(Deserialize)]
 pub struct GetMessageStatusesQuery {
     fragment_ids: String,
@@ -42,3 +71,61 @@ pub async fn get_fragments_logs(context: ContextLock) -> Result<impl Reply, Reje
         .map_err(warp::reject::custom)
         .map(|r| warp::reply::json(&r))
 }
+
+#[derive(Deserialize)]
+pub struct GetPendingFragmentsQuery {
+    #[serde(rename = "type")]
+    fragment_type: Option<FragmentType>,
+    account: Option<String>,
+    min_age: Option<Duration>,
+    max_age: Option<Duration>,
+}
+
+pub async fn get_pending_fragments(
+    query: GetPendingFragmentsQuery,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_pending_fragments(
+        &context,
+        query.fragment_type,
+        query.account,
+        query.min_age,
+        query.max_age,
+    )
+    .await
+    .map_err(warp::reject::custom)
+    .map(|r| warp::reply::json(&r))
+}
+
+pub async fn get_pending_fragment(
+    fragment_id_hex: String,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_pending_fragment(&context, &fragment_id_hex)
+        .await
+        .map_err(warp::reject::custom)?
+        .ok_or_else(warp::reject::not_found)
+}
+
+/// stream the changes of the tip as server-sent events, the sequence number
+/// of every event being its id. A client reconnecting with the id of the last
+/// event it received in the `Last-Event-ID` header gets the events it missed
+/// first, if the node still has them, or a `resync` event otherwise.
+pub async fn get_tip_events(
+    last_event_id: Option<u64>,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    let events = logic::subscribe_tip_events(&context, last_event_id)
+        .map_err(warp::reject::custom)?
+        .map(|event| {
+            Ok::<_, Infallible>((
+                warp::sse::id(event.sequence),
+                warp::sse::event(event.kind.name()),
+                warp::sse::json(event),
+            ))
+        });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)))
+}
@@ -1,17 +1,31 @@
 use crate::{
     blockchain::StorageError,
+    fragment::{self, selection::FragmentSelectionAlgorithmParams, TipLedger},
     intercom::{self, TransactionMsg},
     rest::Context,
 };
-use chain_core::property::{Deserialize, Fragment as _};
-use chain_crypto::{digest::Error as DigestError, hash::Error as HashError, PublicKeyFromStrError};
+use chain_core::property::{Deserialize, Fragment as _, Serialize};
+use chain_crypto::{
+    digest::Error as DigestError, hash::Error as HashError, PublicKey, PublicKeyFromStrError,
+};
 use chain_impl_mockchain::{
+    account::{AccountAlg, Identifier},
+    fee::FeeAlgorithm,
     fragment::{Fragment, FragmentId},
+    transaction::UnspecifiedAccountIdentifier,
     value::ValueError,
 };
-use futures::{channel::mpsc::SendError, channel::mpsc::TrySendError, prelude::*};
-use jormungandr_lib::interfaces::{FragmentLog, FragmentOrigin, FragmentStatus};
+use futures::{channel::mpsc::SendError, channel::mpsc::TrySendError, prelude::*, stream};
+use jormungandr_lib::{
+    interfaces::{
+        CertificateType, FeeEstimate, FeeEstimateRequest, FragmentLog, FragmentOrigin,
+        FragmentStatus, FragmentType, FragmentValidation, FragmentValidationStatus,
+        PendingFragment, TipEvent, TransactionInputType,
+    },
+    time::{Duration, SystemTime},
+};
 use std::{collections::HashMap, str::FromStr};
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
@@ -27,6 +41,8 @@ pub enum Error {
     #[error(transparent)]
     Deserialize(std::io::Error),
     #[error(transparent)]
+    Serialize(std::io::Error),
+    #[error(transparent)]
     TxMsgSendError(#[from] TrySendError<TransactionMsg>),
     #[error(transparent)]
     MsgSendError(#[from] SendError),
@@ -40,6 +56,8 @@ pub enum Error {
     Storage(#[from] StorageError),
     #[error(transparent)]
     Hex(#[from] hex::FromHexError),
+    #[error("a {0:?} certificate is not carried by a transaction")]
+    NoTransactionFee(CertificateType),
 }
 
 pub async fn get_fragments_statuses(
@@ -75,17 +93,21 @@ pub async fn get_fragments_statuses(
     .await
 }
 
-pub async fn post_fragments(
-    context: &Context,
-    messages: Vec<String>,
-) -> Result<Vec<String>, Error> {
-    let fragments = messages
+fn decode_fragments(messages: Vec<String>) -> Result<Vec<Fragment>, Error> {
+    messages
         .into_iter()
         .map(|message| {
             let message = hex::decode(message)?;
             Fragment::deserialize(message.as_slice()).map_err(Error::Deserialize)
         })
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect()
+}
+
+pub async fn post_fragments(
+    context: &Context,
+    messages: Vec<String>,
+) -> Result<Vec<String>, Error> {
+    let fragments = decode_fragments(messages)?;
     let fragment_ids = fragments
         .iter()
         .map(|fragment| fragment.id().to_string())
@@ -98,6 +120,39 @@ pub async fn post_fragments(
     Ok(fragment_ids)
 }
 
+/// check whether the fragments would be accepted in the mempool, without
+/// adding them to it. The fragments are applied one after the other to the
+/// ledger of the tip, as they would be in the next block.
+pub async fn validate_fragments(
+    context: &Context,
+    messages: Vec<String>,
+) -> Result<Vec<FragmentValidation>, Error> {
+    let fragments = decode_fragments(messages)?;
+    let accept_update_proposals = context.try_full()?.accept_update_proposals;
+    let tip = context.blockchain_tip()?.get_ref().await;
+    let mut tip_ledger = TipLedger::new(&tip);
+    Ok(fragments
+        .iter()
+        .map(|fragment| {
+            let status = if !fragment::is_fragment_valid(fragment, accept_update_proposals) {
+                FragmentValidationStatus::Invalid {
+                    reason: "the fragment is malformed or of a kind not accepted in the mempool"
+                        .to_string(),
+                }
+            } else {
+                match tip_ledger.apply(fragment) {
+                    Ok(()) => FragmentValidationStatus::Valid,
+                    Err(reason) => FragmentValidationStatus::Invalid { reason },
+                }
+            };
+            FragmentValidation {
+                fragment_id: fragment.id().into(),
+                status,
+            }
+        })
+        .collect())
+}
+
 pub async fn get_fragments_logs(context: &Context) -> Result<Vec<FragmentLog>, Error> {
     let span =
         span!(parent: context.span()?, Level::TRACE, "fragment_logs", request = "fragment_logs");
@@ -115,3 +170,187 @@ pub async fn get_fragments_logs(context: &Context) -> Result<Vec<FragmentLog>, E
     .instrument(span)
     .await
 }
+
+/// the fragments of the mempool that match all the given filters: the type
+/// of the fragment, an account it spends from, given as the hex of its public
+/// key, and bounds on the time since it was received
+pub async fn get_pending_fragments(
+    context: &Context,
+    fragment_type: Option<FragmentType>,
+    account: Option<String>,
+    min_age: Option<Duration>,
+    max_age: Option<Duration>,
+) -> Result<Vec<PendingFragment>, Error> {
+    let account = account
+        .map(|account_hex| {
+            PublicKey::<AccountAlg>::from_str(&account_hex).map(|public_key| {
+                let identifier =
+                    UnspecifiedAccountIdentifier::from_single_account(Identifier::from(public_key));
+                TransactionInputType::Account(identifier.into())
+            })
+        })
+        .transpose()?;
+    let span = span!(parent: context.span()?, Level::TRACE, "pending_fragments", request = "pending_fragments");
+    let pending = async move {
+        let (reply_handle, reply_future) = intercom::unary_reply();
+        let mut mbox = context.try_full()?.transaction_task.clone();
+        mbox.send(TransactionMsg::GetPendingFragments(reply_handle))
+            .await
+            .map_err(|e| {
+                tracing::debug!(reason = %e, "error getting pending fragments");
+                Error::MsgSendError(e)
+            })?;
+        reply_future.await.map_err(Error::from)
+    }
+    .instrument(span)
+    .await?;
+
+    let now = SystemTime::now();
+    Ok(pending
+        .into_iter()
+        .filter(|fragment| {
+            fragment_type.map_or(true, |fragment_type| {
+                fragment.fragment_type == fragment_type
+            })
+        })
+        .filter(|fragment| {
+            account.as_ref().map_or(true, |account| {
+                fragment.inputs.iter().any(|input| &input.input == account)
+            })
+        })
+        .filter(|fragment| {
+            // a fragment received after `now`, with the clock set back in
+            // between, is considered just received
+            let age = now
+                .duration_since(fragment.received_at)
+                .unwrap_or_else(|_| Duration::new(0, 0));
+            min_age.map_or(true, |min_age| age >= min_age)
+                && max_age.map_or(true, |max_age| age <= max_age)
+        })
+        .collect())
+}
+
+/// the estimated size of a signed transaction fragment without its inputs,
+/// outputs and certificate: the fragment header and the transaction header
+const TRANSACTION_SIZE_ESTIMATE: u32 = 8;
+/// the estimated size of an input with its witness
+const INPUT_SIZE_ESTIMATE: u32 = 41 + 65;
+/// the estimated size of an output to a group address, the largest kind
+const OUTPUT_SIZE_ESTIMATE: u32 = 66 + 8;
+/// the estimated size of a certificate with its signature
+const CERTIFICATE_SIZE_ESTIMATE: u32 = 256;
+
+/// the fee of a transaction of the given shape under the fee settings of the
+/// tip, and the fee to pay to be selected in the next block when the blocks
+/// are filled with the fragments paying the highest fee per byte first
+pub async fn estimate_fee(
+    context: &Context,
+    request: FeeEstimateRequest,
+) -> Result<FeeEstimate, Error> {
+    let tip = context.blockchain_tip()?.get_ref().await;
+    let ledger_params = tip.epoch_ledger_parameters();
+    let fees = &ledger_params.fees;
+    let fee = match request.certificate {
+        Some(certificate) => certificate
+            .transaction_fee(fees, request.inputs, request.outputs)
+            .ok_or(Error::NoTransactionFee(certificate))?,
+        None => fees.calculate(None, request.inputs, request.outputs),
+    }
+    .0;
+
+    let full_context = context.try_full()?;
+    if full_context.fragment_selection != FragmentSelectionAlgorithmParams::HighestFeeDensityFirst {
+        return Ok(FeeEstimate {
+            fee: fee.into(),
+            suggested_fee: None,
+        });
+    }
+
+    let size = request.size.unwrap_or_else(|| {
+        TRANSACTION_SIZE_ESTIMATE
+            + INPUT_SIZE_ESTIMATE * u32::from(request.inputs)
+            + OUTPUT_SIZE_ESTIMATE * u32::from(request.outputs)
+            + request.certificate.map_or(0, |_| CERTIFICATE_SIZE_ESTIMATE)
+    });
+    let span =
+        span!(parent: context.span()?, Level::TRACE, "fee_estimate", request = "fee_estimate");
+    async move {
+        let (reply_handle, reply_future) = intercom::unary_reply();
+        let mut mbox = full_context.transaction_task.clone();
+        mbox.send(TransactionMsg::GetFeeDensityThreshold {
+            block_content_max_size: ledger_params.block_content_max_size,
+            size,
+            reply_handle,
+        })
+        .await
+        .map_err(|e| {
+            tracing::debug!(reason = %e, "error getting the fee density threshold");
+            Error::MsgSendError(e)
+        })?;
+        let threshold = reply_future.await?;
+        let suggested_fee = threshold.map_or(fee, |threshold| {
+            threshold.lowest_fee_above(u64::from(size)).max(fee)
+        });
+        Ok(FeeEstimate {
+            fee: fee.into(),
+            suggested_fee: Some(suggested_fee.into()),
+        })
+    }
+    .instrument(span)
+    .await
+}
+
+/// the hex of the serialized fragment with the given id, `None` if it is not
+/// in the mempool
+pub async fn get_pending_fragment(
+    context: &Context,
+    fragment_id_hex: &str,
+) -> Result<Option<String>, Error> {
+    let fragment_id = FragmentId::from_str(fragment_id_hex)?;
+    let span = span!(parent: context.span()?, Level::TRACE, "pending_fragment", request = "pending_fragment");
+    async move {
+        let (reply_handle, reply_future) = intercom::unary_reply();
+        let mut mbox = context.try_full()?.transaction_task.clone();
+        mbox.send(TransactionMsg::GetPendingFragment(
+            fragment_id,
+            reply_handle,
+        ))
+        .await
+        .map_err(|e| {
+            tracing::debug!(reason = %e, "error getting pending fragment");
+            Error::MsgSendError(e)
+        })?;
+        let fragment = reply_future.await?;
+        fragment
+            .map(|fragment| {
+                fragment
+                    .serialize_as_vec()
+                    .map(hex::encode)
+                    .map_err(Error::Serialize)
+            })
+            .transpose()
+    }
+    .instrument(span)
+    .await
+}
+
+/// the events of the tip kept by the node after the one with the sequence
+/// number `last_seen`, followed by the events to come
+pub fn subscribe_tip_events(
+    context: &Context,
+    last_seen: Option<u64>,
+) -> Result<impl Stream<Item = TipEvent>, Error> {
+    let (missed, receiver) = context.blockchain()?.tip_events().subscribe(last_seen);
+    let next = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                // the events are skipped, the client sees the gap in
+                // the sequence numbers
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(stream::iter(missed).chain(next))
+}
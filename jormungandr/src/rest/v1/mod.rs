@@ -12,7 +12,7 @@ pub fn filter(
     let root = warp::path!("v1" / ..);
 
     let fragments = {
-        let root = warp::path!("fragments");
+        let root = warp::path!("fragments" / ..);
 
         let post = warp::path::end()
             .and(warp::post())
@@ -21,6 +21,13 @@ pub fn filter(
             .and_then(handlers::post_fragments)
             .boxed();
 
+        let validate = warp::path!("validate")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_context.clone())
+            .and_then(handlers::validate_fragments)
+            .boxed();
+
         let status = warp::path!("statuses")
             .and(warp::get())
             .and(warp::query())
@@ -30,14 +37,48 @@ pub fn filter(
 
         let logs = warp::path!("logs")
             .and(warp::get())
-            .and(with_context)
+            .and(with_context.clone())
             .and_then(handlers::get_fragments_logs)
             .boxed();
 
-        root.and(post.or(status).or(logs)).boxed()
+        let pending = warp::path!("pending")
+            .and(warp::get())
+            .and(warp::query())
+            .and(with_context.clone())
+            .and_then(handlers::get_pending_fragments)
+            .boxed();
+
+        let pending_fragment = warp::path!(String)
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_pending_fragment)
+            .boxed();
+
+        root.and(
+            post.or(validate)
+                .or(status)
+                .or(logs)
+                .or(pending)
+                .or(pending_fragment),
+        )
+        .boxed()
     };
 
-    let routes = fragments;
+    let events = warp::path!("events" / "tip")
+        .and(warp::get())
+        .and(warp::sse::last_event_id::<u64>())
+        .and(with_context.clone())
+        .and_then(handlers::get_tip_events)
+        .boxed();
+
+    let fees = warp::path!("fees" / "estimate")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_context)
+        .and_then(handlers::estimate_fee)
+        .boxed();
+
+    let routes = fragments.or(events).or(fees);
 
     root.and(routes).recover(handle_rejection).boxed()
 }
@@ -46,9 +87,11 @@ pub fn filter(
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
     if let Some(err) = err.find::<logic::Error>() {
         let (body, code) = match err {
-            logic::Error::PublicKey(_) | logic::Error::Hash(_) | logic::Error::Hex(_) => {
-                (err.to_string(), StatusCode::BAD_REQUEST)
-            }
+            logic::Error::PublicKey(_)
+            | logic::Error::Hash(_)
+            | logic::Error::Hex(_)
+            | logic::Error::Deserialize(_)
+            | logic::Error::NoTransactionFee(_) => (err.to_string(), StatusCode::BAD_REQUEST),
             err => (
                 display_internal_server_error(err),
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -56,6 +56,11 @@ pub struct StartArguments {
     /// to `public_address`.
     #[structopt(long = "listen-address")]
     pub listen_address: Option<Address>,
+
+    /// Import the blocks of a file written by the `export-blocks` command
+    /// before connecting to the network.
+    #[structopt(long = "import-blocks", parse(from_os_str))]
+    pub import_blocks: Option<PathBuf>,
 }
 
 #[derive(StructOpt, Debug)]
@@ -114,9 +119,42 @@ pub struct CommandLine {
     #[structopt(long = "source-version")]
     pub source_version: bool,
 
-    /// Initialize the storage and exit, useful to check that the storage has been set up correctly.
+    /// Initialize the storage, check its integrity and exit. The issues found are logged
+    /// and the node exits with an error if the storage is not consistent.
     #[structopt(long = "storage-check")]
     pub storage_check: bool,
+
+    /// Like `--storage-check`, but truncate an inconsistent storage to the last block of
+    /// the main branch that can be reached through consistent blocks.
+    #[structopt(long = "storage-repair")]
+    pub storage_repair: bool,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    /// Export blocks of the storage to a file, the file can then be imported
+    /// in the storage of another node with `--import-blocks`.
+    ExportBlocks(ExportBlocksArguments),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ExportBlocksArguments {
+    /// The block after which the blocks are exported. By default the blocks
+    /// are exported from the block0.
+    #[structopt(long = "from", parse(try_from_str))]
+    pub from: Option<HeaderHash>,
+
+    /// The last block exported. By default the blocks are exported up to the
+    /// tip of the storage.
+    #[structopt(long = "to", parse(try_from_str))]
+    pub to: Option<HeaderHash>,
+
+    /// The file to write the blocks to.
+    #[structopt(long = "output", parse(from_os_str))]
+    pub output: PathBuf,
 }
 
 impl CommandLine {
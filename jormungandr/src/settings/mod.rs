@@ -2,7 +2,7 @@ mod command_arguments;
 pub mod logging;
 pub mod start;
 
-pub use self::command_arguments::CommandLine;
+pub use self::command_arguments::{Command, CommandLine, ExportBlocksArguments};
 pub use self::start::Error;
 use crate::blockcfg::HeaderHash;
 use std::path::PathBuf;
@@ -1,11 +1,13 @@
 #![allow(deprecated)]
 use crate::{
+    fragment::selection::FragmentSelectionAlgorithmParams,
     network::p2p::{layers::LayersConfig, topic, Address, PolicyConfig},
     settings::logging::{LogFormat, LogOutput},
     settings::LOG_FILTER_LEVEL_POSSIBLE_VALUES,
 };
-pub use jormungandr_lib::interfaces::{Cors, Rest, Tls};
-use jormungandr_lib::{interfaces::Mempool, time::Duration};
+use chain_crypto::Ed25519;
+pub use jormungandr_lib::interfaces::{Cors, P2pTls, Rest, Tls, TrustedCheckpoint};
+use jormungandr_lib::{crypto::key::Identifier, interfaces::Mempool, time::Duration};
 
 use multiaddr::Multiaddr;
 use serde::{de::Error as _, de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
@@ -18,7 +20,7 @@ use std::{collections::BTreeMap, fmt, path::PathBuf};
 pub struct Config {
     #[serde(default)]
     pub secret_files: Vec<PathBuf>,
-    pub storage: Option<PathBuf>,
+    pub storage: Option<Storage>,
     pub log: Option<ConfigLogSettings>,
 
     /// setting of the mempool, fragment logs and related data
@@ -46,6 +48,11 @@ pub struct Config {
     pub bootstrap_from_trusted_peers: bool,
     #[serde(default)]
     pub skip_bootstrap: bool,
+
+    /// a block the chain pulled from the peers during the bootstrap must
+    /// contain. The proofs of the blocks up to it are not checked.
+    #[serde(default)]
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -79,6 +86,29 @@ pub struct P2pConfig {
     #[serde(default)]
     pub public_id: Option<poldercast::Id>,
 
+    /// the file of the bech32 encoded ed25519 secret key authenticating the
+    /// node to its peers, as generated by `jcli key generate --type node-id`.
+    /// If not specified, a new key is generated every time the node starts.
+    #[serde(default)]
+    pub node_key_file: Option<PathBuf>,
+
+    /// the bech32 encoded ids of the nodes allowed to authenticate to this
+    /// node. If not specified, any node is allowed.
+    #[serde(default)]
+    pub allowed_node_ids: Option<Vec<Identifier<Ed25519>>>,
+
+    /// only exchange with the nodes of `allowed_node_ids`: the subscriptions
+    /// of the other nodes are refused, the node does not connect to them nor
+    /// share its address with them, and the random topology layers are
+    /// disabled. The default is to take part in the public network.
+    #[serde(default)]
+    pub private_network: bool,
+
+    /// TLS settings of the connections with the peers. If not specified, the
+    /// connections are not encrypted.
+    #[serde(default)]
+    pub tls: Option<P2pTls>,
+
     /// the rendezvous points for the peer to connect to in order to initiate
     /// the p2p discovery from.
     pub trusted_peers: Option<Vec<TrustedPeer>>,
@@ -150,6 +180,13 @@ pub struct P2pConfig {
     /// gossip with the trusted peers if any are defined.
     #[serde(default)]
     pub max_bootstrap_attempts: Option<usize>,
+
+    /// The peers known to the node are saved in the storage directory and
+    /// used as additional bootstrap candidates at the next start. The peers
+    /// not seen for longer than this value are forgotten. The default value
+    /// is 1 day.
+    #[serde(default)]
+    pub known_peers_max_age: Option<Duration>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -167,6 +204,50 @@ pub struct TrustedPeer {
     pub id: Option<poldercast::Id>,
 }
 
+/// the storage settings, either only the path of the storage directory or
+/// the path along with the storage options
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Storage {
+    Path(PathBuf),
+    Settings(StorageSettings),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StorageSettings {
+    pub path: PathBuf,
+
+    /// remove the old blocks from the storage, the default is to keep
+    /// all the blocks.
+    #[serde(default)]
+    pub pruning: Option<Pruning>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pruning {
+    /// the number of most recent epochs, including the current one, of
+    /// which the blocks are kept in the storage.
+    pub keep_epochs: u32,
+}
+
+impl Storage {
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            Storage::Path(path) => path,
+            Storage::Settings(settings) => &settings.path,
+        }
+    }
+
+    pub fn pruning(&self) -> Option<&Pruning> {
+        match self {
+            Storage::Path(_) => None,
+            Storage::Settings(settings) => settings.pruning.as_ref(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Leadership {
@@ -174,6 +255,10 @@ pub struct Leadership {
     /// the least recently used log will be erased from the logs for a new one
     /// to be inserted.
     pub logs_capacity: usize,
+    /// the algorithm selecting the fragments of the mempool to put in
+    /// the blocks
+    #[serde(default)]
+    pub fragment_selection: FragmentSelectionAlgorithmParams,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
@@ -209,6 +294,10 @@ impl Default for P2pConfig {
             public_address: None,
             listen_address: None,
             public_id: None,
+            node_key_file: None,
+            allowed_node_ids: None,
+            private_network: false,
+            tls: None,
             trusted_peers: None,
             topics_of_interest: None,
             max_connections: None,
@@ -221,6 +310,7 @@ impl Default for P2pConfig {
             gossip_interval: None,
             topology_force_reset_interval: None,
             max_bootstrap_attempts: None,
+            known_peers_max_age: None,
         }
     }
 }
@@ -229,6 +319,7 @@ impl Default for Leadership {
     fn default() -> Self {
         Leadership {
             logs_capacity: 1_024,
+            fragment_selection: FragmentSelectionAlgorithmParams::default(),
         }
     }
 }
@@ -1,13 +1,18 @@
 pub mod config;
 pub mod network;
 
-use self::config::{Config, Leadership};
+use self::config::{Config, Leadership, Pruning};
 use self::network::{Protocol, TrustedPeer};
 use crate::settings::logging::{LogFormat, LogOutput, LogSettings, LogSettingsEntry};
 use crate::settings::{command_arguments::*, Block0Info};
+use chain_crypto::{bech32::Bech32 as _, Ed25519, SecretKey};
 pub use jormungandr_lib::interfaces::{Cors, Mempool, Rest, Tls};
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
+use tonic::transport::{Certificate, Identity};
 use tracing::level_filters::LevelFilter;
 
 const DEFAULT_FILTER_LEVEL: LevelFilter = LevelFilter::TRACE;
@@ -25,12 +30,35 @@ pub enum Error {
     ExpectedBlock0Info,
     #[error("In the node configuration file, the `p2p.listen_address` value is not a valid address. Use format `/ip4/x.x.x.x/tcp/4920")]
     ListenAddressNotValid,
+    #[error("The explorer needs all the blocks of the chain, it cannot be enabled with `storage.pruning`")]
+    ExplorerWithPrunedStorage,
+    #[error("Cannot read the node key file {path}")]
+    NodeKeyIo {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("The node key file {path} does not contain a bech32 encoded ed25519 secret key")]
+    NodeKey {
+        path: PathBuf,
+        #[source]
+        source: chain_crypto::bech32::Error,
+    },
+    #[error("Cannot read the p2p TLS file {path}")]
+    P2pTlsIo {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("The node cannot run in a private network without `p2p.allowed_node_ids`")]
+    PrivateNetworkWithoutAllowedNodes,
 }
 
 /// Overall Settings for node
 pub struct Settings {
     pub network: network::Configuration,
     pub storage: Option<PathBuf>,
+    pub storage_pruning: Option<Pruning>,
     pub block_0: Block0Info,
     pub secrets: Vec<PathBuf>,
     pub rest: Option<Rest>,
@@ -39,6 +67,7 @@ pub struct Settings {
     pub leadership: Leadership,
     pub explorer: bool,
     pub no_blockchain_updates_warning_interval: std::time::Duration,
+    pub import_blocks: Option<PathBuf>,
 }
 
 pub struct RawSettings {
@@ -108,6 +137,7 @@ impl RawSettings {
                 listen: cmd_listen,
                 tls: None,
                 cors: None,
+                admin: None,
             }),
             (None, None) => None,
         }
@@ -132,10 +162,15 @@ impl RawSettings {
             config.as_ref().and_then(|cfg| cfg.storage.as_ref()),
         ) {
             (Some(path), _) => Some(path.clone()),
-            (None, Some(path)) => Some(path.clone()),
+            (None, Some(storage)) => Some(storage.path().clone()),
             (None, None) => None,
         };
 
+        let storage_pruning = config
+            .as_ref()
+            .and_then(|cfg| cfg.storage.as_ref())
+            .and_then(|storage| storage.pruning().cloned());
+
         let mut secrets = command_arguments.secret.clone();
         if let Some(secret_files) = config.as_ref().map(|cfg| cfg.secret_files.clone()) {
             secrets.extend(secret_files);
@@ -164,8 +199,13 @@ impl RawSettings {
                     .map_or(false, |settings| settings.enabled)
             });
 
+        if explorer && storage_pruning.is_some() {
+            return Err(Error::ExplorerWithPrunedStorage);
+        }
+
         Ok(Settings {
             storage,
+            storage_pruning,
             block_0,
             network,
             secrets,
@@ -185,8 +225,40 @@ impl RawSettings {
                 .unwrap_or_else(|| {
                     std::time::Duration::from_secs(DEFAULT_NO_BLOCKCHAIN_UPDATES_WARNING_INTERVAL)
                 }),
+            import_blocks: command_arguments.import_blocks.clone(),
+        })
+    }
+}
+
+fn load_node_key(path: &Path) -> Result<SecretKey<Ed25519>, Error> {
+    let key = std::fs::read_to_string(path).map_err(|source| Error::NodeKeyIo {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    SecretKey::try_from_bech32_str(key.trim()).map_err(|source| Error::NodeKey {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn load_p2p_tls(tls: &config::P2pTls) -> Result<network::Tls, Error> {
+    fn read(path: &Path) -> Result<Vec<u8>, Error> {
+        std::fs::read(path).map_err(|source| Error::P2pTlsIo {
+            path: path.to_path_buf(),
+            source,
         })
     }
+
+    Ok(network::Tls {
+        identity: Identity::from_pem(read(&tls.cert_file)?, read(&tls.priv_key_file)?),
+        ca: Certificate::from_pem(read(&tls.ca_file)?),
+        domain_name: tls.domain_name.clone(),
+        client_ca: tls
+            .client_ca_file
+            .as_ref()
+            .map(|path| read(path).map(Certificate::from_pem))
+            .transpose()?,
+    })
 }
 
 #[allow(deprecated)]
@@ -194,17 +266,23 @@ fn generate_network(
     command_arguments: &StartArguments,
     config: &Option<Config>,
 ) -> Result<network::Configuration, Error> {
-    let (mut p2p, http_fetch_block0_service, skip_bootstrap, bootstrap_from_trusted_peers) =
-        if let Some(cfg) = config {
-            (
-                cfg.p2p.clone(),
-                cfg.http_fetch_block0_service.clone(),
-                cfg.skip_bootstrap,
-                cfg.bootstrap_from_trusted_peers,
-            )
-        } else {
-            (config::P2pConfig::default(), Vec::new(), false, false)
-        };
+    let (
+        mut p2p,
+        http_fetch_block0_service,
+        skip_bootstrap,
+        bootstrap_from_trusted_peers,
+        trusted_checkpoint,
+    ) = if let Some(cfg) = config {
+        (
+            cfg.p2p.clone(),
+            cfg.http_fetch_block0_service.clone(),
+            cfg.skip_bootstrap,
+            cfg.bootstrap_from_trusted_peers,
+            cfg.trusted_checkpoint.clone(),
+        )
+    } else {
+        (config::P2pConfig::default(), Vec::new(), false, false, None)
+    };
 
     if p2p.trusted_peers.is_some() {
         if let Some(peers) = p2p.trusted_peers.as_mut() {
@@ -270,11 +348,33 @@ fn generate_network(
         .map(|v| v.to_socket_addr().ok_or(Error::ListenAddressNotValid))
         .transpose()?;
 
+    let node_key = p2p
+        .node_key_file
+        .as_ref()
+        .map(|path| load_node_key(path))
+        .transpose()?;
+
+    let tls = p2p.tls.as_ref().map(load_p2p_tls).transpose()?;
+
+    let allowed_node_ids: Vec<_> = p2p
+        .allowed_node_ids
+        .unwrap_or_default()
+        .into_iter()
+        .map(|id| id.into_public_key())
+        .collect();
+    if p2p.private_network && allowed_node_ids.is_empty() {
+        return Err(Error::PrivateNetworkWithoutAllowedNodes);
+    }
+
     let mut network = network::Configuration {
         profile: profile.build(),
+        node_key,
+        allowed_node_ids,
+        private_network: p2p.private_network,
         listen_address,
         trusted_peers,
         protocol: Protocol::Grpc,
+        tls,
         policy: p2p.policy.clone(),
         layers: p2p.layers.clone(),
         max_connections: p2p
@@ -292,9 +392,14 @@ fn generate_network(
             .unwrap_or_else(|| std::time::Duration::from_secs(10)),
         topology_force_reset_interval: p2p.topology_force_reset_interval.map(|d| d.into()),
         max_bootstrap_attempts: p2p.max_bootstrap_attempts,
+        known_peers_max_age: p2p
+            .known_peers_max_age
+            .map(|d| d.into())
+            .unwrap_or(network::DEFAULT_KNOWN_PEERS_MAX_AGE),
         http_fetch_block0_service,
         bootstrap_from_trusted_peers,
         skip_bootstrap,
+        trusted_checkpoint,
         legacy_node_id: Some(legacy_node_id),
     };
 
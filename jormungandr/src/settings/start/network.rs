@@ -1,8 +1,10 @@
 #![allow(deprecated)]
 use super::config;
 use crate::network::p2p::{layers::LayersConfig, Address, PolicyConfig};
+use chain_crypto::{Ed25519, PublicKey, SecretKey};
 use jormungandr_lib::multiaddr::{self, multiaddr_resolve_dns};
 use poldercast::NodeProfile;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
 
 use std::convert::TryFrom;
 use std::{net::SocketAddr, str, time::Duration};
@@ -38,6 +40,38 @@ pub struct Listen {
     pub timeout: Duration,
 }
 
+/// TLS settings of the connections between the nodes, with the contents
+/// of the files given in the configuration
+#[derive(Clone)]
+pub struct Tls {
+    /// the certificate chain and the private key of the node
+    pub identity: Identity,
+    /// the authority of the certificates of the peers the node connects to
+    pub ca: Certificate,
+    /// the name the certificates of the peers are issued for
+    pub domain_name: String,
+    /// the authority of the certificates the peers connecting to the node
+    /// must present, no client certificate is asked for if not set
+    pub client_ca: Option<Certificate>,
+}
+
+impl Tls {
+    pub fn server_config(&self) -> ServerTlsConfig {
+        let config = ServerTlsConfig::new().identity(self.identity.clone());
+        match &self.client_ca {
+            Some(client_ca) => config.client_ca_root(client_ca.clone()),
+            None => config,
+        }
+    }
+
+    pub fn client_config(&self) -> ClientTlsConfig {
+        ClientTlsConfig::new()
+            .ca_certificate(self.ca.clone())
+            .identity(self.identity.clone())
+            .domain_name(self.domain_name.clone())
+    }
+}
+
 /// The limit on the number of simultaneous P2P connections
 /// used unless the corresponding configuration option is specified.
 pub const DEFAULT_MAX_CONNECTIONS: usize = 256;
@@ -49,6 +83,10 @@ pub const DEFAULT_MAX_INBOUND_CONNECTIONS: usize = 192;
 /// The default timeout for connections
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// How long the peers known to the node are kept across restarts
+/// if the corresponding configuration option is not specified.
+pub const DEFAULT_KNOWN_PEERS_MAX_AGE: Duration = Duration::from_secs(24 * 3600);
+
 ///
 /// The network static configuration settings
 #[derive(Clone)]
@@ -60,12 +98,27 @@ pub struct Configuration {
 
     pub profile: NodeProfile,
 
+    /// the secret key authenticating the node to its peers, a new one is
+    /// generated when the node starts if not given
+    pub node_key: Option<SecretKey<Ed25519>>,
+
+    /// the ids of the nodes allowed to authenticate to this node, any node
+    /// is allowed if empty
+    pub allowed_node_ids: Vec<PublicKey<Ed25519>>,
+
+    /// whether the node only exchanges with the nodes of `allowed_node_ids`
+    pub private_network: bool,
+
     /// list of trusted addresses
     pub trusted_peers: Vec<TrustedPeer>,
 
     /// the protocol to utilise for the p2p network
     pub protocol: Protocol,
 
+    /// the TLS settings of the connections with the peers, the connections
+    /// are not encrypted if not set
+    pub tls: Option<Tls>,
+
     /// Maximum allowed number of peer connections.
     pub max_connections: usize,
 
@@ -90,12 +143,19 @@ pub struct Configuration {
 
     pub max_bootstrap_attempts: Option<usize>,
 
+    /// the peers saved in the storage directory that were not seen for
+    /// longer than this are not loaded
+    pub known_peers_max_age: Duration,
+
     /// Whether to limit bootstrap to trusted peers (which increase their load / reduce their connectivities)
     pub bootstrap_from_trusted_peers: bool,
 
     /// Whether to skip bootstrap, not recommended in normal settings. useful to true for self-node
     pub skip_bootstrap: bool,
 
+    /// A block the chain pulled from the peers during the bootstrap must contain
+    pub trusted_checkpoint: Option<config::TrustedCheckpoint>,
+
     pub http_fetch_block0_service: Vec<String>,
 
     /// A pre-0.9 node ID to put in "node-id-bin" metadata when subscribing
@@ -1,6 +1,6 @@
 use crate::{
     blockcfg, blockchain,
-    blockchain::StorageError,
+    blockchain::{ArchiveError, LedgerSnapshotError, StorageError},
     diagnostic::DiagnosticError,
     explorer, network, secure,
     settings::{self, logging},
@@ -41,6 +41,8 @@ pub enum Error {
     },
     #[error("Storage error")]
     StorageError(#[from] StorageError),
+    #[error("Error while preparing the ledger snapshots")]
+    LedgerSnapshot(#[from] LedgerSnapshotError),
     #[error("Error while loading the legacy blockchain state")]
     Blockchain(#[from] blockchain::Error),
     #[error("Error in the genesis-block")]
@@ -59,6 +61,18 @@ pub enum Error {
     ServiceTerminatedWithError(#[from] crate::utils::task::ServiceError),
     #[error("Unable to get system limits: {0}")]
     DiagnosticError(#[from] DiagnosticError),
+    #[error("Cannot export blocks without a storage, set it with `--storage` or in the node configuration")]
+    ExportWithoutStorage,
+    #[error("The storage has no blocks to export")]
+    ExportEmptyStorage,
+    #[error("Error while exporting or importing blocks")]
+    ChainArchive(#[from] ArchiveError),
+    #[error(
+        "The storage check found {0} issues, run with `--storage-repair` to repair the storage"
+    )]
+    StorageInconsistent(usize),
+    #[error("The storage has no consistent block to be repaired from")]
+    StorageUnrepairable,
     #[error("Interrupted by the user")]
     Interrupted,
 }
@@ -82,6 +96,9 @@ impl Error {
             Error::IO { .. } => 3,
             Error::ParseError { .. } => 4,
             Error::StorageError { .. } => 5,
+            Error::LedgerSnapshot { .. } => 5,
+            Error::StorageInconsistent { .. } => 5,
+            Error::StorageUnrepairable => 5,
             Error::Blockchain { .. } => 6,
             Error::Block0 { .. } => 7,
             Error::Block0Mismatch { .. } => 7,
@@ -92,6 +109,9 @@ impl Error {
             Error::ExplorerBootstrapError { .. } => 11,
             Error::ServiceTerminatedWithError { .. } => 12,
             Error::DiagnosticError { .. } => 13,
+            Error::ExportWithoutStorage => 14,
+            Error::ExportEmptyStorage => 14,
+            Error::ChainArchive { .. } => 14,
         }
     }
 }
@@ -5,17 +5,23 @@ use tracing::{span, Level};
 pub use self::error::{Error, ErrorKind};
 use crate::{
     blockcfg::{Block, HeaderId},
-    blockchain::{Blockchain, ErrorKind as BlockchainError, Storage, Tip},
+    blockchain::{
+        self, Blockchain, ErrorKind as BlockchainError, LedgerSnapshots, Storage, Tip,
+        MAIN_BRANCH_TAG,
+    },
     network,
-    settings::start::Settings,
+    settings::{start::Settings, Block0Info, ExportBlocksArguments},
 };
 
-/// prepare the block storage from the given settings
-pub fn prepare_storage(setting: &Settings) -> Result<Storage, Error> {
+/// prepare the block storage from the given settings. A pruned storage is
+/// pruned up to its pruning point if `prune` is set.
+pub fn prepare_storage(setting: &Settings, prune: bool) -> Result<Storage, Error> {
     let span = span!(Level::TRACE, "sub_task", kind = "storage");
     let storage_span = span.clone();
     let _enter = span.enter();
     if let Some(dir) = &setting.storage {
+        Storage::recover_rebuild(dir)?;
+
         std::fs::create_dir_all(dir).map_err(|err| Error::IO {
             source: err,
             reason: ErrorKind::BlockStorage,
@@ -23,12 +29,60 @@ pub fn prepare_storage(setting: &Settings) -> Result<Storage, Error> {
 
         tracing::info!("storing blockchain in '{:?}'", dir);
 
-        Storage::file(dir, storage_span).map_err(Into::into)
+        let storage = Storage::file(dir, storage_span)?;
+        if prune && setting.storage_pruning.is_some() {
+            storage.prune()?;
+        }
+        Ok(storage)
     } else {
         Storage::memory(storage_span).map_err(Into::into)
     }
 }
 
+/// check the integrity of the storage and log the issues found. If `repair`
+/// is set, the storage is truncated to the last block of the main branch
+/// that can be reached through consistent blocks.
+pub fn check_storage(setting: &Settings, storage: Storage, repair: bool) -> Result<(), Error> {
+    let span = span!(Level::TRACE, "sub_task", kind = "storage");
+    let _enter = span.enter();
+
+    tracing::info!("checking the storage");
+    let report = blockchain::check_storage(&storage)?;
+    for issue in &report.issues {
+        tracing::warn!("{}", issue);
+    }
+    tracing::info!(
+        "{} blocks of the main branch checked, {} issues found",
+        report.blocks_checked,
+        report.issues.len()
+    );
+
+    if report.is_consistent() {
+        return Ok(());
+    }
+    if !repair {
+        return Err(Error::StorageInconsistent(report.issues.len()));
+    }
+
+    if setting.storage.is_none() {
+        return Ok(());
+    }
+    let (first_block, last_consistent) = match (report.first_block, report.last_consistent) {
+        (Some(first_block), Some(last_consistent)) => (first_block, last_consistent),
+        _ => return Err(Error::StorageUnrepairable),
+    };
+
+    tracing::info!(
+        "repairing the storage, keeping the main branch up to {}",
+        last_consistent
+    );
+    let pruned = storage.is_pruned()?;
+    storage.rebuild(first_block, last_consistent, pruned, false)?;
+    tracing::info!("the storage is repaired");
+
+    Ok(())
+}
+
 /// Try to fetch the block0_id from the HTTP base URL (services) in the array
 ///
 /// The HTTP url is expecting to be of the form: URL/<hash-id>.block0
@@ -90,7 +144,6 @@ async fn fetch_block0_http(base_services: &[String], block0_id: &HeaderId) -> Op
 ///     1. check the storage if we don't have it already there;
 ///     2. check the network nodes we know about
 pub async fn prepare_block_0(settings: &Settings, storage: &Storage) -> Result<Block, Error> {
-    use crate::settings::Block0Info;
     use chain_core::property::Deserialize as _;
     match &settings.block_0 {
         Block0Info::Path(path, opt_block0_id) => {
@@ -146,9 +199,57 @@ pub async fn prepare_block_0(settings: &Settings, storage: &Storage) -> Result<B
     }
 }
 
+/// write the blocks of the storage to the file given to the
+/// `export-blocks` command
+pub async fn export_blocks(
+    settings: &Settings,
+    storage: &Storage,
+    arguments: &ExportBlocksArguments,
+) -> Result<(), Error> {
+    if settings.storage.is_none() {
+        return Err(Error::ExportWithoutStorage);
+    }
+
+    let block0_id = match &settings.block_0 {
+        Block0Info::Hash(block0_id) | Block0Info::Path(_, Some(block0_id)) => *block0_id,
+        Block0Info::Path(_, None) => prepare_block_0(settings, storage).await?.header.id(),
+    };
+    let from = arguments.from.unwrap_or(block0_id);
+    let to = match arguments.to {
+        Some(to) => to,
+        None => storage
+            .get_tag(MAIN_BRANCH_TAG)?
+            .ok_or(Error::ExportEmptyStorage)?,
+    };
+
+    tracing::info!(
+        "exporting the blocks from {} to {} to '{:?}'",
+        from,
+        to,
+        arguments.output
+    );
+    let count = blockchain::export_blocks(storage, block0_id, from, to, &arguments.output).await?;
+    tracing::info!("{} blocks exported", count);
+
+    Ok(())
+}
+
+/// prepare the ledger snapshots directory of the block storage, if the
+/// storage is persisted on disk
+pub fn prepare_ledger_snapshots(setting: &Settings) -> Result<Option<LedgerSnapshots>, Error> {
+    setting
+        .storage
+        .as_ref()
+        .map(LedgerSnapshots::new)
+        .transpose()
+        .map_err(Into::into)
+}
+
 pub async fn load_blockchain(
     block0: Block,
     storage: Storage,
+    snapshots: Option<LedgerSnapshots>,
+    pruning_keep_epochs: Option<u32>,
     cache_capacity: usize,
     rewards_report_all: bool,
 ) -> Result<(Blockchain, Tip), Error> {
@@ -157,11 +258,15 @@ pub async fn load_blockchain(
         storage,
         cache_capacity,
         rewards_report_all,
+        snapshots,
+        pruning_keep_epochs,
     );
 
     let main_branch = match blockchain.load_from_block0(block0.clone()).await {
         Err(error) => match error.kind() {
-            BlockchainError::Block0AlreadyInStorage => blockchain.load_from_storage(block0).await,
+            BlockchainError::Block0AlreadyInStorage | BlockchainError::Block0PrunedFromStorage => {
+                blockchain.load_from_storage(block0).await
+            }
             _ => Err(error),
         },
         Ok(branch) => Ok(branch),
@@ -1,8 +1,9 @@
 use arc_swap::ArcSwapOption;
 use chain_impl_mockchain::block::Block;
 use jormungandr_lib::time::SecondsSinceUnixEpoch;
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 const SLOT_START_TIME_UNDEFINED: u64 = u64::max_value();
@@ -20,6 +21,9 @@ struct StatsCounterImpl {
     slot_start_time: AtomicU64,
     tip_block: ArcSwapOption<Block>,
     peers_connected_cnt: AtomicUsize,
+    deep_fork_rejected_cnt: AtomicUsize,
+    reorg_cnt: AtomicUsize,
+    reorg_depths: Mutex<BTreeMap<u32, u64>>,
 }
 
 impl Default for StatsCounterImpl {
@@ -31,6 +35,9 @@ impl Default for StatsCounterImpl {
             slot_start_time: AtomicU64::new(SLOT_START_TIME_UNDEFINED),
             tip_block: ArcSwapOption::from(None),
             peers_connected_cnt: AtomicUsize::default(),
+            deep_fork_rejected_cnt: AtomicUsize::default(),
+            reorg_cnt: AtomicUsize::default(),
+            reorg_depths: Mutex::default(),
         }
     }
 }
@@ -71,6 +78,40 @@ impl StatsCounter {
         self.stats.peers_connected_cnt.load(Ordering::Relaxed)
     }
 
+    pub fn add_deep_fork_rejected_cnt(&self, count: usize) {
+        self.stats
+            .deep_fork_rejected_cnt
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// number of candidate branches rejected because they fork from the
+    /// tip deeper than the epoch stability depth
+    pub fn deep_fork_rejected_cnt(&self) -> u64 {
+        self.stats.deep_fork_rejected_cnt.load(Ordering::Relaxed) as u64
+    }
+
+    /// count a switch of the tip to another branch, `depth` being the
+    /// number of blocks of the previous branch that were rolled back
+    pub fn add_reorg(&self, depth: u32) {
+        self.stats.reorg_cnt.fetch_add(1, Ordering::Relaxed);
+        *self
+            .stats
+            .reorg_depths
+            .lock()
+            .unwrap()
+            .entry(depth)
+            .or_default() += 1;
+    }
+
+    pub fn reorg_cnt(&self) -> u64 {
+        self.stats.reorg_cnt.load(Ordering::Relaxed) as u64
+    }
+
+    /// number of reorgs by depth
+    pub fn reorg_depths(&self) -> BTreeMap<u32, u64> {
+        self.stats.reorg_depths.lock().unwrap().clone()
+    }
+
     pub fn uptime_sec(&self) -> u64 {
         self.stats.start_time.elapsed().as_secs()
     }
@@ -63,10 +63,14 @@ where
     running: Vec<RunningTask<TID, WID>>,
     timeouts: DelayQueue<TimedOutTask<TID, WID>>,
     launcher: Launcher,
+    timeout_handler: Option<TimeoutHandler<TID, WID>>,
     max_running_same_task: usize,
     timeout: Duration,
 }
 
+/// Called with the TID and the WID of the launched tasks that timed out
+pub type TimeoutHandler<TID, WID> = Box<dyn Fn(TID, WID) + Send>;
+
 pub struct FireForgetSchedulerConfig {
     /// How many tasks can be run in parallel
     pub max_running: usize,
@@ -95,11 +99,19 @@ where
             running: Vec::with_capacity(config.max_running),
             timeouts: DelayQueue::with_capacity(config.max_running),
             launcher,
+            timeout_handler: None,
             max_running_same_task: config.max_running_same_task,
             timeout: config.timeout,
         }
     }
 
+    /// Set the handler called when a launched task times out.
+    /// It must be quick and non-blocking.
+    pub fn with_timeout_handler(mut self, handler: TimeoutHandler<TID, WID>) -> Self {
+        self.timeout_handler = Some(handler);
+        self
+    }
+
     pub fn scheduler(&self) -> FireForgetScheduler<TID, WID, Data> {
         self.command_sender.clone()
     }
@@ -136,6 +148,9 @@ where
     fn declare_timed_out(&mut self, timed_out: TimedOutTask<TID, WID>) {
         self.running
             .retain(|running| !running.is_timed_out(&timed_out));
+        if let Some(handler) = &self.timeout_handler {
+            handler(timed_out.tid, timed_out.wid);
+        }
         self.try_run_scheduled();
     }
 
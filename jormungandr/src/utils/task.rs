@@ -171,11 +171,12 @@ impl Services {
     }
 
     /// select on all the started services. this function will block until first services returns
-    pub fn wait_any_finished(self) -> Result<(), ServiceError> {
-        let finish_listener = self.finish_listener;
-        let result = self
-            .runtime
-            .block_on(async move { finish_listener.into_future().await.0 });
+    ///
+    /// The other services keep running, so the runtime can still be used to
+    /// run the shutdown tasks with `block_on_task`.
+    pub fn wait_any_finished(&mut self) -> Result<(), ServiceError> {
+        let finish_listener = &mut self.finish_listener;
+        let result = self.runtime.block_on(finish_listener.next());
         match result {
             // No services were started or some service exited successfully
             None | Some(Ok(Ok(()))) => Ok(()),
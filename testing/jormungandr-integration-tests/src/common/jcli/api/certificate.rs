@@ -27,6 +27,34 @@ impl Certificate {
             .as_single_line()
     }
 
+    pub fn new_update_proposal<S: Into<String>, P: AsRef<Path>>(
+        self,
+        proposer_id: S,
+        changes_file: P,
+    ) -> String {
+        self.command
+            .update_proposal(proposer_id, changes_file)
+            .build()
+            .assert()
+            .success()
+            .get_output()
+            .as_single_line()
+    }
+
+    pub fn new_update_vote<S: Into<String>, Q: Into<String>>(
+        self,
+        proposal_id: S,
+        voter_id: Q,
+    ) -> String {
+        self.command
+            .update_vote(proposal_id, voter_id)
+            .build()
+            .assert()
+            .success()
+            .get_output()
+            .as_single_line()
+    }
+
     pub fn new_public_vote_tally<S: Into<String>>(self, vote_plan_id: S) -> String {
         self.command
             .public_vote_tally(vote_plan_id)
@@ -47,6 +47,32 @@ impl CertificateCommand {
         self
     }
 
+    pub fn update_proposal<S: Into<String>, P: AsRef<Path>>(
+        mut self,
+        proposer_id: S,
+        changes_file: P,
+    ) -> Self {
+        self.command
+            .arg("new")
+            .arg("update-proposal")
+            .arg(proposer_id.into())
+            .arg(changes_file.as_ref());
+        self
+    }
+
+    pub fn update_vote<S: Into<String>, Q: Into<String>>(
+        mut self,
+        proposal_id: S,
+        voter_id: Q,
+    ) -> Self {
+        self.command
+            .arg("new")
+            .arg("update-vote")
+            .arg(proposal_id.into())
+            .arg(voter_id.into());
+        self
+    }
+
     pub fn public_vote_tally<S: Into<String>>(mut self, vote_plan_id: S) -> Self {
         self.command
             .arg("new")
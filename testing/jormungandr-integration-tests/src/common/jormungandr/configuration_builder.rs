@@ -6,12 +6,12 @@ use crate::common::{
 };
 use chain_crypto::Ed25519;
 use chain_impl_mockchain::{chaintypes::ConsensusVersion, fee::LinearFee};
-use jormungandr_lib::crypto::key::KeyPair;
+use jormungandr_lib::crypto::key::{Identifier, KeyPair};
 use jormungandr_lib::interfaces::{
     ActiveSlotCoefficient, CommitteeIdDef, ConsensusLeaderId, EpochStabilityDepth, FeesGoTo,
     Initial, InitialUTxO, KESUpdateSpeed, Log, LogEntry, LogOutput, Mempool, NodeConfig,
-    NodeSecret, NumberOfSlotsPerEpoch, Policy, SignedCertificate, SlotDuration, Tls, TrustedPeer,
-    Value,
+    NodeSecret, NumberOfSlotsPerEpoch, P2pTls, Policy, SignedCertificate, SlotDuration, Tls,
+    TrustedPeer, Value,
 };
 
 use assert_fs::fixture::{ChildPath, PathChild};
@@ -146,6 +146,31 @@ impl ConfigurationBuilder {
         self
     }
 
+    pub fn with_node_key_file(&mut self, node_key_file: &ChildPath) -> &mut Self {
+        self.node_config_builder
+            .with_node_key_file(node_key_file.path().into());
+        self
+    }
+
+    pub fn with_allowed_node_ids(
+        &mut self,
+        allowed_node_ids: Vec<Identifier<Ed25519>>,
+    ) -> &mut Self {
+        self.node_config_builder
+            .with_allowed_node_ids(allowed_node_ids);
+        self
+    }
+
+    pub fn with_private_network(&mut self) -> &mut Self {
+        self.node_config_builder.with_private_network();
+        self
+    }
+
+    pub fn with_p2p_tls_config(&mut self, tls: P2pTls) -> &mut Self {
+        self.node_config_builder.with_p2p_tls_config(tls);
+        self
+    }
+
     pub fn with_rest_tls_config(&mut self, tls: Tls) -> &mut Self {
         self.node_config_builder.with_rest_tls_config(tls);
         self
@@ -21,3 +21,21 @@ pub fn tls_ca_crt() -> PathBuf {
     tls_ca_crt.push("resources/tls/ca.crt");
     tls_ca_crt
 }
+
+pub fn tls_p2p_node_private_key() -> PathBuf {
+    let mut tls_p2p_node_private_key = root_dir();
+    tls_p2p_node_private_key.push("resources/tls/p2p/node.key");
+    tls_p2p_node_private_key
+}
+
+pub fn tls_p2p_node_crt() -> PathBuf {
+    let mut tls_p2p_node_crt = root_dir();
+    tls_p2p_node_crt.push("resources/tls/p2p/node.crt");
+    tls_p2p_node_crt
+}
+
+pub fn tls_p2p_ca_crt() -> PathBuf {
+    let mut tls_p2p_ca_crt = root_dir();
+    tls_p2p_ca_crt.push("resources/tls/p2p/ca.crt");
+    tls_p2p_ca_crt
+}
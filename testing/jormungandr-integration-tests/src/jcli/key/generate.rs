@@ -42,6 +42,13 @@ pub fn test_sumed25519_12_key_generation() {
     assert_ne!(generated_key, "", "generated key is empty");
 }
 
+#[test]
+pub fn test_node_id_key_generation() {
+    let jcli: JCli = Default::default();
+    let generated_key = jcli.key().generate("node-id");
+    assert!(generated_key.starts_with("ed25519_sk"));
+}
+
 #[test]
 pub fn test_unknown_key_type_generation() {
     let jcli: JCli = Default::default();
@@ -1 +1,2 @@
 pub mod start_node;
+pub mod update_proposal;
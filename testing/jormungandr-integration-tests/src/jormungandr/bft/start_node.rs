@@ -1,4 +1,7 @@
-use crate::common::jormungandr::{ConfigurationBuilder, Starter};
+use crate::common::{
+    jcli::JCli,
+    jormungandr::{ConfigurationBuilder, Starter},
+};
 use jormungandr_lib::interfaces::{Log, LogEntry, LogOutput};
 
 use assert_fs::prelude::*;
@@ -91,3 +94,25 @@ pub fn test_jormungandr_without_logger_starts_successfully() {
     let config = ConfigurationBuilder::new().without_log().build(&temp_dir);
     let _jormungandr = Starter::new().config(config).start().unwrap();
 }
+
+#[test]
+pub fn test_jormungandr_node_id_is_loaded_from_node_key_file() {
+    let jcli: JCli = Default::default();
+    let temp_dir = TempDir::new().unwrap();
+    let node_key = jcli.key().generate("node-id");
+    let node_key_file = temp_dir.child("node_key.sk");
+    node_key_file.write_str(&node_key).unwrap();
+    let expected_node_id = jcli.key().convert_to_public_string(&node_key);
+
+    let config = ConfigurationBuilder::new()
+        .with_node_key_file(&node_key_file)
+        .build(&temp_dir);
+
+    // the node keeps its id across restarts
+    for _ in 0..2 {
+        let jormungandr = Starter::new().config(config.clone()).start().unwrap();
+        let stats = jormungandr.rest().stats().unwrap().stats.unwrap();
+        assert_eq!(stats.node_id, expected_node_id);
+        jormungandr.stop();
+    }
+}
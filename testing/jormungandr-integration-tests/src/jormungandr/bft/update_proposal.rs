@@ -0,0 +1,89 @@
+use crate::common::{
+    jcli::JCli,
+    jormungandr::{ConfigurationBuilder, JormungandrProcess, Starter},
+};
+use assert_fs::{fixture::FileWriteStr, prelude::*, TempDir};
+use chain_impl_mockchain::fee::LinearFee;
+use jormungandr_lib::{
+    crypto::key::KeyPair,
+    interfaces::{Mempool, UpdateProposalStatus},
+};
+use jortestkit::process::Wait;
+use rand::rngs::OsRng;
+use std::time::Duration;
+
+fn wait_for_proposal<F>(jormungandr: &JormungandrProcess, predicate: F) -> UpdateProposalStatus
+where
+    F: Fn(&UpdateProposalStatus) -> bool,
+{
+    let mut wait = Wait::new(Duration::from_secs(1), 30);
+    loop {
+        let proposals = jormungandr.rest().update_proposals().unwrap();
+        if let Some(proposal) = proposals.into_iter().find(|p| predicate(p)) {
+            return proposal;
+        }
+        wait.check_timeout()
+            .expect("the update proposal is not in the ledger");
+        wait.advance();
+    }
+}
+
+#[test]
+pub fn fees_are_changed_by_an_update_proposal() {
+    let temp_dir = TempDir::new().unwrap();
+    let jcli: JCli = Default::default();
+    let leader_key_pair = KeyPair::generate(&mut OsRng);
+    let leader_id = leader_key_pair.identifier().to_bech32_str();
+
+    let config = ConfigurationBuilder::new()
+        .with_leader_key_pair(leader_key_pair)
+        .with_slots_per_epoch(10)
+        .with_linear_fees(LinearFee::new(0, 0, 0))
+        .with_mempool(Mempool {
+            accept_update_proposals: true,
+            ..Mempool::default()
+        })
+        .build(&temp_dir);
+    let jormungandr = Starter::new().config(config).start().unwrap();
+
+    let changes_file = temp_dir.child("changes.yaml");
+    changes_file
+        .write_str("linear_fees:\n  constant: 10\n  coefficient: 1\n  certificate: 5\n")
+        .unwrap();
+
+    let proposal = jcli
+        .certificate()
+        .new_update_proposal(&leader_id, changes_file.path());
+    let proposal_id = jcli
+        .rest()
+        .v0()
+        .message()
+        .post(&proposal, jormungandr.rest_uri());
+    let status = wait_for_proposal(&jormungandr, |p| p.proposal_id == proposal_id.into());
+    assert_eq!(status.changes.linear_fees, Some(LinearFee::new(10, 1, 5)));
+    assert!(status.votes.is_empty());
+
+    let vote = jcli
+        .certificate()
+        .new_update_vote(proposal_id.to_string(), &leader_id);
+    jcli.rest()
+        .v0()
+        .message()
+        .post(&vote, jormungandr.rest_uri());
+
+    // with a single leader the vote is a majority, the proposal is adopted at
+    // the start of the next epoch and removed from the ledger
+    let mut wait = Wait::new(Duration::from_secs(1), 30);
+    while jormungandr.rest().settings().unwrap().fees != LinearFee::new(10, 1, 5) {
+        wait.check_timeout()
+            .expect("the fees are not updated by the proposal");
+        wait.advance();
+    }
+    assert!(!jormungandr
+        .rest()
+        .update_proposals()
+        .unwrap()
+        .iter()
+        .any(|p| p.proposal_id == proposal_id.into()));
+    jormungandr.assert_no_errors_in_log();
+}
@@ -4,8 +4,11 @@ use crate::common::{
     startup,
 };
 
+use chain_core::property::{Fragment as _, Serialize};
 use chain_impl_mockchain::{chaintypes::ConsensusType, fee::LinearFee};
-use jormungandr_lib::interfaces::{ActiveSlotCoefficient, BlockDate, Mempool};
+use jormungandr_lib::interfaces::{
+    ActiveSlotCoefficient, BlockDate, CertificateType, FeeEstimateRequest, Mempool,
+};
 use jormungandr_testing_utils::testing::{
     node::time, FragmentGenerator, FragmentSender, FragmentSenderSetup, FragmentVerifier,
     MemPoolCheck,
@@ -31,6 +34,7 @@ pub fn send_all_fragments() {
             .with_mempool(Mempool {
                 pool_max_entries: 1_000_000usize.into(),
                 log_max_entries: 1_000_000usize.into(),
+                ..Mempool::default()
             }),
     )
     .unwrap();
@@ -68,3 +72,134 @@ pub fn send_all_fragments() {
         .wait_and_verify_all_are_in_block(Duration::from_secs(2), mem_checks, &jormungandr)
         .unwrap();
 }
+
+#[test]
+pub fn pending_fragments_are_listed_until_added_to_a_block() {
+    let receiver = startup::create_new_account_address();
+    let mut sender = startup::create_new_account_address();
+
+    let (jormungandr, _) = startup::start_stake_pool(
+        &[sender.clone()],
+        &[receiver.clone()],
+        ConfigurationBuilder::new()
+            .with_slots_per_epoch(60)
+            .with_consensus_genesis_praos_active_slot_coeff(ActiveSlotCoefficient::MAXIMUM)
+            // long slots so the fragment stays in the mempool for a while
+            .with_slot_duration(30)
+            .with_linear_fees(LinearFee::new(1, 1, 1)),
+    )
+    .unwrap();
+
+    let fragment = sender
+        .transaction_to(
+            &jormungandr.genesis_block_hash(),
+            &jormungandr.fees(),
+            receiver.address(),
+            1.into(),
+        )
+        .unwrap();
+    let fragment_id = fragment.id();
+    let rest = jormungandr.rest();
+    let mem_pool_check = rest.send_fragment(fragment.clone()).unwrap();
+
+    let pending = rest
+        .pending_fragments(&format!(
+            "type=transaction&account={}",
+            sender.identifier().to_hex()
+        ))
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].fragment_id, fragment_id.into());
+    assert_eq!(pending[0].inputs.len(), 1);
+    assert_eq!(pending[0].outputs.len(), 1);
+    assert_eq!(pending[0].certificate, None);
+
+    let other_account = rest
+        .pending_fragments(&format!("account={}", receiver.identifier().to_hex()))
+        .unwrap();
+    assert!(other_account.is_empty());
+    let too_recent = rest.pending_fragments("min_age=1h").unwrap();
+    assert!(too_recent.is_empty());
+
+    assert_eq!(
+        rest.pending_fragment(&fragment_id).unwrap(),
+        Some(hex::encode(fragment.serialize_as_vec().unwrap()))
+    );
+
+    FragmentVerifier
+        .wait_and_verify_is_in_block(Duration::from_secs(2), mem_pool_check, &jormungandr)
+        .unwrap();
+    assert_eq!(rest.pending_fragment(&fragment_id).unwrap(), None);
+}
+
+#[test]
+pub fn validated_fragments_are_not_added_to_the_mempool() {
+    let receiver = startup::create_new_account_address();
+    let mut sender = startup::create_new_account_address();
+
+    let (jormungandr, _) = startup::start_stake_pool(
+        &[sender.clone()],
+        &[receiver.clone()],
+        ConfigurationBuilder::new().with_linear_fees(LinearFee::new(1, 1, 1)),
+    )
+    .unwrap();
+
+    let fragment = sender
+        .transaction_to(
+            &jormungandr.genesis_block_hash(),
+            &jormungandr.fees(),
+            receiver.address(),
+            1.into(),
+        )
+        .unwrap();
+    let rest = jormungandr.rest();
+
+    // the second copy spends the same nonce as the first one
+    let validations = rest
+        .validate_fragments(&[fragment.clone(), fragment.clone()])
+        .unwrap();
+    assert_eq!(validations.len(), 2);
+    assert_eq!(validations[0].fragment_id, fragment.id().into());
+    assert!(validations[0].status.is_valid());
+    assert!(!validations[1].status.is_valid());
+
+    assert!(rest.pending_fragments("").unwrap().is_empty());
+    assert!(!rest.fragment_logs().unwrap().contains_key(&fragment.id()));
+}
+
+#[test]
+pub fn fee_is_estimated_with_the_fee_settings_of_the_tip() {
+    let receiver = startup::create_new_account_address();
+    let sender = startup::create_new_account_address();
+
+    let (jormungandr, _) = startup::start_stake_pool(
+        &[sender],
+        &[receiver],
+        ConfigurationBuilder::new().with_linear_fees(LinearFee::new(2, 3, 5)),
+    )
+    .unwrap();
+
+    let estimate = jormungandr
+        .rest()
+        .estimate_fee(&FeeEstimateRequest {
+            inputs: 1,
+            outputs: 2,
+            certificate: Some(CertificateType::StakeDelegation),
+            size: None,
+        })
+        .unwrap();
+    assert_eq!(estimate.fee, 16.into());
+    // the blocks are filled with the oldest fragments first by default
+    assert_eq!(estimate.suggested_fee, None);
+
+    // the update proposals are not carried by a transaction
+    assert!(jormungandr
+        .rest()
+        .estimate_fee(&FeeEstimateRequest {
+            inputs: 1,
+            outputs: 2,
+            certificate: Some(CertificateType::UpdateProposal),
+            size: None,
+        })
+        .is_err());
+}
@@ -1,13 +1,11 @@
 use crate::common::{
-    jcli::JCli,
     jormungandr::{ConfigurationBuilder, Starter},
     startup,
-    transaction_utils::TransactionHash,
 };
 
 use super::setup::{Config, Fixture};
 
-use chain_core::property::FromStr;
+use chain_core::property::{Fragment as _, FromStr};
 use chain_crypto::{Ed25519, PublicKey, Signature, Verification};
 use chain_impl_mockchain::{
     block::Header,
@@ -340,13 +338,13 @@ pub async fn upload_block_nonexisting_stake_pool() {
 #[tokio::test]
 pub async fn get_fragments() {
     let temp_dir = TempDir::new().unwrap();
-    let jcli: JCli = Default::default();
 
     let mut sender = startup::create_new_account_address();
     let receiver = startup::create_new_account_address();
     let output_value = 1u64;
     let config = ConfigurationBuilder::new()
-        .with_slot_duration(4)
+        // long slots so the fragment stays in the mempool for a while
+        .with_slot_duration(30)
         .with_funds(vec![InitialUTxO {
             address: sender.address(),
             value: 100.into(),
@@ -355,22 +353,24 @@ pub async fn get_fragments() {
 
     let server = Starter::new().config(config.clone()).start().unwrap();
 
-    let transaction = sender
+    let fragment = sender
         .transaction_to(
             &server.genesis_block_hash(),
             &server.fees(),
             receiver.address(),
             output_value.into(),
         )
-        .unwrap()
-        .encode();
+        .unwrap();
+    let fragment_id = fragment.id();
+    server.rest().send_fragment(fragment).unwrap();
 
-    let fragment_id = jcli
-        .fragment_sender(&server)
-        .send(&transaction)
-        .assert_in_block();
     let client = Config::attach_to_local_node(config.get_p2p_listen_port()).client();
-    println!("{:?}", client.get_fragments(vec![fragment_id.into()]).await);
+    let fragments = client
+        .get_fragments(vec![fragment_id, TestGen::hash().into()])
+        .await
+        .unwrap();
+    let fragment_ids: Vec<Hash> = fragments.iter().map(|fragment| fragment.id()).collect();
+    assert_eq!(fragment_ids, vec![fragment_id]);
 }
 
 // L1021 PullBlocks correct hashes
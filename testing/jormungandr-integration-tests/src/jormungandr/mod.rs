@@ -4,6 +4,7 @@ pub mod fragments;
 pub mod genesis;
 pub mod grpc;
 pub mod legacy;
+pub mod private_network;
 pub mod recovery;
 pub mod tls;
 pub mod transactions;
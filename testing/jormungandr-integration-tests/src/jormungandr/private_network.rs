@@ -0,0 +1,113 @@
+use crate::common::{
+    jcli::JCli,
+    jormungandr::{ConfigurationBuilder, JormungandrProcess, Starter},
+};
+use assert_fs::{fixture::ChildPath, prelude::*, TempDir};
+use chain_crypto::Ed25519;
+use jormungandr_lib::crypto::key::Identifier;
+use jortestkit::process::{self as process_utils, Wait};
+use std::time::Duration;
+
+struct NodeKey {
+    file: ChildPath,
+    id: Identifier<Ed25519>,
+}
+
+fn node_key(temp_dir: &TempDir, name: &str) -> NodeKey {
+    let jcli: JCli = Default::default();
+    let secret = jcli.key().generate("node-id");
+    let file = temp_dir.child(format!("{}.sk", name));
+    file.write_str(&secret).unwrap();
+    let id = Identifier::from_bech32_str(&jcli.key().convert_to_public_string(&secret)).unwrap();
+    NodeKey { file, id }
+}
+
+fn has_gossiping_peer(jormungandr: &JormungandrProcess) -> bool {
+    jormungandr
+        .rest()
+        .network_stats()
+        .unwrap()
+        .iter()
+        .any(|peer| peer.last_gossip_received.is_some())
+}
+
+/// start a private leader allowing `allowed` and a passive node
+/// authenticating with `passive_key` that trusts the leader
+fn start_private_nodes(
+    temp_dir: &TempDir,
+    allowed: &NodeKey,
+    passive_key: &NodeKey,
+) -> (JormungandrProcess, JormungandrProcess) {
+    let leader_key = node_key(temp_dir, "leader");
+
+    let leader_dir = temp_dir.child("leader");
+    leader_dir.create_dir_all().unwrap();
+    let leader_config = ConfigurationBuilder::new()
+        .with_node_key_file(&leader_key.file)
+        .with_allowed_node_ids(vec![allowed.id.clone()])
+        .with_private_network()
+        .build(&leader_dir);
+    let leader = Starter::new()
+        .config(leader_config.clone())
+        .start()
+        .unwrap();
+
+    let passive_dir = temp_dir.child("passive");
+    passive_dir.create_dir_all().unwrap();
+    let passive_config = ConfigurationBuilder::new()
+        .with_trusted_peers(vec![leader.to_trusted_peer()])
+        .with_block_hash(leader_config.genesis_block_hash())
+        .with_node_key_file(&passive_key.file)
+        .with_allowed_node_ids(vec![leader_key.id])
+        .with_private_network()
+        .build(&passive_dir);
+    let passive = Starter::new()
+        .config(passive_config)
+        .passive()
+        .start()
+        .unwrap();
+
+    (leader, passive)
+}
+
+#[test]
+pub fn private_network_accepts_allowed_node() {
+    let temp_dir = TempDir::new().unwrap();
+    let passive_key = node_key(&temp_dir, "passive");
+    let (leader, _passive) = start_private_nodes(&temp_dir, &passive_key, &passive_key);
+
+    let mut wait = Wait::new(Duration::from_secs(1), 30);
+    while !has_gossiping_peer(&leader) {
+        wait.check_timeout()
+            .expect("the allowed node did not subscribe to the private node");
+        wait.advance();
+    }
+    leader.assert_no_errors_in_log();
+}
+
+#[test]
+pub fn private_network_refuses_node_not_allowed() {
+    let temp_dir = TempDir::new().unwrap();
+    let allowed_key = node_key(&temp_dir, "allowed");
+    let stranger_key = node_key(&temp_dir, "stranger");
+    let (leader, _passive) = start_private_nodes(&temp_dir, &allowed_key, &stranger_key);
+
+    process_utils::sleep(30);
+    assert!(
+        !has_gossiping_peer(&leader),
+        "a node that is not allowed subscribed to the private node"
+    );
+    leader.assert_no_errors_in_log();
+}
+
+#[test]
+pub fn private_network_without_allowed_node_ids_fails_to_start() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = ConfigurationBuilder::new()
+        .with_private_network()
+        .build(&temp_dir);
+
+    Starter::new()
+        .config(config)
+        .start_fail("The node cannot run in a private network without `p2p.allowed_node_ids`");
+}
@@ -1,9 +1,11 @@
 use crate::common::{
-    jormungandr::{ConfigurationBuilder, Starter, StartupVerificationMode},
+    jormungandr::{ConfigurationBuilder, JormungandrProcess, Starter, StartupVerificationMode},
     resources,
 };
-use assert_fs::TempDir;
-use jormungandr_lib::interfaces::Tls;
+use assert_fs::{prelude::*, TempDir};
+use jormungandr_lib::interfaces::{P2pTls, Tls};
+use jortestkit::process::Wait;
+use std::time::Duration;
 
 #[test]
 #[cfg(any(unix, windows))]
@@ -33,3 +35,75 @@ pub fn test_rest_tls_config() {
         jormungandr.secure_rest(&ca_crt_file).stats().unwrap()
     );
 }
+
+fn p2p_tls_config(mutual: bool) -> P2pTls {
+    P2pTls {
+        cert_file: resources::tls_p2p_node_crt(),
+        priv_key_file: resources::tls_p2p_node_private_key(),
+        ca_file: resources::tls_p2p_ca_crt(),
+        domain_name: "localhost".to_owned(),
+        client_ca_file: if mutual {
+            Some(resources::tls_p2p_ca_crt())
+        } else {
+            None
+        },
+    }
+}
+
+/// start a leader and a passive node bootstrapping from it, both with the
+/// given p2p TLS settings, and wait for the passive node to subscribe to the
+/// leader
+fn assert_nodes_connect_over_tls(tls: P2pTls) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let leader_dir = temp_dir.child("leader");
+    leader_dir.create_dir_all().unwrap();
+    let leader_config = ConfigurationBuilder::new()
+        .with_p2p_tls_config(tls.clone())
+        .build(&leader_dir);
+    let leader = Starter::new()
+        .config(leader_config.clone())
+        .start()
+        .unwrap();
+
+    let passive_dir = temp_dir.child("passive");
+    passive_dir.create_dir_all().unwrap();
+    let passive_config = ConfigurationBuilder::new()
+        .with_trusted_peers(vec![leader.to_trusted_peer()])
+        .with_block_hash(leader_config.genesis_block_hash())
+        .with_p2p_tls_config(tls)
+        .build(&passive_dir);
+    let passive = Starter::new()
+        .config(passive_config)
+        .passive()
+        .start()
+        .unwrap();
+
+    let has_gossiping_peer = |jormungandr: &JormungandrProcess| {
+        jormungandr
+            .rest()
+            .network_stats()
+            .unwrap()
+            .iter()
+            .any(|peer| peer.last_gossip_received.is_some())
+    };
+    let mut wait = Wait::new(Duration::from_secs(1), 30);
+    while !has_gossiping_peer(&leader) {
+        wait.check_timeout()
+            .expect("the passive node did not subscribe to the leader over TLS");
+        wait.advance();
+    }
+
+    leader.assert_no_errors_in_log();
+    passive.assert_no_errors_in_log();
+}
+
+#[test]
+pub fn test_p2p_tls_config() {
+    assert_nodes_connect_over_tls(p2p_tls_config(false));
+}
+
+#[test]
+pub fn test_p2p_mutual_tls_config() {
+    assert_nodes_connect_over_tls(p2p_tls_config(true));
+}
@@ -23,6 +23,7 @@ pub fn accounts_funds_are_updated_after_transaction() {
             .with_mempool(Mempool {
                 pool_max_entries: 1_000_000usize.into(),
                 log_max_entries: 1_000_000usize.into(),
+                ..Mempool::default()
             }),
     )
     .unwrap();
@@ -87,3 +88,52 @@ pub fn accounts_funds_are_updated_after_transaction() {
         "receiver value after transaction"
     );
 }
+
+#[test]
+pub fn consecutive_account_transactions_sent_separately_are_accepted() {
+    let jcli: JCli = Default::default();
+    let receiver = startup::create_new_account_address();
+    let mut sender = startup::create_new_account_address();
+
+    let (jormungandr, _) = startup::start_stake_pool(
+        &[sender.clone()],
+        &[receiver.clone()],
+        ConfigurationBuilder::new()
+            .with_slots_per_epoch(20)
+            .with_consensus_genesis_praos_active_slot_coeff(ActiveSlotCoefficient::MAXIMUM)
+            .with_slot_duration(10)
+            .with_mempool(Mempool {
+                ledger_validation: true,
+                ..Mempool::default()
+            }),
+    )
+    .unwrap();
+
+    let first_transaction = sender
+        .transaction_to(
+            &jormungandr.genesis_block_hash(),
+            &jormungandr.fees(),
+            receiver.address(),
+            1.into(),
+        )
+        .unwrap()
+        .encode();
+    sender.confirm_transaction();
+    let second_transaction = sender
+        .transaction_to(
+            &jormungandr.genesis_block_hash(),
+            &jormungandr.fees(),
+            receiver.address(),
+            1.into(),
+        )
+        .unwrap()
+        .encode();
+
+    // the second transaction spends the next counter of the account, it is
+    // sent before the first one is in a block
+    let first = jcli.fragment_sender(&jormungandr).send(&first_transaction);
+    let second = jcli.fragment_sender(&jormungandr).send(&second_transaction);
+
+    first.assert_in_block();
+    second.assert_in_block();
+}
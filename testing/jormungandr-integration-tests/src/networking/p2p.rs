@@ -251,6 +251,40 @@ pub fn node_put_in_quarantine_nodes_which_are_not_whitelisted() {
     );
 }
 
+#[test]
+pub fn unreachable_node_gets_a_low_score() {
+    let mut network_controller = network::builder()
+        .single_trust_direction(CLIENT, SERVER)
+        .initials(vec![
+            wallet("delegated1").with(1_000_000).delegated_to(CLIENT),
+            wallet("delegated2").with(1_000_000).delegated_to(SERVER),
+        ])
+        .build()
+        .unwrap();
+
+    let server = network_controller.spawn_and_wait(SERVER);
+    let client = network_controller.spawn_and_wait(CLIENT);
+
+    server.shutdown();
+
+    process_utils::sleep(10);
+
+    let scores = client
+        .rest()
+        .p2p_scores()
+        .expect("cannot list the peer scores");
+    let server_score = scores
+        .iter()
+        .find(|peer| peer.address == server.address().to_string())
+        .expect("the unreachable node has no score");
+    assert!(
+        server_score.score < 0.0,
+        "the unreachable node has a score of {}",
+        server_score.score
+    );
+    assert_are_in_quarantine(&client, vec![&server], "after shutting down the server");
+}
+
 #[test]
 pub fn node_trust_itself() {
     let mut network_controller = network::builder()
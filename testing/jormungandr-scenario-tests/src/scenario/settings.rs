@@ -297,6 +297,7 @@ impl Prepare for NodeConfig {
             explorer: Explorer::prepare(context),
             bootstrap_from_trusted_peers: None,
             skip_bootstrap: None,
+            trusted_checkpoint: None,
         }
     }
 }
@@ -310,6 +311,7 @@ impl Prepare for Rest {
             listen: context.generate_new_rest_listen_address(),
             tls: None,
             cors: None,
+            admin: None,
         }
     }
 }
@@ -348,6 +350,10 @@ impl Prepare for P2p {
             policy: Some(Policy::prepare(context)),
             layers: None,
             public_id: None,
+            node_key_file: None,
+            allowed_node_ids: None,
+            private_network: false,
+            tls: None,
         }
     }
 }
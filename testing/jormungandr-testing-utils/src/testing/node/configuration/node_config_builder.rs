@@ -2,9 +2,12 @@
 
 use std::path::PathBuf;
 
+use chain_crypto::Ed25519;
 use jormungandr_lib::{
+    crypto::key::Identifier,
     interfaces::{
-        Explorer, Log, Mempool, NodeConfig, P2p, Policy, Rest, Tls, TopicsOfInterest, TrustedPeer,
+        Explorer, Log, Mempool, NodeConfig, P2p, P2pTls, Policy, Rest, Tls, TopicsOfInterest,
+        TrustedPeer,
     },
     time::Duration,
 };
@@ -48,6 +51,7 @@ impl NodeConfigBuilder {
                     .unwrap(),
                 tls: None,
                 cors: None,
+                admin: None,
             },
             p2p: P2p {
                 trusted_peers: vec![],
@@ -66,6 +70,10 @@ impl NodeConfigBuilder {
                 }),
                 layers: None,
                 public_id: None,
+                node_key_file: None,
+                allowed_node_ids: None,
+                private_network: false,
+                tls: None,
             },
             mempool: Some(Mempool::default()),
             explorer: Explorer { enabled: false },
@@ -102,6 +110,29 @@ impl NodeConfigBuilder {
         self
     }
 
+    pub fn with_node_key_file(&mut self, node_key_file: PathBuf) -> &mut Self {
+        self.p2p.node_key_file = Some(node_key_file);
+        self
+    }
+
+    pub fn with_allowed_node_ids(
+        &mut self,
+        allowed_node_ids: Vec<Identifier<Ed25519>>,
+    ) -> &mut Self {
+        self.p2p.allowed_node_ids = Some(allowed_node_ids);
+        self
+    }
+
+    pub fn with_private_network(&mut self) -> &mut Self {
+        self.p2p.private_network = true;
+        self
+    }
+
+    pub fn with_p2p_tls_config(&mut self, tls: P2pTls) -> &mut Self {
+        self.p2p.tls = Some(tls);
+        self
+    }
+
     pub fn with_rest_tls_config(&mut self, tls: Tls) -> &mut Self {
         self.rest.tls = Some(tls);
         self
@@ -127,6 +158,7 @@ impl NodeConfigBuilder {
             explorer: self.explorer.clone(),
             bootstrap_from_trusted_peers: Some(!self.p2p.trusted_peers.is_empty()),
             skip_bootstrap: Some(self.p2p.trusted_peers.is_empty()),
+            trusted_checkpoint: None,
         }
     }
 }
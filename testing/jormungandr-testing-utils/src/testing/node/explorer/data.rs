@@ -90,3 +90,11 @@ pub struct TransactionById;
     response_derives = "Debug"
 )]
 pub struct AllVotePlans;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "resources/explorer/graphql/updateproposals.graphql",
+    schema_path = "resources/explorer/graphql/schema.graphql",
+    response_derives = "Debug"
+)]
+pub struct UpdateProposals;
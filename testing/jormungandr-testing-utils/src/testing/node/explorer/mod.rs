@@ -2,8 +2,9 @@ use self::{
     client::GraphQLClient,
     data::{
         address, all_blocks, all_stake_pools, all_vote_plans, block_by_chain_length, epoch,
-        last_block, stake_pool, status, transaction_by_id, Address, AllBlocks, AllStakePools,
-        AllVotePlans, BlockByChainLength, Epoch, LastBlock, StakePool, Status, TransactionById,
+        last_block, stake_pool, status, transaction_by_id, update_proposals, Address, AllBlocks,
+        AllStakePools, AllVotePlans, BlockByChainLength, Epoch, LastBlock, StakePool, Status,
+        TransactionById, UpdateProposals,
     },
 };
 use chain_impl_mockchain::block::BlockDate as LibBlockDate;
@@ -181,6 +182,17 @@ impl Explorer {
         Ok(response_body)
     }
 
+    pub fn update_proposals(
+        &self,
+    ) -> Result<Response<update_proposals::ResponseData>, ExplorerError> {
+        let query = UpdateProposals::build_query(update_proposals::Variables);
+        self.print_request(&query);
+        let response = self.client.run(query).map_err(ExplorerError::ClientError)?;
+        let response_body = response.json()?;
+        self.print_log(&response_body);
+        Ok(response_body)
+    }
+
     pub fn transaction(
         &self,
         hash: Hash,
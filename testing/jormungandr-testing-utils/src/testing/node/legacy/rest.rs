@@ -7,7 +7,10 @@ use crate::{
 };
 use chain_core::property::Fragment as _;
 use chain_impl_mockchain::fragment::{Fragment, FragmentId};
-use jormungandr_lib::{crypto::hash::Hash, interfaces::FragmentLog};
+use jormungandr_lib::{
+    crypto::hash::Hash,
+    interfaces::{FeeEstimateRequest, FragmentLog},
+};
 use reqwest::blocking::Response;
 use std::collections::HashMap;
 
@@ -114,6 +117,10 @@ impl BackwardCompatibleRest {
         self.raw().p2p_available()?.text()
     }
 
+    pub fn p2p_scores(&self) -> Result<String, reqwest::Error> {
+        self.raw().p2p_scores()?.text()
+    }
+
     pub fn p2p_view(&self) -> Result<String, reqwest::Error> {
         self.raw().p2p_view()?.text()
     }
@@ -127,6 +134,18 @@ impl BackwardCompatibleRest {
         tip.parse().map_err(RestError::HashParseError)
     }
 
+    pub fn pending_fragments(&self, query: &str) -> Result<String, reqwest::Error> {
+        let response_text = self.raw().pending_fragments(query)?.text()?;
+        self.print_response_text(&response_text);
+        Ok(response_text)
+    }
+
+    pub fn pending_fragment(&self, fragment_id: &FragmentId) -> Result<Response, reqwest::Error> {
+        let response = self.raw().pending_fragment(fragment_id)?;
+        self.print_debug_response(&response);
+        Ok(response)
+    }
+
     pub fn settings(&self) -> Result<String, reqwest::Error> {
         self.raw().settings()?.text()
     }
@@ -180,7 +199,25 @@ impl BackwardCompatibleRest {
         Ok(checks)
     }
 
+    pub fn validate_fragments(&self, fragments: &[Fragment]) -> Result<String, reqwest::Error> {
+        let response_text = self.raw().validate_fragments(fragments)?.text()?;
+        self.print_response_text(&response_text);
+        Ok(response_text)
+    }
+
+    pub fn estimate_fee(&self, request: &FeeEstimateRequest) -> Result<String, reqwest::Error> {
+        let response_text = self.raw().estimate_fee(request)?.text()?;
+        self.print_response_text(&response_text);
+        Ok(response_text)
+    }
+
     pub fn vote_plan_statuses(&self) -> Result<String, reqwest::Error> {
         self.raw().vote_plan_statuses()?.text()
     }
+
+    pub fn update_proposals(&self) -> Result<String, reqwest::Error> {
+        let response_text = self.raw().update_proposals()?.text()?;
+        self.print_response_text(&response_text);
+        Ok(response_text)
+    }
 }
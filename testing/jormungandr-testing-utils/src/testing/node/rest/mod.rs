@@ -11,8 +11,10 @@ use chain_impl_mockchain::fragment::{Fragment, FragmentId};
 use jormungandr_lib::{
     crypto::hash::Hash,
     interfaces::{
-        AccountState, EnclaveLeaderId, EpochRewardsInfo, FragmentLog, LeadershipLog, NodeStatsDto,
-        PeerRecord, PeerStats, SettingsDto, StakeDistributionDto, VotePlanStatus,
+        AccountState, EnclaveLeaderId, EpochRewardsInfo, FeeEstimate, FeeEstimateRequest,
+        FragmentLog, FragmentValidation, LeadershipLog, NodeStatsDto, PeerRecord, PeerScore,
+        PeerStats, PendingFragment, SettingsDto, StakeDistributionDto, UpdateProposalStatus,
+        VotePlanStatus,
     },
 };
 use std::collections::HashMap;
@@ -155,6 +157,10 @@ impl JormungandrRest {
         serde_json::from_str(&self.inner.p2p_available()?).map_err(RestError::CannotDeserialize)
     }
 
+    pub fn p2p_scores(&self) -> Result<Vec<PeerScore>, RestError> {
+        serde_json::from_str(&self.inner.p2p_scores()?).map_err(RestError::CannotDeserialize)
+    }
+
     pub fn p2p_view(&self) -> Result<Vec<String>, RestError> {
         serde_json::from_str(&self.inner.p2p_view()?).map_err(RestError::CannotDeserialize)
     }
@@ -167,6 +173,20 @@ impl JormungandrRest {
         self.inner.fragment_logs()
     }
 
+    pub fn pending_fragments(&self, query: &str) -> Result<Vec<PendingFragment>, RestError> {
+        serde_json::from_str(&self.inner.pending_fragments(query)?)
+            .map_err(RestError::CannotDeserialize)
+    }
+
+    /// the hex of the fragment if it is in the mempool of the node
+    pub fn pending_fragment(&self, fragment_id: &FragmentId) -> Result<Option<String>, RestError> {
+        let response = self.inner.pending_fragment(fragment_id)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.text()?))
+    }
+
     pub fn settings(&self) -> Result<SettingsDto, RestError> {
         serde_json::from_str(&self.inner.settings()?).map_err(RestError::CannotDeserialize)
     }
@@ -203,8 +223,25 @@ impl JormungandrRest {
             .map_err(Into::into)
     }
 
+    pub fn validate_fragments(
+        &self,
+        fragments: &[Fragment],
+    ) -> Result<Vec<FragmentValidation>, RestError> {
+        serde_json::from_str(&self.inner.validate_fragments(fragments)?)
+            .map_err(RestError::CannotDeserialize)
+    }
+
+    pub fn estimate_fee(&self, request: &FeeEstimateRequest) -> Result<FeeEstimate, RestError> {
+        serde_json::from_str(&self.inner.estimate_fee(request)?)
+            .map_err(RestError::CannotDeserialize)
+    }
+
     pub fn vote_plan_statuses(&self) -> Result<Vec<VotePlanStatus>, RestError> {
         serde_json::from_str(&self.inner.vote_plan_statuses()?)
             .map_err(RestError::CannotDeserialize)
     }
+
+    pub fn update_proposals(&self) -> Result<Vec<UpdateProposalStatus>, RestError> {
+        serde_json::from_str(&self.inner.update_proposals()?).map_err(RestError::CannotDeserialize)
+    }
 }
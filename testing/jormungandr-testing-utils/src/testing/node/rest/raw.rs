@@ -4,7 +4,8 @@ use bech32::FromBase32;
 use chain_core::property::Serialize;
 use chain_crypto::PublicKey;
 use chain_impl_mockchain::account;
-use chain_impl_mockchain::fragment::Fragment;
+use chain_impl_mockchain::fragment::{Fragment, FragmentId};
+use jormungandr_lib::interfaces::FeeEstimateRequest;
 use jortestkit::process::Wait;
 use reqwest::{
     blocking::Response,
@@ -61,7 +62,15 @@ impl RawRest {
     }
 
     fn get(&self, path: &str) -> Result<reqwest::blocking::Response, reqwest::Error> {
-        let request = self.path(path);
+        self.get_with_version(path, ApiVersion::V0)
+    }
+
+    fn get_with_version(
+        &self,
+        path: &str,
+        api_version: ApiVersion,
+    ) -> Result<reqwest::blocking::Response, reqwest::Error> {
+        let request = format!("{}/{}/{}", self.uri, api_version, path);
         self.print_request_path(&request);
         match &self.settings.certificate {
             None => reqwest::blocking::get(&request),
@@ -76,10 +85,6 @@ impl RawRest {
         }
     }
 
-    fn path(&self, path: &str) -> String {
-        format!("{}/v0/{}", self.uri, path)
-    }
-
     fn path_http_or_https(&self, path: &str, api_version: ApiVersion) -> String {
         if self.settings.use_https_for_post {
             let url = url::Url::parse(&self.uri).unwrap();
@@ -143,6 +148,10 @@ impl RawRest {
         self.get("network/p2p/available")
     }
 
+    pub fn p2p_scores(&self) -> Result<Response, reqwest::Error> {
+        self.get("network/p2p/scores")
+    }
+
     pub fn p2p_view(&self) -> Result<Response, reqwest::Error> {
         self.get("network/p2p/view")
     }
@@ -163,6 +172,18 @@ impl RawRest {
         self.get("fragment/logs")
     }
 
+    /// the pending fragments matching the filters of `query`, a url query
+    /// string such as `type=transaction&min_age=1s`
+    pub fn pending_fragments(&self, query: &str) -> Result<Response, reqwest::Error> {
+        let request = format!("fragments/pending?{}", query);
+        self.get_with_version(&request, ApiVersion::V1)
+    }
+
+    pub fn pending_fragment(&self, fragment_id: &FragmentId) -> Result<Response, reqwest::Error> {
+        let request = format!("fragments/{}", fragment_id);
+        self.get_with_version(&request, ApiVersion::V1)
+    }
+
     pub fn leaders(&self) -> Result<Response, reqwest::Error> {
         self.get("leaders")
     }
@@ -221,10 +242,39 @@ impl RawRest {
             .send()
     }
 
+    pub fn validate_fragments(&self, fragments: &[Fragment]) -> Result<Response, reqwest::Error> {
+        let builder = reqwest::blocking::Client::builder();
+        let client = builder.build()?;
+
+        client
+            .post(&self.path_http_or_https("fragments/validate", ApiVersion::V1))
+            .json(
+                &fragments
+                    .iter()
+                    .map(|x| hex::encode(&x.serialize_as_vec().unwrap()))
+                    .collect::<Vec<String>>(),
+            )
+            .send()
+    }
+
+    pub fn estimate_fee(&self, request: &FeeEstimateRequest) -> Result<Response, reqwest::Error> {
+        let builder = reqwest::blocking::Client::builder();
+        let client = builder.build()?;
+
+        client
+            .post(&self.path_http_or_https("fees/estimate", ApiVersion::V1))
+            .json(request)
+            .send()
+    }
+
     pub fn vote_plan_statuses(&self) -> Result<Response, reqwest::Error> {
         self.get("vote/active/plans")
     }
 
+    pub fn update_proposals(&self) -> Result<Response, reqwest::Error> {
+        self.get("update/proposals")
+    }
+
     pub fn send_until_ok<F>(&self, action: F, mut wait: Wait) -> Result<(), RestError>
     where
         F: Fn(&RawRest) -> Result<Response, reqwest::Error>,